@@ -1,3 +1,9 @@
+use crate::reactor::role::Access;
+use crate::reactor::role::Role;
+use crate::TopicError;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
 use thiserror::Error;
 
 pub mod boolean;
@@ -17,6 +23,39 @@ pub mod std_obj;
 ///
 pub mod ro_stream;
 
+/// RAII registration of callbacks
+///
+pub mod callback_guard;
+pub use callback_guard::CallbackGuard;
+
+/// Background tasks owned by the attributes
+///
+pub mod tasks;
+
+/// Delivery of the received samples to the callbacks
+///
+pub mod dispatcher;
+pub use dispatcher::DispatchConfig;
+pub use dispatcher::DispatchMetrics;
+pub use dispatcher::OverflowPolicy;
+
+/// Number attribute computed from other attributes
+///
+pub mod derived;
+
+/// Statistics, settling detection and resampling of number attributes
+///
+pub mod statistics;
+pub use statistics::Resampled;
+pub use statistics::Resampling;
+pub use statistics::Statistics;
+pub use statistics::TimedValue;
+
+/// Typed values with their header metadata
+///
+pub mod sample;
+pub use sample::Sample;
+
 /// Error type for attribute operations
 #[derive(Error, Debug)]
 pub enum AttributeError {
@@ -24,11 +63,33 @@ pub enum AttributeError {
     NotFound(String),
     #[error("Invalid type: expect:{0} found:{1}")]
     InvalidType(String, String),
+    #[error("Permission denied: role {role} cannot {access} on {topic}")]
+    PermissionDenied {
+        role: Role,
+        access: Access,
+        topic: String,
+    },
+    #[error("Command not confirmed: {0}")]
+    NotConfirmed(String),
+    #[error("Timeout: {0}")]
+    Timeout(String),
+    #[error("Invalid derived attribute: {0}")]
+    InvalidDerived(String),
+    #[error(transparent)]
+    Topic(#[from] TopicError),
+    #[error("Session error: {0}")]
+    Session(String),
 }
 
 /// Unique identifier for callbacks
 pub type CallbackId = u64;
 
+/// Shared storage of the callbacks registered on an attribute
+///
+/// A synchronous mutex is used so that callbacks can be removed from `Drop`.
+/// The lock must never be held across an await point.
+pub(crate) type CallbackStorage<T> = Arc<Mutex<HashMap<CallbackId, CallbackEntry<T>>>>;
+
 /// Type alias for asynchronous callback function with generic type T
 pub type CallbackFn<T> =
     Box<dyn Fn(T) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> + Send + Sync>;
@@ -1,4 +1,7 @@
+use super::sample::Sample;
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::BooleanBuffer;
 use crate::AttributeMetadata;
@@ -14,10 +17,15 @@ pub struct BooleanAttribute {
 impl BooleanAttribute {
     /// Create a new instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        Self {
-            inner: StdObjAttribute::<BooleanBuffer>::new(session, metadata).await,
-        }
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        Ok(Self {
+            inner: StdObjAttribute::<BooleanBuffer>::new(session, metadata).await?,
+        })
     }
 
     /// Send command and do not wait for validation
@@ -39,7 +47,7 @@ impl BooleanAttribute {
     ///
     ///
     #[inline]
-    pub async fn set(&mut self, value: bool) -> Result<(), String> {
+    pub async fn set(&mut self, value: bool) -> Result<(), AttributeError> {
         self.inner
             .set(
                 BooleanBuffer::builder()
@@ -74,10 +82,20 @@ impl BooleanAttribute {
         self.inner.get().await
     }
 
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub async fn sample(&self) -> Option<Sample<bool>> {
+        let (buffer, received_at) = self.inner.get_received().await?;
+        let value = buffer.value()?;
+        Some(Sample::from_buffer(&buffer, value, received_at))
+    }
+
     /// Add a callback that will be triggered when receiving BooleanBuffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
     #[inline]
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(BooleanBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
@@ -1,4 +1,7 @@
+use super::sample::Sample;
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::BytesBuffer;
 use crate::AttributeMetadata;
@@ -13,14 +16,20 @@ pub struct BytesAttribute {
 
 impl BytesAttribute {
     /// Create a new instance
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        let inner = StdObjAttribute::<BytesBuffer>::new(session, metadata).await;
-        Self { inner }
+    ///
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let inner = StdObjAttribute::<BytesBuffer>::new(session, metadata).await?;
+        Ok(Self { inner })
     }
 
     /// Set the value and wait for validation
     #[inline]
-    pub async fn set(&mut self, value: Bytes) -> Result<(), String> {
+    pub async fn set(&mut self, value: Bytes) -> Result<(), AttributeError> {
         self.inner
             .set(
                 BytesBuffer::builder()
@@ -39,6 +48,13 @@ impl BytesAttribute {
         self.inner.get().await
     }
 
+    /// Get the last received value with its timestamp, source and sequence
+    pub async fn sample(&self) -> Option<Sample<Bytes>> {
+        let (buffer, received_at) = self.inner.get_received().await?;
+        let value = buffer.value()?;
+        Some(Sample::from_buffer(&buffer, value, received_at))
+    }
+
     /// Wait for a specific bytes value to be received
     #[inline]
     pub async fn wait_for_value(
@@ -58,7 +74,7 @@ impl BytesAttribute {
     /// Add a callback that will be triggered when receiving BytesBuffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
     #[inline]
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(BytesBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
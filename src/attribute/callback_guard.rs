@@ -0,0 +1,108 @@
+use super::CallbackId;
+
+/// Function called by the guard to deregister its callback
+///
+pub type CallbackRemover = Box<dyn FnOnce(CallbackId) + Send + Sync>;
+
+/// RAII registration of an attribute callback
+///
+/// The callback stays registered as long as the guard lives. When the guard is
+/// dropped the callback is removed synchronously from the attribute, no task or
+/// runtime is needed.
+///
+/// Use [CallbackGuard::detach] to keep the callback registered for the whole
+/// life of the attribute.
+///
+#[must_use = "dropping the guard immediately removes the callback"]
+pub struct CallbackGuard {
+    /// Identifier of the registered callback
+    ///
+    id: CallbackId,
+
+    /// Deregistration function, None once detached
+    ///
+    remover: Option<CallbackRemover>,
+}
+
+impl CallbackGuard {
+    /// Create a new guard
+    ///
+    pub fn new(id: CallbackId, remover: CallbackRemover) -> Self {
+        Self {
+            id,
+            remover: Some(remover),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Identifier of the registered callback
+    ///
+    pub fn id(&self) -> CallbackId {
+        self.id
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Release the guard without removing the callback
+    ///
+    /// The returned identifier can still be used with `remove_callback`.
+    ///
+    pub fn detach(mut self) -> CallbackId {
+        self.remover = None;
+        self.id
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+impl Drop for CallbackGuard {
+    fn drop(&mut self) {
+        if let Some(remover) = self.remover.take() {
+            remover(self.id);
+        }
+    }
+}
+
+impl std::fmt::Debug for CallbackGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackGuard")
+            .field("id", &self.id)
+            .field("detached", &self.remover.is_none())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    fn guard_recording(id: CallbackId) -> (CallbackGuard, Arc<Mutex<Vec<CallbackId>>>) {
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let recorder = removed.clone();
+        let guard = CallbackGuard::new(id, Box::new(move |id| recorder.lock().unwrap().push(id)));
+        (guard, removed)
+    }
+
+    #[test]
+    fn test_drop_removes_callback() {
+        let (guard, removed) = guard_recording(1);
+        assert_eq!(guard.id(), 1);
+        assert!(removed.lock().unwrap().is_empty());
+
+        drop(guard);
+        assert_eq!(*removed.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_detach_keeps_callback() {
+        let (guard, removed) = guard_recording(1);
+
+        let id = guard.detach();
+
+        assert_eq!(id, 1);
+        assert!(removed.lock().unwrap().is_empty());
+    }
+}
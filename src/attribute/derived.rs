@@ -0,0 +1,616 @@
+use super::dispatcher::Dispatcher;
+use super::number::NumberAttribute;
+use super::tasks::AttributeTasks;
+use super::AttributeError;
+use super::CallbackEntry;
+use super::CallbackGuard;
+use super::CallbackId;
+use crate::fbs::NumberBuffer;
+use crate::fbs::PzaBuffer;
+use crate::reactor::Access;
+use crate::reactor::Connection;
+use crate::AttributeMetadata;
+use crate::AttributeMode;
+use crate::Reactor;
+use crate::Topic;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::watch;
+use tokio::sync::Mutex;
+
+/// Function computing the derived value from the source values, in source order
+///
+pub type ComputeFn = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// Topic the derived value is republished on
+///
+struct Publication {
+    /// Metadata of the republished attribute
+    metadata: AttributeMetadata,
+
+    /// Connection followed to publish and answer queries
+    connections: watch::Receiver<Connection>,
+}
+
+/// Builder of a [DerivedAttribute]
+///
+pub struct DerivedAttributeBuilder {
+    /// Name used in logs and task names
+    name: String,
+
+    /// Attributes the value is computed from
+    sources: Vec<NumberAttribute>,
+
+    /// Republication of the value, if any
+    publication: Option<Result<Publication, AttributeError>>,
+
+    /// Reactor stopping the tasks on shutdown, if any
+    reactor: Option<Reactor>,
+}
+
+impl DerivedAttributeBuilder {
+    /// Add a source attribute, its value is given at the next index of the
+    /// compute function input
+    ///
+    pub fn source(mut self, attribute: &NumberAttribute) -> Self {
+        self.sources.push(attribute.clone());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Republish the value on `{topic}/att` of the reactor session
+    ///
+    /// Other clients can then read it as a read-only number attribute. The
+    /// tasks of the attribute are stopped by [Reactor::shutdown].
+    ///
+    /// # Arguments
+    /// * `reactor` - Reactor whose session (and role) is used
+    /// * `topic` - Attribute topic, e.g. `pza/bench/power`
+    pub fn publish_on<T: Into<String>>(mut self, reactor: &Reactor, topic: T) -> Self {
+        let topic = topic.into();
+        let publication = Topic::parse(&topic)
+            .map_err(AttributeError::from)
+            .and_then(|parsed| match reactor.role() {
+                Some(role) if !role.can_publish(&parsed.att_topic()) => {
+                    Err(AttributeError::PermissionDenied {
+                        role,
+                        access: Access::Publish,
+                        topic: parsed.att_topic(),
+                    })
+                }
+                _ => Ok(Publication {
+                    metadata: AttributeMetadata::from_topic(
+                        topic.clone(),
+                        Some("number".to_string()),
+                        AttributeMode::ReadOnly,
+                    ),
+                    connections: reactor.connection_updates(),
+                }),
+            });
+        self.publication = Some(publication);
+        self.reactor = Some(reactor.clone());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Republish the value on `{topic}/att` of the connections published on the channel
+    ///
+    pub fn publish<T: Into<String>>(
+        mut self,
+        connections: watch::Receiver<Connection>,
+        topic: T,
+    ) -> Self {
+        let topic = topic.into();
+        let publication = Topic::parse(&topic)
+            .map_err(AttributeError::from)
+            .map(|_| Publication {
+                metadata: AttributeMetadata::from_topic(
+                    topic,
+                    Some("number".to_string()),
+                    AttributeMode::ReadOnly,
+                ),
+                connections,
+            });
+        self.publication = Some(publication);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Create the attribute, the value is computed as soon as every source has one
+    ///
+    /// # Arguments
+    /// * `compute` - Computes the value from the source values, in source order
+    ///
+    /// # Returns
+    /// An error without source or if the publication topic is not valid
+    pub async fn build<F>(self, compute: F) -> Result<DerivedAttribute, AttributeError>
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        if self.sources.is_empty() {
+            return Err(AttributeError::InvalidDerived(format!(
+                "{} has no source attribute",
+                self.name
+            )));
+        }
+        let publication = self.publication.transpose()?;
+
+        let tasks = AttributeTasks::new(self.name.clone());
+        let output = Output {
+            name: self.name.clone(),
+            compute: Arc::new(compute),
+            last_value: Arc::new(Mutex::new(None)),
+            dispatcher: Dispatcher::new(self.name.clone(), &tasks),
+            publish: publication.as_ref().map(|publication| {
+                (
+                    Topic::parse(&publication.metadata.topic)
+                        .expect("Topic checked by the builder")
+                        .att_topic(),
+                    publication.connections.clone(),
+                )
+            }),
+        };
+
+        // Source updates are forwarded to the compute task
+        let (sender, updates) = unbounded_channel();
+        let mut inputs = Vec::with_capacity(self.sources.len());
+        let mut guards = Vec::with_capacity(self.sources.len());
+        for (index, source) in self.sources.iter().enumerate() {
+            let sender = sender.clone();
+            let guard = source
+                .add_callback(
+                    move |buffer: NumberBuffer| {
+                        if let Some(value) = buffer.value() {
+                            let _ = sender.send((index, value));
+                        }
+                        Box::pin(async {})
+                    },
+                    None::<fn(&NumberBuffer) -> bool>,
+                )
+                .await;
+            guards.push(guard);
+            // Checked after the registration to not miss a value
+            inputs.push(source.get().await.and_then(|buffer| buffer.value()));
+        }
+        output.update(&inputs).await;
+
+        tasks.spawn("compute", output.clone().run(updates, inputs));
+        if let Some((att_topic, connections)) = output.publish.clone() {
+            tasks.spawn(
+                "queryable",
+                serve_queries(att_topic, connections, output.last_value.clone()),
+            );
+        }
+        if let Some(reactor) = &self.reactor {
+            reactor.task_scope().track(&tasks);
+        }
+
+        Ok(DerivedAttribute {
+            name: self.name,
+            metadata: publication.map(|publication| publication.metadata),
+            dispatcher: output.dispatcher,
+            last_value: output.last_value,
+            sources: Arc::new(guards),
+            tasks,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+/// Computation and delivery of the derived value
+///
+#[derive(Clone)]
+struct Output {
+    name: String,
+    compute: ComputeFn,
+    last_value: Arc<Mutex<Option<NumberBuffer>>>,
+    dispatcher: Arc<Dispatcher<NumberBuffer>>,
+
+    /// Attribute topic and connection of the republication
+    publish: Option<(String, watch::Receiver<Connection>)>,
+}
+
+impl Output {
+    /// Body of the compute task: recompute on each source update
+    ///
+    async fn run(self, mut updates: UnboundedReceiver<(usize, f64)>, mut inputs: Vec<Option<f64>>) {
+        while let Some((index, value)) = updates.recv().await {
+            inputs[index] = Some(value);
+            // Updates received meanwhile are merged into one computation
+            while let Ok((index, value)) = updates.try_recv() {
+                inputs[index] = Some(value);
+            }
+            self.update(&inputs).await;
+        }
+    }
+
+    /// Compute, store, publish and dispatch the value if every input is known
+    ///
+    async fn update(&self, inputs: &[Option<f64>]) {
+        let Some(values) = inputs.iter().copied().collect::<Option<Vec<f64>>>() else {
+            return;
+        };
+        let value = match catch_unwind(AssertUnwindSafe(|| (self.compute)(&values))) {
+            Ok(value) => value,
+            Err(_) => {
+                tracing::error!("Computation of {} panicked", self.name);
+                return;
+            }
+        };
+        let buffer = NumberBuffer::builder()
+            .with_random_sequence()
+            .with_source(0)
+            .with_value(value)
+            .build()
+            .expect("Failed to build NumberBuffer");
+
+        *self.last_value.lock().await = Some(buffer.clone());
+        if let Some((att_topic, connections)) = &self.publish {
+            let Connection { session, role } = connections.borrow().clone();
+            match role {
+                // The role may have changed since the builder checked it
+                Some(role) if !role.can_publish(att_topic) => {
+                    tracing::warn!(
+                        "Role {} cannot publish {} on {}",
+                        role,
+                        self.name,
+                        att_topic
+                    )
+                }
+                _ => {
+                    if let Err(e) = session.put(att_topic, buffer.clone().to_zbytes()).await {
+                        tracing::warn!("Failed to publish {} on {}: {}", self.name, att_topic, e);
+                    }
+                }
+            }
+        }
+        self.dispatcher.dispatch(buffer).await;
+    }
+}
+
+/// Answer the initial value queries of the clients reading the republished value
+///
+async fn serve_queries(
+    att_topic: String,
+    mut connections: watch::Receiver<Connection>,
+    last_value: Arc<Mutex<Option<NumberBuffer>>>,
+) {
+    'connections: loop {
+        let session = connections.borrow_and_update().session.clone();
+        match session.declare_queryable(&att_topic).await {
+            Ok(queryable) => loop {
+                tokio::select! {
+                    query = queryable.recv_async() => match query {
+                        Ok(query) => {
+                            let last = last_value.lock().await.clone();
+                            if let Some(buffer) = last {
+                                if let Err(e) = query.reply(&att_topic, buffer.to_zbytes()).await {
+                                    tracing::warn!("Failed to answer query on {}: {}", att_topic, e);
+                                }
+                            }
+                        }
+                        // Session closed
+                        Err(_) => break,
+                    },
+                    changed = connections.changed() => match changed {
+                        Ok(()) => continue 'connections,
+                        Err(_) => return,
+                    },
+                }
+            },
+            Err(e) => tracing::error!("Failed to declare queryable on {}: {}", att_topic, e),
+        }
+
+        // The session cannot be used anymore, wait for the next one
+        if connections.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Number attribute computed from other number attributes
+///
+/// The value is recomputed each time a source receives a value, once every
+/// source has one. It can be republished on a Zenoh topic so that other clients
+/// read it as a regular read-only number attribute.
+///
+/// ```ignore
+/// let power = DerivedAttribute::builder("power")
+///     .source(&voltage)
+///     .source(&current)
+///     .publish_on(&reactor, "pza/bench/power")
+///     .build(|values| values[0] * values[1])
+///     .await?;
+/// ```
+///
+/// The sources are followed as long as a handle (or clone) of the attribute lives.
+///
+#[derive(Clone, Debug)]
+pub struct DerivedAttribute {
+    /// Name used in logs and task names
+    name: String,
+
+    /// Metadata of the republished attribute, if any
+    metadata: Option<AttributeMetadata>,
+
+    /// Callbacks on the derived value
+    dispatcher: Arc<Dispatcher<NumberBuffer>>,
+
+    /// Last computed value
+    last_value: Arc<Mutex<Option<NumberBuffer>>>,
+
+    /// Callbacks on the sources, removed with the last handle
+    sources: Arc<Vec<CallbackGuard>>,
+
+    /// Compute and queryable tasks, aborted with the last handle
+    tasks: Arc<AttributeTasks>,
+}
+
+impl DerivedAttribute {
+    /// Start the definition of a derived attribute
+    ///
+    pub fn builder<N: Into<String>>(name: N) -> DerivedAttributeBuilder {
+        DerivedAttributeBuilder {
+            name: name.into(),
+            sources: Vec::new(),
+            publication: None,
+            reactor: None,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last computed value
+    ///
+    pub async fn get(&self) -> Option<NumberBuffer> {
+        self.last_value.lock().await.clone()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait for a computed value matching the condition
+    ///
+    pub async fn wait_for<F>(
+        &self,
+        condition: F,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<f64, String>
+    where
+        F: Fn(f64) -> bool + Send + Sync + 'static,
+    {
+        let condition = Arc::new(condition);
+        let accepts = {
+            let condition = condition.clone();
+            move |buffer: &NumberBuffer| buffer.value().is_some_and(|value| condition(value))
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _guard = self
+            .add_callback(
+                move |buffer: NumberBuffer| {
+                    let _ = tx.send(buffer);
+                    Box::pin(async {})
+                },
+                Some(accepts),
+            )
+            .await;
+
+        // Checked after the registration to not miss a value computed meanwhile
+        if let Some(value) = self
+            .get()
+            .await
+            .and_then(|buffer| buffer.value())
+            .filter(|value| condition(*value))
+        {
+            return Ok(value);
+        }
+
+        let received = match timeout {
+            Some(duration) => tokio::time::timeout(duration, rx.recv())
+                .await
+                .map_err(|_| "Timeout waiting for value".to_string())?,
+            None => rx.recv().await,
+        };
+        received
+            .and_then(|buffer| buffer.value())
+            .ok_or_else(|| "Channel closed unexpectedly".to_string())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait for a specific computed value
+    ///
+    pub async fn wait_for_value(
+        &self,
+        value: f64,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<(), String> {
+        self.wait_for(move |v| v == value, timeout)
+            .await
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each computed value
+    /// Optionally, a condition can be provided to filter when the callback is triggered
+    ///
+    /// The callback is removed when the returned guard is dropped.
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
+    where
+        F: Fn(NumberBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+        C: Fn(&NumberBuffer) -> bool + Send + Sync + 'static,
+    {
+        self.dispatcher.register(CallbackEntry {
+            callback: Box::new(callback),
+            condition: condition
+                .map(|c| Box::new(c) as Box<dyn Fn(&NumberBuffer) -> bool + Send + Sync>),
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Remove a callback by its ID
+    ///
+    pub async fn remove_callback(&self, callback_id: CallbackId) -> bool {
+        self.dispatcher.remove(callback_id)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Name of the attribute
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Metadata of the republished attribute, None if not republished
+    ///
+    pub fn metadata(&self) -> Option<&AttributeMetadata> {
+        self.metadata.as_ref()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of source attributes
+    ///
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of background tasks still running (compute, queryable and callback
+    /// workers)
+    ///
+    pub fn running_tasks(&self) -> usize {
+        self.tasks.running()
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::platform_and_client;
+    use crate::test_support::wait_for_queryable;
+    use crate::test_support::wait_for_subscriber;
+    use std::time::Duration;
+    use zenoh::Session;
+
+    fn number(value: f64) -> zenoh::bytes::ZBytes {
+        NumberBuffer::builder()
+            .with_value(value)
+            .with_source(0)
+            .with_random_sequence()
+            .build()
+            .unwrap()
+            .to_zbytes()
+    }
+
+    async fn source(session: &Session, topic: &str) -> NumberAttribute {
+        let metadata = AttributeMetadata::from_topic(
+            topic.to_string(),
+            Some("number".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        NumberAttribute::new(session.clone(), metadata)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn power_from_voltage_and_current() {
+        let (platform, client, _) = platform_and_client().await;
+
+        let voltage = source(&client, "pza/test/derived/voltage").await;
+        let current = source(&client, "pza/test/derived/current").await;
+        assert!(matches!(
+            DerivedAttribute::builder("empty").build(|_| 0.0).await,
+            Err(AttributeError::InvalidDerived(_))
+        ));
+
+        let (connections, _) = watch::channel(Connection::new(client.clone()));
+        let power = DerivedAttribute::builder("power")
+            .source(&voltage)
+            .source(&current)
+            .publish(connections.subscribe(), "pza/test/derived/power")
+            .build(|values| values[0] * values[1])
+            .await
+            .unwrap();
+        assert_eq!(power.source_count(), 2);
+        assert_eq!(power.running_tasks(), 2);
+        wait_for_subscriber(&platform, "pza/test/derived/voltage/att").await;
+        wait_for_subscriber(&platform, "pza/test/derived/current/att").await;
+        wait_for_queryable(&platform, "pza/test/derived/power/att").await;
+
+        // No value until every source has one
+        platform
+            .put("pza/test/derived/voltage/att", number(2.0))
+            .await
+            .unwrap();
+        voltage
+            .wait_for_value(2.0, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert!(power.get().await.is_none());
+
+        platform
+            .put("pza/test/derived/current/att", number(3.0))
+            .await
+            .unwrap();
+        power
+            .wait_for_value(6.0, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+
+        // Other clients read the republished value
+        let reply = platform
+            .get("pza/test/derived/power/att")
+            .await
+            .unwrap()
+            .recv_async()
+            .await
+            .unwrap();
+        let published = NumberBuffer::from_zbytes(reply.result().unwrap().payload().clone());
+        assert_eq!(published.value(), Some(6.0));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _guard = power
+            .add_callback(
+                move |buffer: NumberBuffer| {
+                    let _ = tx.send(buffer.value());
+                    Box::pin(async {})
+                },
+                None::<fn(&NumberBuffer) -> bool>,
+            )
+            .await;
+        platform
+            .put("pza/test/derived/current/att", number(4.0))
+            .await
+            .unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(received, Some(Some(8.0)));
+
+        // Dropping the attribute releases the sources
+        assert_eq!(voltage.inner.callback_count().await, 1);
+        drop(power);
+        assert_eq!(voltage.inner.callback_count().await, 0);
+        assert_eq!(current.inner.callback_count().await, 0);
+    }
+}
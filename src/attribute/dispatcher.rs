@@ -0,0 +1,665 @@
+use super::tasks::AttributeTasks;
+use super::CallbackEntry;
+use super::CallbackGuard;
+use super::CallbackId;
+use super::CallbackStorage;
+use futures::FutureExt;
+use std::any::Any;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use tokio::sync::Notify;
+
+/// Default number of samples queued for each callback
+///
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// What happens to a sample when the queue of a callback is full
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// The oldest queued sample is dropped to make room
+    #[default]
+    DropOldest,
+    /// The new sample is dropped
+    DropNewest,
+    /// The receive loop waits for room, delaying the other callbacks of the attribute
+    Block,
+}
+
+/// Queue settings applied to the callbacks of an attribute
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchConfig {
+    /// Number of samples queued for each callback (at least 1)
+    pub capacity: usize,
+    /// Behavior when a queue is full
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for DispatchConfig {
+    /// 64 samples, the oldest one is dropped when a queue is full
+    ///
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl DispatchConfig {
+    /// Queue of `capacity` samples with the given overflow policy
+    ///
+    pub fn new(capacity: usize, overflow: OverflowPolicy) -> Self {
+        Self { capacity, overflow }
+    }
+}
+
+/// Counters of the samples given to callbacks
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DispatchMetrics {
+    /// Samples processed by the callbacks
+    pub delivered: u64,
+    /// Samples dropped because a queue was full
+    pub dropped: u64,
+    /// Callback runs (or conditions) that panicked
+    pub panicked: u64,
+    /// Samples waiting in the queues
+    pub queued: usize,
+}
+
+/// Shared counters, kept when callbacks are removed
+///
+#[derive(Debug, Default)]
+struct Counters {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    panicked: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self, queued: usize) -> DispatchMetrics {
+        DispatchMetrics {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            panicked: self.panicked.load(Ordering::Relaxed),
+            queued,
+        }
+    }
+}
+
+/// Bounded queue between the receive loop and the worker of one callback
+///
+struct Lane<T> {
+    config: DispatchConfig,
+    queue: Mutex<VecDeque<T>>,
+    closed: AtomicBool,
+
+    /// Wakes the worker when a sample is queued or the lane is closed
+    item: Notify,
+
+    /// Wakes the receive loop when a sample leaves a full queue
+    space: Notify,
+
+    counters: Counters,
+}
+
+impl<T> Lane<T> {
+    fn new(config: DispatchConfig) -> Self {
+        Self {
+            config: DispatchConfig {
+                capacity: config.capacity.max(1),
+                ..config
+            },
+            queue: Mutex::new(VecDeque::new()),
+            closed: AtomicBool::new(false),
+            item: Notify::new(),
+            space: Notify::new(),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Queue a sample according to the overflow policy
+    ///
+    /// # Returns
+    /// True if a sample was dropped
+    async fn push(&self, item: T) -> bool {
+        let mut item = Some(item);
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if self.closed.load(Ordering::Acquire) {
+                    return false;
+                }
+                if queue.len() < self.config.capacity {
+                    queue.extend(item.take());
+                    self.item.notify_one();
+                    return false;
+                }
+                match self.config.overflow {
+                    OverflowPolicy::DropNewest => return true,
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        queue.extend(item.take());
+                        self.item.notify_one();
+                        return true;
+                    }
+                    OverflowPolicy::Block => {}
+                }
+            }
+            self.space.notified().await;
+        }
+    }
+
+    /// Next sample, None once the lane is closed
+    ///
+    async fn pop(&self) -> Option<T> {
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if self.closed.load(Ordering::Acquire) {
+                    return None;
+                }
+                if let Some(item) = queue.pop_front() {
+                    self.space.notify_one();
+                    return Some(item);
+                }
+            }
+            self.item.notified().await;
+        }
+    }
+
+    /// Stop the worker and release a blocked receive loop
+    ///
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.queue.lock().unwrap().clear();
+        self.item.notify_one();
+        self.space.notify_one();
+    }
+
+    fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+}
+
+/// Message of a panic payload
+///
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Delivers the samples of an attribute to its callbacks
+///
+/// Each callback has its own bounded queue and worker task: a slow callback
+/// only delays itself (unless the [OverflowPolicy::Block] policy is used and
+/// its queue is full), samples are processed in order, and a panic is caught,
+/// logged and counted without stopping the delivery.
+///
+pub struct Dispatcher<T> {
+    /// Topic of the attribute, used in logs
+    name: String,
+
+    /// Registered callbacks
+    callbacks: CallbackStorage<T>,
+
+    /// Queue of each registered callback
+    lanes: Mutex<HashMap<CallbackId, Arc<Lane<T>>>>,
+
+    /// Next callback ID
+    next_callback_id: AtomicU64,
+
+    /// Settings of the queues created from now on
+    config: Mutex<DispatchConfig>,
+
+    /// Counters of the removed callbacks and of the conditions
+    counters: Counters,
+
+    /// Tasks of the attribute, the workers run among them
+    tasks: Weak<AttributeTasks>,
+}
+
+impl<T> std::fmt::Debug for Dispatcher<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("name", &self.name)
+            .field("callbacks", &self.lanes.lock().unwrap().len())
+            .field("config", &*self.config.lock().unwrap())
+            .finish()
+    }
+}
+
+impl<T: Clone + Send + 'static> Dispatcher<T> {
+    /// Create a dispatcher without callbacks
+    ///
+    /// The workers of the callbacks are spawned in `tasks`, so they are stopped
+    /// with the other tasks of the attribute.
+    pub fn new<N: Into<String>>(name: N, tasks: &Arc<AttributeTasks>) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.into(),
+            callbacks: Arc::new(Mutex::new(HashMap::new())),
+            lanes: Mutex::new(HashMap::new()),
+            next_callback_id: AtomicU64::new(0),
+            config: Mutex::new(DispatchConfig::default()),
+            counters: Counters::default(),
+            tasks: Arc::downgrade(tasks),
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Settings of the queues of the callbacks registered from now on
+    ///
+    pub fn set_config(&self, config: DispatchConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Settings of the queues of new callbacks
+    ///
+    pub fn config(&self) -> DispatchConfig {
+        *self.config.lock().unwrap()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Register a callback and start its worker
+    ///
+    /// The worker is the `callback/{id}` task of the attribute. The callback is
+    /// removed when the returned guard is dropped.
+    pub fn register(self: &Arc<Self>, entry: CallbackEntry<T>) -> CallbackGuard {
+        let id = self.next_callback_id.fetch_add(1, Ordering::Relaxed);
+        let lane = Arc::new(Lane::new(self.config()));
+
+        self.callbacks.lock().unwrap().insert(id, entry);
+        self.lanes.lock().unwrap().insert(id, lane.clone());
+        match self.tasks.upgrade() {
+            Some(tasks) => tasks.spawn(
+                format!("callback/{}", id),
+                Self::run_worker(self.name.clone(), id, Arc::downgrade(&self.callbacks), lane),
+            ),
+            // The attribute is being dropped, nothing will be dispatched
+            None => lane.close(),
+        }
+
+        let dispatcher = Arc::downgrade(self);
+        CallbackGuard::new(
+            id,
+            Box::new(move |id| {
+                if let Some(dispatcher) = dispatcher.upgrade() {
+                    dispatcher.remove(id);
+                }
+            }),
+        )
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Body of the worker of a callback: run it on each queued sample
+    ///
+    async fn run_worker(
+        name: String,
+        id: CallbackId,
+        callbacks: Weak<Mutex<HashMap<CallbackId, CallbackEntry<T>>>>,
+        lane: Arc<Lane<T>>,
+    ) {
+        while let Some(item) = lane.pop().await {
+            // Only the creation of the future happens under the lock, a panic
+            // is caught before it can poison it
+            let future = match callbacks.upgrade() {
+                Some(callbacks) => {
+                    let callbacks = callbacks.lock().unwrap();
+                    match callbacks.get(&id) {
+                        Some(entry) => catch_unwind(AssertUnwindSafe(|| (entry.callback)(item))),
+                        None => return,
+                    }
+                }
+                None => return,
+            };
+
+            let result = match future {
+                Ok(future) => AssertUnwindSafe(future).catch_unwind().await,
+                Err(payload) => Err(payload),
+            };
+            match result {
+                Ok(()) => {
+                    lane.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(payload) => {
+                    lane.counters.panicked.fetch_add(1, Ordering::Relaxed);
+                    tracing::error!(
+                        "Callback {} of {} panicked: {}",
+                        id,
+                        name,
+                        panic_message(payload.as_ref())
+                    );
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Queue a sample for each callback whose condition accepts it
+    ///
+    /// Returns once the sample is queued, which only waits for callbacks using
+    /// the [OverflowPolicy::Block] policy with a full queue.
+    pub async fn dispatch(&self, item: T) {
+        let accepted: Vec<CallbackId> = {
+            let callbacks = self.callbacks.lock().unwrap();
+            callbacks
+                .iter()
+                .filter(|(id, entry)| match &entry.condition {
+                    Some(condition) => match catch_unwind(AssertUnwindSafe(|| condition(&item))) {
+                        Ok(accepted) => accepted,
+                        Err(payload) => {
+                            self.counters.panicked.fetch_add(1, Ordering::Relaxed);
+                            tracing::error!(
+                                "Condition of callback {} of {} panicked: {}",
+                                id,
+                                self.name,
+                                panic_message(payload.as_ref())
+                            );
+                            false
+                        }
+                    },
+                    None => true,
+                })
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        let lanes: Vec<(CallbackId, Arc<Lane<T>>)> = {
+            let lanes = self.lanes.lock().unwrap();
+            accepted
+                .into_iter()
+                .filter_map(|id| lanes.get(&id).map(|lane| (id, lane.clone())))
+                .collect()
+        };
+
+        for (id, lane) in lanes {
+            if lane.push(item.clone()).await {
+                let dropped = lane.counters.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // Log the first drop then every 1000 to avoid flooding
+                if dropped % 1000 == 1 {
+                    tracing::warn!(
+                        "Queue of callback {} of {} is full, {} sample(s) dropped",
+                        id,
+                        self.name,
+                        dropped
+                    );
+                }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Remove a callback and stop its worker
+    ///
+    /// A run already started is not interrupted, queued samples are dropped.
+    pub fn remove(&self, id: CallbackId) -> bool {
+        let removed = self.callbacks.lock().unwrap().remove(&id).is_some();
+        if let Some(lane) = self.lanes.lock().unwrap().remove(&id) {
+            self.retire(&lane);
+        }
+        removed
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Remove all the callbacks
+    ///
+    pub fn clear(&self) {
+        self.callbacks.lock().unwrap().clear();
+        for (_, lane) in self.lanes.lock().unwrap().drain() {
+            self.retire(&lane);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Close a lane and keep its counters in the dispatcher ones
+    ///
+    fn retire(&self, lane: &Lane<T>) {
+        lane.close();
+        let metrics = lane.counters.snapshot(0);
+        self.counters
+            .delivered
+            .fetch_add(metrics.delivered, Ordering::Relaxed);
+        self.counters
+            .dropped
+            .fetch_add(metrics.dropped, Ordering::Relaxed);
+        self.counters
+            .panicked
+            .fetch_add(metrics.panicked, Ordering::Relaxed);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of registered callbacks
+    ///
+    pub fn count(&self) -> usize {
+        self.callbacks.lock().unwrap().len()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Counters of all the callbacks, including the removed ones
+    ///
+    /// A run still in progress when its callback is removed is not counted.
+    pub fn metrics(&self) -> DispatchMetrics {
+        let lanes = self.lanes.lock().unwrap();
+        lanes
+            .values()
+            .fold(self.counters.snapshot(0), |mut total, lane| {
+                let metrics = lane.counters.snapshot(lane.len());
+                total.delivered += metrics.delivered;
+                total.dropped += metrics.dropped;
+                total.panicked += metrics.panicked;
+                total.queued += metrics.queued;
+                total
+            })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Counters of a registered callback
+    ///
+    pub fn callback_metrics(&self, id: CallbackId) -> Option<DispatchMetrics> {
+        self.lanes
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|lane| lane.counters.snapshot(lane.len()))
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+impl<T> Drop for Dispatcher<T> {
+    fn drop(&mut self) {
+        for (_, lane) in self.lanes.lock().unwrap().drain() {
+            lane.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn entry<F>(callback: F) -> CallbackEntry<u32>
+    where
+        F: Fn(u32) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        CallbackEntry {
+            callback: Box::new(callback),
+            condition: None,
+        }
+    }
+
+    /// Wait until the dispatcher has delivered `expected` samples
+    async fn wait_delivered(dispatcher: &Dispatcher<u32>, expected: u64) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while dispatcher.metrics().delivered < expected {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn slow_callback_does_not_stall_others() {
+        let tasks = AttributeTasks::new("pza/test/dispatch");
+        let dispatcher = Dispatcher::<u32>::new("pza/test/dispatch", &tasks);
+        dispatcher.set_config(DispatchConfig::new(2, OverflowPolicy::DropOldest));
+
+        let (gate, _) = tokio::sync::watch::channel(false);
+        let slow_seen = Arc::new(Mutex::new(Vec::new()));
+        let slow = dispatcher.register(entry({
+            let gate = gate.subscribe();
+            let seen = slow_seen.clone();
+            move |value| {
+                let mut gate = gate.clone();
+                let seen = seen.clone();
+                Box::pin(async move {
+                    let _ = gate.wait_for(|open| *open).await;
+                    seen.lock().unwrap().push(value);
+                })
+            }
+        }));
+        let fast_seen = Arc::new(Mutex::new(Vec::new()));
+        let _fast = dispatcher.register(entry({
+            let seen = fast_seen.clone();
+            move |value| {
+                seen.lock().unwrap().push(value);
+                Box::pin(async {})
+            }
+        }));
+        let _panicking = dispatcher.register(entry(|value| {
+            if value == 3 {
+                panic!("callback failure");
+            }
+            Box::pin(async {})
+        }));
+
+        for value in 0..10 {
+            dispatcher.dispatch(value).await;
+            tokio::task::yield_now().await;
+        }
+        // The fast callback gets everything while the slow one is stuck
+        wait_delivered(&dispatcher, 10 + 9).await;
+        assert_eq!(*fast_seen.lock().unwrap(), (0..10).collect::<Vec<_>>());
+
+        // Only the last samples were kept for the slow one
+        let slow_metrics = dispatcher.callback_metrics(slow.id()).unwrap();
+        assert_eq!(slow_metrics.queued, 2);
+        assert_eq!(slow_metrics.dropped, 7);
+        gate.send_replace(true);
+        wait_delivered(&dispatcher, 10 + 9 + 3).await;
+        assert_eq!(*slow_seen.lock().unwrap(), vec![0, 8, 9]);
+
+        let metrics = dispatcher.metrics();
+        assert_eq!(metrics.panicked, 1);
+        assert_eq!(metrics.dropped, 7);
+
+        // Removed callbacks keep their counters in the total
+        drop(slow);
+        assert_eq!(dispatcher.count(), 2);
+        assert_eq!(dispatcher.metrics(), metrics);
+    }
+
+    #[tokio::test]
+    async fn block_and_drop_newest_policies() {
+        let tasks = AttributeTasks::new("pza/test/dispatch");
+        let dispatcher = Dispatcher::<u32>::new("pza/test/dispatch", &tasks);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let register = |config| {
+            dispatcher.set_config(config);
+            let seen = seen.clone();
+            dispatcher.register(entry(move |value| {
+                let seen = seen.clone();
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    seen.lock().unwrap().push(value);
+                })
+            }))
+        };
+
+        // Nothing is lost when the receive loop waits
+        let blocking = register(DispatchConfig::new(1, OverflowPolicy::Block));
+        for value in 0..20 {
+            dispatcher.dispatch(value).await;
+        }
+        wait_delivered(&dispatcher, 20).await;
+        assert_eq!(*seen.lock().unwrap(), (0..20).collect::<Vec<_>>());
+        drop(blocking);
+
+        // The new samples are refused when the queue is full
+        seen.lock().unwrap().clear();
+        let _newest = register(DispatchConfig::new(1, OverflowPolicy::DropNewest));
+        for value in 0..20 {
+            dispatcher.dispatch(value).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let seen = seen.lock().unwrap().clone();
+        assert_eq!(seen.first(), Some(&0));
+        assert!(seen.len() < 20);
+        assert_eq!(
+            dispatcher.metrics().dropped,
+            20 - seen.len() as u64,
+            "each sample is either delivered or dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn workers_are_attribute_tasks() {
+        let tasks = AttributeTasks::new("pza/test/dispatch");
+        let dispatcher = Dispatcher::<u32>::new("pza/test/dispatch", &tasks);
+        assert_eq!(dispatcher.config().overflow, OverflowPolicy::DropOldest);
+
+        let first = dispatcher.register(entry(|_| Box::pin(async {})));
+        let _second = dispatcher.register(entry(|_| Box::pin(async {})));
+        assert_eq!(tasks.running(), 2);
+        dispatcher.dispatch(1).await;
+        wait_delivered(&dispatcher, 2).await;
+
+        // A removed callback ends its worker
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while tasks.running() != 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Stopping the attribute tasks stops the workers
+        tasks.abort_all();
+        tokio::task::yield_now().await;
+        assert_eq!(tasks.running(), 0);
+        dispatcher.dispatch(2).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(dispatcher.metrics().delivered, 2);
+    }
+}
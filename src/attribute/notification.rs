@@ -1,11 +1,45 @@
 use super::ro_stream::RoStreamAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::NotificationBuffer;
+use crate::fbs::NotificationType;
 use crate::AttributeMetadata;
+use futures::FutureExt;
 use zenoh::Session;
 
+pub mod notification_filter;
 pub mod notification_pack;
 
+pub use notification_filter::NotificationFilter;
+
+/// Emit a notification into the `tracing` ecosystem
+///
+/// Info is logged as INFO, Warning and Alert as WARN, Error as ERROR.
+/// Notifications with a malformed type are logged as WARN.
+///
+pub fn log_notification(notification: &NotificationBuffer) {
+    let source = notification.source_str().unwrap_or("");
+    let message = notification.message_str().unwrap_or("");
+    match notification.notification_type() {
+        Some(NotificationType::Info) => {
+            tracing::info!(target: "panduza::notification", source, "{}", message)
+        }
+        Some(NotificationType::Warning) => {
+            tracing::warn!(target: "panduza::notification", source, "{}", message)
+        }
+        Some(NotificationType::Alert) => {
+            tracing::warn!(target: "panduza::notification", source, alert = true, "{}", message)
+        }
+        Some(NotificationType::Error) => {
+            tracing::error!(target: "panduza::notification", source, "{}", message)
+        }
+        None => {
+            tracing::warn!(target: "panduza::notification", source, "malformed notification type: {}", message)
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Object to manage the NotificationAttribute
 ///
@@ -16,9 +50,14 @@ pub struct NotificationAttribute {
 impl NotificationAttribute {
     /// Create a new instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        let inner = RoStreamAttribute::new(session, metadata).await;
-        Self { inner }
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let inner = RoStreamAttribute::new(session, metadata).await?;
+        Ok(Self { inner })
     }
 
     /// Wait for a specific notification value to be received
@@ -41,7 +80,7 @@ impl NotificationAttribute {
     /// Add a callback that will be triggered when receiving NotificationBuffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
     #[inline]
-    pub async fn add_callback<F>(&self, callback: F) -> CallbackId
+    pub async fn add_callback<F>(&self, callback: F) -> CallbackGuard
     where
         F: Fn(
                 NotificationBuffer,
@@ -62,7 +101,7 @@ impl NotificationAttribute {
         &self,
         callback: F,
         condition: Option<C>,
-    ) -> CallbackId
+    ) -> CallbackGuard
     where
         F: Fn(
                 NotificationBuffer,
@@ -75,6 +114,55 @@ impl NotificationAttribute {
         self.inner.add_callback(callback, condition).await
     }
 
+    /// Add a callback triggered only for notifications that pass the filter
+    ///
+    #[inline]
+    pub async fn add_filtered_callback<F>(
+        &self,
+        filter: NotificationFilter,
+        callback: F,
+    ) -> CallbackGuard
+    where
+        F: Fn(
+                NotificationBuffer,
+            ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.inner
+            .add_callback(
+                callback,
+                Some(move |n: &NotificationBuffer| filter.matches(n)),
+            )
+            .await
+    }
+
+    /// Wait for a notification that passes the filter
+    ///
+    pub async fn wait_for_notification(
+        &self,
+        filter: NotificationFilter,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<NotificationBuffer, String> {
+        self.inner
+            .wait_for_value(move |n| filter.matches(n), timeout)
+            .await
+    }
+
+    /// Forward the notifications that pass the filter to the `tracing` ecosystem
+    ///
+    /// See [log_notification] for the level mapping.
+    /// Notifications are forwarded as long as the returned guard lives.
+    ///
+    pub async fn forward_to_tracing(&self, filter: NotificationFilter) -> CallbackGuard {
+        self.add_filtered_callback(filter, |notification| {
+            log_notification(&notification);
+            async {}.boxed()
+        })
+        .await
+    }
+
     /// Remove a callback by its ID
     ///
     #[inline]
@@ -0,0 +1,127 @@
+use crate::fbs::NotificationBuffer;
+use crate::fbs::NotificationType;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Filter used to select notifications by instance source and type
+///
+/// An empty filter matches every notification.
+///
+pub struct NotificationFilter {
+    /// Accepted instance sources, all sources if empty
+    ///
+    sources: Vec<String>,
+
+    /// Accepted notification types, all types if empty
+    ///
+    types: Vec<NotificationType>,
+
+    /// Minimal severity accepted, if any
+    ///
+    min_type: Option<NotificationType>,
+}
+
+impl NotificationFilter {
+    /// Create a filter that matches every notification
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Accept notifications coming from this instance
+    ///
+    pub fn with_source<S: Into<String>>(mut self, source: S) -> Self {
+        self.sources.push(source.into());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Accept notifications of this type
+    ///
+    pub fn with_type(mut self, notification_type: NotificationType) -> Self {
+        self.types.push(notification_type);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Accept only notifications at least as severe as this type
+    ///
+    pub fn with_min_type(mut self, notification_type: NotificationType) -> Self {
+        self.min_type = Some(notification_type);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// True if the notification passes the filter
+    ///
+    /// Notifications with a malformed type only match filters without type constraints.
+    ///
+    pub fn matches(&self, notification: &NotificationBuffer) -> bool {
+        if !self.sources.is_empty() {
+            match notification.source_str() {
+                Some(source) if self.sources.iter().any(|s| s == source) => {}
+                _ => return false,
+            }
+        }
+
+        if self.types.is_empty() && self.min_type.is_none() {
+            return true;
+        }
+
+        match notification.notification_type() {
+            Some(notification_type) => {
+                (self.types.is_empty() || self.types.contains(&notification_type))
+                    && self
+                        .min_type
+                        .map(|min| notification_type.severity() >= min.severity())
+                        .unwrap_or(true)
+            }
+            None => false,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(notification_type: NotificationType, source: &str) -> NotificationBuffer {
+        NotificationBuffer::builder()
+            .with_notification_type(notification_type)
+            .with_notification_source(source)
+            .with_notification_message("message")
+            .with_source(0)
+            .with_sequence(0)
+            .build()
+            .expect("Failed to build notification")
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let error_a = notification(NotificationType::Error, "instance_a");
+        let info_b = notification(NotificationType::Info, "instance_b");
+
+        assert!(NotificationFilter::new().matches(&error_a));
+        assert!(NotificationFilter::new()
+            .with_source("instance_a")
+            .matches(&error_a));
+        assert!(!NotificationFilter::new()
+            .with_source("instance_a")
+            .matches(&info_b));
+        assert!(NotificationFilter::new()
+            .with_type(NotificationType::Info)
+            .matches(&info_b));
+        assert!(NotificationFilter::new()
+            .with_min_type(NotificationType::Warning)
+            .matches(&error_a));
+        assert!(!NotificationFilter::new()
+            .with_min_type(NotificationType::Warning)
+            .matches(&info_b));
+    }
+}
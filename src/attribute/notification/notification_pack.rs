@@ -1,17 +1,24 @@
-use crate::attribute::CallbackId;
+use super::NotificationFilter;
+use crate::attribute::CallbackGuard;
 use crate::fbs::NotificationBuffer;
 use crate::fbs::NotificationType;
 use crate::NotificationAttribute;
 use futures::FutureExt;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
 
 #[derive(Clone, Debug)]
 /// A pack of notifications, used to group multiple NotificationBuffer objects together.
+///
+/// The pack stops collecting notifications when its last clone is dropped.
+///
 pub struct NotificationPack {
-    /// Unique identifier for the callback associated with this pack
+    /// Registration of the callback that feeds this pack
     ///
-    cb_id: CallbackId,
+    _callback_guard: Arc<CallbackGuard>,
 
     /// The attribute associated with this pack
     ///
@@ -20,21 +27,10 @@ pub struct NotificationPack {
     /// Vector of notifications in the pack
     ///
     pub notifications: Arc<Mutex<Vec<NotificationBuffer>>>,
-}
 
-impl Drop for NotificationPack {
-    fn drop(&mut self) {
-        let attribute = self.attribute.clone();
-        let cb_id = self.cb_id;
-        // Spawn a task to remove the callback asynchronously
-        // (assuming NotificationAttribute::remove_callback is async)
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
-            rt.block_on(async move {
-                attribute.remove_callback(cb_id).await;
-            });
-        });
-    }
+    /// Notified each time a notification is pushed in the pack
+    ///
+    update_notifier: Arc<Notify>,
 }
 
 impl NotificationPack {
@@ -43,14 +39,17 @@ impl NotificationPack {
     pub async fn new(attribute: NotificationAttribute) -> Self {
         // Initialize an empty vector for notifications
         let notifications = Arc::new(Mutex::new(Vec::new()));
+        let update_notifier = Arc::new(Notify::new());
 
         // Register a callback on the attribute to handle incoming notifications
-        let cb_id = attribute
+        let callback_guard = attribute
             .add_callback({
                 let notifications = notifications.clone();
+                let update_notifier = update_notifier.clone();
                 move |notification| {
                     let mut notifications_vec = notifications.lock().unwrap();
                     notifications_vec.push(notification.clone());
+                    update_notifier.notify_waiters();
                     async move {}.boxed()
                 }
             })
@@ -58,17 +57,25 @@ impl NotificationPack {
 
         // Return the new NotificationPack instance
         Self {
-            cb_id,
+            _callback_guard: Arc::new(callback_guard),
             attribute,
             notifications,
+            update_notifier,
         }
     }
 
+    /// The attribute associated with this pack
+    ///
+    pub fn attribute(&self) -> &NotificationAttribute {
+        &self.attribute
+    }
+
     /// Push a new notification into the pack
     ///
     pub fn push(&mut self, notification: NotificationBuffer) {
         let mut notifications = self.notifications.lock().unwrap();
         notifications.push(notification);
+        self.update_notifier.notify_waiters();
     }
 
     /// Reset the pack, clearing all notifications
@@ -84,11 +91,78 @@ impl NotificationPack {
 
     pub fn has_alert(&self) -> bool {
         let notifications = self.notifications.lock().unwrap();
-        for notification in notifications.iter() {
-            if notification.notification_type().unwrap() == NotificationType::Alert {
-                return true;
+        notifications
+            .iter()
+            .any(|n| n.notification_type() == Some(NotificationType::Alert))
+    }
+
+    /// Returns the notifications of the pack that pass the filter
+    ///
+    pub fn filtered(&self, filter: &NotificationFilter) -> Vec<NotificationBuffer> {
+        let notifications = self.notifications.lock().unwrap();
+        notifications
+            .iter()
+            .filter(|n| filter.matches(n))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the errors reported by the given instance
+    ///
+    pub fn errors_from(&self, instance: &str) -> Vec<NotificationBuffer> {
+        self.filtered(
+            &NotificationFilter::new()
+                .with_source(instance)
+                .with_type(NotificationType::Error),
+        )
+    }
+
+    /// Returns the number of notifications of each type
+    ///
+    /// Notifications with a malformed type are not counted.
+    ///
+    pub fn count_by_type(&self) -> HashMap<NotificationType, usize> {
+        let notifications = self.notifications.lock().unwrap();
+        let mut counts = HashMap::new();
+        for notification_type in notifications.iter().filter_map(|n| n.notification_type()) {
+            *counts.entry(notification_type).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Wait until a notification that passes the filter is in the pack
+    ///
+    /// Notifications already in the pack are checked first.
+    ///
+    pub async fn wait_for(
+        &self,
+        filter: &NotificationFilter,
+        timeout: Duration,
+    ) -> Result<NotificationBuffer, String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register before checking to not miss a notification pushed in between
+            let notified = self.update_notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(notification) = self.filtered(filter).into_iter().next() {
+                return Ok(notification);
             }
+
+            tokio::time::timeout_at(deadline, notified)
+                .await
+                .map_err(|_| "Timeout waiting for notification".to_string())?;
         }
-        false
+    }
+
+    /// Wait until an error notification is in the pack
+    ///
+    pub async fn wait_for_error(&self, timeout: Duration) -> Result<NotificationBuffer, String> {
+        self.wait_for(
+            &NotificationFilter::new().with_type(NotificationType::Error),
+            timeout,
+        )
+        .await
     }
 }
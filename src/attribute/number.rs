@@ -1,7 +1,23 @@
+use super::sample::Sample;
+use super::statistics::timed_value;
+use super::statistics::Accumulator;
+use super::statistics::Resampled;
+use super::statistics::Resampler;
+use super::statistics::Resampling;
+use super::statistics::StabilityWindow;
+use super::statistics::Statistics;
+use super::statistics::TimedValue;
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::NumberBuffer;
 use crate::AttributeMetadata;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::mpsc::UnboundedReceiver;
 use zenoh::Session;
 
 #[derive(Clone, Debug)]
@@ -12,14 +28,20 @@ pub struct NumberAttribute {
 
 impl NumberAttribute {
     /// Create a new instance
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        let inner = StdObjAttribute::<NumberBuffer>::new(session, metadata).await;
-        Self { inner }
+    ///
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let inner = StdObjAttribute::<NumberBuffer>::new(session, metadata).await?;
+        Ok(Self { inner })
     }
 
     /// Set the value and wait for validation
     #[inline]
-    pub async fn set(&mut self, value: f64) -> Result<(), String> {
+    pub async fn set(&mut self, value: f64) -> Result<(), AttributeError> {
         self.inner
             .set(
                 NumberBuffer::builder()
@@ -38,6 +60,13 @@ impl NumberAttribute {
         self.inner.get().await
     }
 
+    /// Get the last received value with its timestamp, source and sequence
+    pub async fn sample(&self) -> Option<Sample<f64>> {
+        let (buffer, received_at) = self.inner.get_received().await?;
+        let value = buffer.value()?;
+        Some(Sample::from_buffer(&buffer, value, received_at))
+    }
+
     /// Wait for a specific number value to be received
     #[inline]
     pub async fn wait_for_value(
@@ -57,7 +86,7 @@ impl NumberAttribute {
     /// Add a callback that will be triggered when receiving NumberBuffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
     #[inline]
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(NumberBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
@@ -79,4 +108,149 @@ impl NumberAttribute {
     pub fn metadata(&self) -> &AttributeMetadata {
         self.inner.metadata()
     }
+
+    /// Statistics of the samples received during `duration`
+    ///
+    /// The window starts at the header time of the next received sample and
+    /// covers `duration` of header time. It also ends `duration` after the
+    /// arrival of that first sample when the stream goes silent. Samples
+    /// without header timestamp are only counted in [Statistics::untimed].
+    ///
+    /// # Returns
+    /// None if no timestamped sample is received within `duration`
+    pub async fn measure(&self, duration: Duration) -> Option<Statistics> {
+        let (mut samples, _guard) = self.timed_values().await;
+        let mut accumulator = Accumulator::default();
+
+        let first = tokio::time::timeout(duration, async {
+            loop {
+                match samples.recv().await? {
+                    Some(sample) => return Some(sample),
+                    None => accumulator.skip(),
+                }
+            }
+        })
+        .await
+        .ok()??;
+        let end = first.timestamp + duration;
+        let deadline = tokio::time::Instant::now() + duration;
+        accumulator.push(first.timestamp, first.value);
+
+        while let Ok(Some(sample)) = tokio::time::timeout_at(deadline, samples.recv()).await {
+            let Some(sample) = sample else {
+                accumulator.skip();
+                continue;
+            };
+            if sample.timestamp > end {
+                break;
+            }
+            if sample.timestamp >= first.timestamp {
+                accumulator.push(sample.timestamp, sample.value);
+            }
+        }
+        accumulator.finish()
+    }
+
+    /// Wait for the value to stay within `tolerance` of its latest value
+    /// during `duration`
+    ///
+    /// The duration is measured on the header timestamps, samples without one
+    /// are ignored. While no sample is received, the latest value is
+    /// considered held.
+    ///
+    /// # Returns
+    /// The settled value, or `AttributeError::Timeout` after `timeout`
+    pub async fn wait_until_stable(
+        &self,
+        tolerance: f64,
+        duration: Duration,
+        timeout: Duration,
+    ) -> Result<f64, AttributeError> {
+        let (mut samples, _guard) = self.timed_values().await;
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut window = StabilityWindow::new(tolerance, duration);
+
+        // Arrival of the latest sample, to extend its header time while silent
+        let mut arrival = Instant::now();
+        if let Some(sample) = self.get().await.as_ref().and_then(timed_value) {
+            window.push(sample);
+        }
+
+        loop {
+            let now = window.latest().map(|latest| latest + arrival.elapsed());
+            let wait = match now.and_then(|now| window.remaining(now)) {
+                Some(Duration::ZERO) => {
+                    return Ok(window.value().expect("stable window without sample"));
+                }
+                Some(remaining) => remaining,
+                None => timeout,
+            };
+
+            tokio::select! {
+                sample = samples.recv() => {
+                    let Some(sample) = sample else {
+                        break;
+                    };
+                    if let Some(sample) = sample {
+                        window.push(sample);
+                        arrival = Instant::now();
+                    }
+                }
+                _ = tokio::time::sleep(wait) => {}
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+        Err(AttributeError::Timeout(format!(
+            "{} not stable within {} during {:?}",
+            self.metadata().topic,
+            tolerance,
+            duration
+        )))
+    }
+
+    /// Stream of the received values reduced by `resampling`
+    ///
+    /// Periods are measured on the header timestamps, samples without one are
+    /// skipped.
+    pub async fn resample(&self, resampling: Resampling) -> Resampled {
+        let (sender, receiver) = unbounded_channel();
+        let resampler = Mutex::new(Resampler::new(resampling));
+        let guard = self
+            .add_callback(
+                move |buffer: NumberBuffer| {
+                    if let Some(sample) = timed_value(&buffer) {
+                        let emitted = resampler.lock().unwrap().push(sample);
+                        if let Some(emitted) = emitted {
+                            let _ = sender.send(emitted);
+                        }
+                    }
+                    Box::pin(async {})
+                },
+                None::<fn(&NumberBuffer) -> bool>,
+            )
+            .await;
+        Resampled {
+            receiver,
+            _guard: guard,
+        }
+    }
+
+    /// Channel of the values received while the guard lives
+    ///
+    /// Values without header timestamp are sent as None.
+    async fn timed_values(&self) -> (UnboundedReceiver<Option<TimedValue>>, CallbackGuard) {
+        let (sender, receiver) = unbounded_channel();
+        let guard = self
+            .add_callback(
+                move |buffer: NumberBuffer| {
+                    if buffer.value().is_some() {
+                        let _ = sender.send(timed_value(&buffer));
+                    }
+                    Box::pin(async {})
+                },
+                None::<fn(&NumberBuffer) -> bool>,
+            )
+            .await;
+        (receiver, guard)
+    }
 }
@@ -1,9 +1,18 @@
-use super::{CallbackEntry, CallbackId};
+use super::dispatcher::DispatchConfig;
+use super::dispatcher::DispatchMetrics;
+use super::dispatcher::Dispatcher;
+use super::tasks::AttributeTasks;
+use super::tasks::TaskScope;
+use super::{AttributeError, CallbackEntry, CallbackGuard, CallbackId};
 use crate::fbs::PzaBuffer;
+use crate::reactor::Connection;
 use crate::AttributeMetadata;
-use std::collections::HashMap;
+use crate::Topic;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::watch;
+use zenoh::handlers::FifoChannelHandler;
+use zenoh::pubsub::Subscriber;
+use zenoh::sample::Sample;
 use zenoh::Session;
 
 /// Standard message attribute implementation
@@ -13,75 +22,100 @@ pub struct RoStreamAttribute<B: PzaBuffer> {
     /// Metadata for the attribute
     metadata: AttributeMetadata,
 
-    /// Async callbacks storage
-    callbacks: Arc<Mutex<HashMap<CallbackId, CallbackEntry<B>>>>,
+    /// Topic of the values
+    att_topic: String,
 
-    /// Next callback ID
-    next_callback_id: Arc<Mutex<CallbackId>>,
+    /// Async callbacks and their queues
+    dispatcher: Arc<Dispatcher<B>>,
+
+    /// Receive loop and session follower, aborted with the last handle
+    tasks: Arc<AttributeTasks>,
 }
 
 impl<B: PzaBuffer> RoStreamAttribute<B> {
     // ------------------------------------------------------------------------
     /// Create a new instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        // Initialize async callbacks storage
-        let callbacks = Arc::new(Mutex::new(HashMap::<CallbackId, CallbackEntry<B>>::new()));
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic, or
+    /// [AttributeError::Session] if the subscriber cannot be declared
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let att_topic = Topic::parse(&metadata.topic)?.att_topic();
+
+        // Initialize async callbacks dispatcher, its workers are attribute tasks
+        let tasks = AttributeTasks::new(metadata.topic.clone());
+        let dispatcher = Dispatcher::new(metadata.topic.clone(), &tasks);
 
         // Trigger the callback mechanism on message reception
-        let att_topic = format!("{}/att", &metadata.topic);
-        let subscriber = session
-            .declare_subscriber(&att_topic)
-            .await
-            .expect("Failed to declare subscriber for attribute topic");
-        let last_value = Arc::new(Mutex::new(None));
-
-        tokio::spawn({
-            let callbacks = callbacks.clone();
-            let last_value = last_value.clone();
-            async move {
-                while let Ok(sample) = subscriber.recv_async().await {
-                    // Create Buffer from the received zbytes
-                    let buffer = B::from_zbytes(sample.payload().clone());
-
-                    // Update the last received value
-                    {
-                        let mut last = last_value.lock().await;
-                        *last = Some(buffer.clone());
-                    }
-
-                    // Trigger all async callbacks
-                    let callbacks_map = callbacks.lock().await;
-                    let mut futures = Vec::new();
-
-                    for (_id, callback_entry) in callbacks_map.iter() {
-                        // Check condition if present
-                        let should_trigger = if let Some(condition) = &callback_entry.condition {
-                            condition(&buffer)
-                        } else {
-                            true
-                        };
-
-                        if should_trigger {
-                            futures.push((callback_entry.callback)(buffer.clone()));
-                        }
-                    }
-
-                    // Drop the lock before awaiting futures
-                    drop(callbacks_map);
-
-                    // Execute all callbacks concurrently
-                    futures::future::join_all(futures).await;
+        let subscriber = session.declare_subscriber(&att_topic).await.map_err(|e| {
+            AttributeError::Session(format!("cannot declare subscriber on {}: {}", att_topic, e))
+        })?;
+        Self::spawn_listener(&tasks, subscriber, dispatcher.clone());
+
+        // Return attribute
+        Ok(Self {
+            metadata,
+            att_topic,
+            dispatcher,
+            tasks,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Dispatch the samples of a subscriber to the callbacks
+    ///
+    /// The task ends when the subscriber is undeclared (e.g. its session is closed)
+    /// and replaces the previous receive loop of the attribute.
+    fn spawn_listener(
+        tasks: &AttributeTasks,
+        subscriber: Subscriber<FifoChannelHandler<Sample>>,
+        dispatcher: Arc<Dispatcher<B>>,
+    ) {
+        tasks.spawn("listener", async move {
+            while let Ok(sample) = subscriber.recv_async().await {
+                let buffer = B::from_zbytes(sample.payload().clone());
+
+                // Queue the buffer for the callbacks, each one runs in its own task
+                dispatcher.dispatch(buffer).await;
+            }
+        });
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Subscribe again on the session of each new connection published on the
+    /// channel
+    ///
+    /// Registered callbacks are kept.
+    pub fn follow(&self, mut connections: watch::Receiver<Connection>) {
+        let dispatcher = self.dispatcher.clone();
+        let att_topic = self.att_topic.clone();
+        // Weak reference: the follower must not keep the tasks alive
+        let tasks = Arc::downgrade(&self.tasks);
+        self.tasks.spawn("follow", async move {
+            while connections.changed().await.is_ok() {
+                let session = connections.borrow_and_update().session.clone();
+                match session.declare_subscriber(&att_topic).await {
+                    Ok(subscriber) => match tasks.upgrade() {
+                        Some(tasks) => Self::spawn_listener(&tasks, subscriber, dispatcher.clone()),
+                        None => return,
+                    },
+                    Err(e) => tracing::error!("Failed to subscribe again to {}: {}", att_topic, e),
                 }
             }
         });
+    }
 
-        // Return attribute
-        Self {
-            metadata,
-            callbacks,
-            next_callback_id: Arc::new(Mutex::new(0)),
-        }
+    // ------------------------------------------------------------------------
+
+    /// Track the background tasks of this attribute in a reactor scope
+    ///
+    pub fn track_in(&self, scope: &TaskScope) {
+        scope.track(&self.tasks);
     }
 
     // ------------------------------------------------------------------------
@@ -98,8 +132,8 @@ impl<B: PzaBuffer> RoStreamAttribute<B> {
         // Use a broadcast channel to avoid the move issue
         let (tx, mut rx) = tokio::sync::broadcast::channel(1);
 
-        // Add temporary callback
-        let callback_id = self
+        // Add temporary callback, removed when the guard is dropped
+        let callback_guard = self
             .add_callback(
                 move |buffer: B| {
                     let buffer_clone = buffer.clone();
@@ -120,7 +154,7 @@ impl<B: PzaBuffer> RoStreamAttribute<B> {
         };
 
         // Remove the callback
-        self.remove_callback(callback_id).await;
+        drop(callback_guard);
 
         match result {
             Ok(Ok(buffer)) => Ok(buffer),
@@ -133,7 +167,9 @@ impl<B: PzaBuffer> RoStreamAttribute<B> {
 
     /// Add an async callback that will be triggered when receiving buffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    ///
+    /// The callback is removed when the returned guard is dropped.
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(B) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
@@ -141,43 +177,66 @@ impl<B: PzaBuffer> RoStreamAttribute<B> {
             + 'static,
         C: Fn(&B) -> bool + Send + Sync + 'static,
     {
-        let mut callbacks = self.callbacks.lock().await;
-        let mut next_id = self.next_callback_id.lock().await;
-
-        let callback_id = *next_id;
-        *next_id += 1;
-
         let callback_entry = CallbackEntry {
             callback: Box::new(callback),
             condition: condition.map(|c| Box::new(c) as Box<dyn Fn(&B) -> bool + Send + Sync>),
         };
-
-        callbacks.insert(callback_id, callback_entry);
-        callback_id
+        self.dispatcher.register(callback_entry)
     }
 
     // ------------------------------------------------------------------------
 
     /// Remove an async callback by its ID
     pub async fn remove_callback(&self, callback_id: CallbackId) -> bool {
-        let mut callbacks = self.callbacks.lock().await;
-        callbacks.remove(&callback_id).is_some()
+        self.dispatcher.remove(callback_id)
     }
 
     // ------------------------------------------------------------------------
 
     /// Clear all async callbacks
     pub async fn clear_callbacks(&self) {
-        let mut callbacks = self.callbacks.lock().await;
-        callbacks.clear();
+        self.dispatcher.clear();
     }
 
     // ------------------------------------------------------------------------
 
     /// Get the number of registered async callbacks
     pub async fn callback_count(&self) -> usize {
-        let callbacks = self.callbacks.lock().await;
-        callbacks.len()
+        self.dispatcher.count()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the queue of the callbacks added from now on
+    ///
+    /// Each callback runs in its own task and receives the samples through a
+    /// bounded queue, see [DispatchConfig].
+    pub fn set_dispatch_config(&self, config: DispatchConfig) {
+        self.dispatcher.set_config(config);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Queue settings of the callbacks added from now on
+    ///
+    pub fn dispatch_config(&self) -> DispatchConfig {
+        self.dispatcher.config()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Delivered, dropped and panicked samples of all the callbacks
+    ///
+    pub fn dispatch_metrics(&self) -> DispatchMetrics {
+        self.dispatcher.metrics()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Delivered, dropped and panicked samples of one callback
+    ///
+    pub fn callback_metrics(&self, callback_id: CallbackId) -> Option<DispatchMetrics> {
+        self.dispatcher.callback_metrics(callback_id)
     }
 
     // ------------------------------------------------------------------------
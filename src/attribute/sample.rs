@@ -0,0 +1,102 @@
+use crate::fbs::PzaBuffer;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Typed value read from an attribute with the metadata of its header
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample<T> {
+    /// Decoded value
+    pub value: T,
+
+    /// Time set by the sender in the header
+    pub timestamp: Option<SystemTime>,
+
+    /// Sender identifier from the header
+    pub source: Option<u16>,
+
+    /// Sequence number from the header
+    pub sequence: Option<u16>,
+
+    /// Time the sample was received by this client
+    pub received_at: SystemTime,
+}
+
+impl<T> Sample<T> {
+    /// Build a sample from a received buffer and its decoded value
+    ///
+    pub fn from_buffer<B: PzaBuffer>(buffer: &B, value: T, received_at: SystemTime) -> Self {
+        Self {
+            value,
+            timestamp: buffer.timestamp(),
+            source: buffer.source(),
+            sequence: buffer.sequence(),
+            received_at,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Time between the header timestamp and the reception
+    ///
+    /// None without timestamp or if the clocks of the sender and this client
+    /// are too far apart for the sample to seem received before being sent.
+    pub fn latency(&self) -> Option<Duration> {
+        self.received_at.duration_since(self.timestamp?).ok()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Time to order samples across attributes, the header timestamp when
+    /// present, else the reception time
+    ///
+    pub fn time(&self) -> SystemTime {
+        self.timestamp.unwrap_or(self.received_at)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Transform the value, keeping the header metadata
+    ///
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Sample<U> {
+        Sample {
+            value: f(self.value),
+            timestamp: self.timestamp,
+            source: self.source,
+            sequence: self.sequence,
+            received_at: self.received_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbs::NumberBuffer;
+
+    #[test]
+    fn sample_from_number_buffer() {
+        let buffer = NumberBuffer::builder()
+            .with_value(1.5)
+            .with_source(7)
+            .with_sequence(42)
+            .build()
+            .unwrap();
+        let sent = buffer.timestamp().unwrap();
+        let received_at = sent + Duration::from_millis(3);
+
+        let sample = Sample::from_buffer(&buffer, buffer.value().unwrap(), received_at);
+        assert_eq!(sample.value, 1.5);
+        assert_eq!(sample.source, Some(7));
+        assert_eq!(sample.sequence, Some(42));
+        assert_eq!(sample.time(), sent);
+        assert_eq!(sample.latency(), Some(Duration::from_millis(3)));
+
+        let late = Sample {
+            received_at: sent - Duration::from_secs(1),
+            ..sample.map(|value| value * 2.0)
+        };
+        assert_eq!(late.value, 3.0);
+        assert_eq!(late.latency(), None);
+    }
+}
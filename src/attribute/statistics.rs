@@ -0,0 +1,451 @@
+use super::CallbackGuard;
+use crate::fbs::NumberBuffer;
+use crate::fbs::PzaBuffer;
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// Value of a sample with the time taken from its header
+///
+/// # Returns
+/// None if the sample has no value or no header timestamp
+pub(crate) fn timed_value(buffer: &NumberBuffer) -> Option<TimedValue> {
+    Some(TimedValue {
+        timestamp: buffer.timestamp()?,
+        value: buffer.value()?,
+    })
+}
+
+/// Number value with the time it was sampled at
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedValue {
+    pub timestamp: SystemTime,
+    pub value: f64,
+}
+
+/// Statistics of the samples received during a window
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    /// Number of samples
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+    /// Header time of the first sample
+    pub start: SystemTime,
+    /// Header time of the last sample
+    pub end: SystemTime,
+    /// Samples without header timestamp, left out of the statistics
+    pub untimed: usize,
+}
+
+/// Running computation of [Statistics] (Welford)
+///
+#[derive(Debug, Default)]
+pub(crate) struct Accumulator {
+    count: usize,
+    min: f64,
+    max: f64,
+    mean: f64,
+    m2: f64,
+    start: Option<SystemTime>,
+    end: Option<SystemTime>,
+    untimed: usize,
+}
+
+impl Accumulator {
+    pub(crate) fn push(&mut self, timestamp: SystemTime, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.start = Some(self.start.map_or(timestamp, |start| start.min(timestamp)));
+        self.end = Some(self.end.map_or(timestamp, |end| end.max(timestamp)));
+    }
+
+    /// Count a sample that has no header timestamp
+    ///
+    pub(crate) fn skip(&mut self) {
+        self.untimed += 1;
+    }
+
+    /// Statistics of the pushed samples, None if there is none
+    ///
+    pub(crate) fn finish(&self) -> Option<Statistics> {
+        Some(Statistics {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            stddev: (self.m2 / self.count as f64).sqrt(),
+            start: self.start?,
+            end: self.end?,
+            untimed: self.untimed,
+        })
+    }
+}
+
+/// Samples of a value settling within a tolerance
+///
+/// Only the samples within `tolerance` of the latest one are kept, the value
+/// is stable once they cover `duration` of header time.
+#[derive(Debug)]
+pub(crate) struct StabilityWindow {
+    tolerance: f64,
+    duration: Duration,
+    samples: VecDeque<TimedValue>,
+}
+
+impl StabilityWindow {
+    pub(crate) fn new(tolerance: f64, duration: Duration) -> Self {
+        Self {
+            tolerance: tolerance.abs(),
+            duration,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a sample, samples older than the latest one are ignored
+    ///
+    pub(crate) fn push(&mut self, sample: TimedValue) {
+        if let Some(latest) = self.samples.back() {
+            if sample.timestamp < latest.timestamp {
+                return;
+            }
+        }
+        if let Some(outside) = self
+            .samples
+            .iter()
+            .rposition(|s| (s.value - sample.value).abs() > self.tolerance)
+        {
+            self.samples.drain(..=outside);
+        }
+        self.samples.push_back(sample);
+
+        // The oldest sample is useless once the next one already covers the duration
+        while self.samples.len() > 1
+            && sample
+                .timestamp
+                .duration_since(self.samples[1].timestamp)
+                .is_ok_and(|covered| covered >= self.duration)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Header time of the latest sample
+    ///
+    pub(crate) fn latest(&self) -> Option<SystemTime> {
+        self.samples.back().map(|s| s.timestamp)
+    }
+
+    /// Time still needed at `now` before the value is stable
+    ///
+    /// # Returns
+    /// None without sample, zero once stable
+    pub(crate) fn remaining(&self, now: SystemTime) -> Option<Duration> {
+        let first = self.samples.front()?;
+        let covered = now.duration_since(first.timestamp).unwrap_or_default();
+        Some(self.duration.saturating_sub(covered))
+    }
+
+    /// Latest value
+    ///
+    pub(crate) fn value(&self) -> Option<f64> {
+        self.samples.back().map(|s| s.value)
+    }
+}
+
+/// How a [Resampled] stream reduces the incoming samples
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resampling {
+    /// Keep one sample out of n
+    Decimate(usize),
+    /// Mean of the samples of each period, stamped with the period start
+    Mean(Duration),
+    /// Last sample of each period, stamped with the period start
+    Last(Duration),
+}
+
+/// State of a resampling
+///
+/// Periods are aligned on the unix epoch. A period is emitted when the first
+/// sample of a later period is received, samples arriving late are counted in
+/// the current period.
+#[derive(Debug)]
+pub(crate) struct Resampler {
+    resampling: Resampling,
+    seen: usize,
+    period: Option<u128>,
+    sum: f64,
+    count: usize,
+    last: f64,
+}
+
+impl Resampler {
+    pub(crate) fn new(resampling: Resampling) -> Self {
+        Self {
+            resampling,
+            seen: 0,
+            period: None,
+            sum: 0.0,
+            count: 0,
+            last: 0.0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, sample: TimedValue) -> Option<TimedValue> {
+        let period = match self.resampling {
+            Resampling::Decimate(factor) => {
+                let keep = self.seen.is_multiple_of(factor.max(1));
+                self.seen += 1;
+                return keep.then_some(sample);
+            }
+            Resampling::Mean(period) | Resampling::Last(period) => period,
+        };
+
+        let length = period.as_nanos().max(1);
+        let index = sample
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            / length;
+        let mut emitted = None;
+        match self.period {
+            Some(current) if index > current => {
+                emitted = Some(self.emit(current, length));
+                self.period = Some(index);
+            }
+            Some(_) => {}
+            None => self.period = Some(index),
+        }
+        self.sum += sample.value;
+        self.count += 1;
+        self.last = sample.value;
+        emitted
+    }
+
+    fn emit(&mut self, index: u128, length: u128) -> TimedValue {
+        let value = match self.resampling {
+            Resampling::Mean(_) => self.sum / self.count as f64,
+            _ => self.last,
+        };
+        self.sum = 0.0;
+        self.count = 0;
+        let nanos = index * length;
+        TimedValue {
+            timestamp: UNIX_EPOCH
+                + Duration::new(
+                    (nanos / 1_000_000_000) as u64,
+                    (nanos % 1_000_000_000) as u32,
+                ),
+            value,
+        }
+    }
+}
+
+/// Resampled stream of a number attribute
+///
+/// The stream stops when it is dropped.
+pub struct Resampled {
+    pub(crate) receiver: UnboundedReceiver<TimedValue>,
+    pub(crate) _guard: CallbackGuard,
+}
+
+impl Resampled {
+    /// Wait for the next value
+    ///
+    pub async fn recv(&mut self) -> Option<TimedValue> {
+        self.receiver.recv().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute::number::NumberAttribute;
+    use crate::test_support::platform_and_client;
+    use crate::test_support::wait_for_subscriber;
+    use crate::AttributeMetadata;
+    use crate::AttributeMode;
+
+    fn at(millis: u64, value: f64) -> TimedValue {
+        TimedValue {
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            value,
+        }
+    }
+
+    #[test]
+    fn window_statistics_and_stability() {
+        let mut accumulator = Accumulator::default();
+        assert!(accumulator.finish().is_none());
+        for (millis, value) in [(300, 4.0), (100, 2.0), (200, 6.0)] {
+            let sample = at(millis, value);
+            accumulator.push(sample.timestamp, sample.value);
+        }
+        let stats = accumulator.finish().unwrap();
+        assert_eq!(
+            (stats.count, stats.min, stats.max, stats.mean),
+            (3, 2.0, 6.0, 4.0)
+        );
+        assert!((stats.stddev - (8.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(stats.start, at(100, 0.0).timestamp);
+        assert_eq!(stats.end, at(300, 0.0).timestamp);
+        accumulator.skip();
+        assert_eq!(accumulator.finish().unwrap().untimed, 1);
+
+        let mut window = StabilityWindow::new(0.1, Duration::from_millis(500));
+        window.push(at(0, 1.0));
+        window.push(at(100, 3.0));
+        window.push(at(300, 3.05));
+        // Settled at 100, the first sample is dropped
+        assert_eq!(
+            window.remaining(at(300, 0.0).timestamp),
+            Some(Duration::from_millis(300))
+        );
+        window.push(at(650, 2.98));
+        assert_eq!(
+            window.remaining(at(650, 0.0).timestamp),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(window.value(), Some(2.98));
+        // Late samples are ignored
+        window.push(at(200, 9.0));
+        assert_eq!(window.latest(), Some(at(650, 0.0).timestamp));
+    }
+
+    #[test]
+    fn resampling() {
+        let mut decimate = Resampler::new(Resampling::Decimate(3));
+        let kept: Vec<_> = (0..7)
+            .filter_map(|i| decimate.push(at(i, i as f64)))
+            .map(|s| s.value)
+            .collect();
+        assert_eq!(kept, vec![0.0, 3.0, 6.0]);
+
+        let samples = [at(10, 1.0), at(90, 3.0), at(150, 5.0), at(420, 7.0)];
+        let mut mean = Resampler::new(Resampling::Mean(Duration::from_millis(100)));
+        let means: Vec<_> = samples.iter().filter_map(|s| mean.push(*s)).collect();
+        assert_eq!(means, vec![at(0, 2.0), at(100, 5.0)]);
+
+        let mut last = Resampler::new(Resampling::Last(Duration::from_millis(100)));
+        let lasts: Vec<_> = samples.iter().filter_map(|s| last.push(*s)).collect();
+        assert_eq!(lasts, vec![at(0, 3.0), at(100, 5.0)]);
+    }
+
+    #[test]
+    fn samples_without_timestamp_have_no_time() {
+        use crate::fbs::panduza_generated::panduza::Header;
+        use crate::fbs::panduza_generated::panduza::HeaderArgs;
+        use crate::fbs::panduza_generated::panduza::Message;
+        use crate::fbs::panduza_generated::panduza::MessageArgs;
+        use crate::fbs::panduza_generated::panduza::Number;
+        use crate::fbs::panduza_generated::panduza::NumberArgs;
+        use crate::fbs::panduza_generated::panduza::Payload;
+
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let payload = Number::create(
+            &mut builder,
+            &NumberArgs {
+                value: 1.0,
+                ..Default::default()
+            },
+        );
+        let header = Header::create(
+            &mut builder,
+            &HeaderArgs {
+                timestamp: None,
+                source: 0,
+                sequence: 0,
+            },
+        );
+        let message = Message::create(
+            &mut builder,
+            &MessageArgs {
+                header: Some(header),
+                payload_type: Payload::Number,
+                payload: Some(payload.as_union_value()),
+            },
+        );
+        builder.finish(message, None);
+        let untimed = NumberBuffer::from_zbytes(builder.finished_data().to_vec().into());
+        assert_eq!(untimed.value(), Some(1.0));
+        assert_eq!(timed_value(&untimed), None);
+
+        let timed = NumberBuffer::from_zbytes(number(2.0));
+        assert_eq!(timed_value(&timed).map(|sample| sample.value), Some(2.0));
+    }
+
+    fn number(value: f64) -> zenoh::bytes::ZBytes {
+        NumberBuffer::builder()
+            .with_value(value)
+            .with_source(0)
+            .with_random_sequence()
+            .build()
+            .unwrap()
+            .to_zbytes()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn measure_and_wait_until_stable() {
+        let (platform, client, _) = platform_and_client().await;
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/statistics/voltage".to_string(),
+            Some("number".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let voltage = NumberAttribute::new(client.clone(), metadata)
+            .await
+            .unwrap();
+        wait_for_subscriber(&platform, "pza/test/statistics/voltage/att").await;
+
+        let publisher = {
+            let platform = platform.clone();
+            tokio::spawn(async move {
+                for value in [1.0, 5.0, 3.0, 3.0, 3.02, 2.99, 3.01] {
+                    platform
+                        .put("pza/test/statistics/voltage/att", number(value))
+                        .await
+                        .unwrap();
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            })
+        };
+
+        let stats = voltage.measure(Duration::from_millis(250)).await.unwrap();
+        assert!(stats.count >= 2);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+
+        let value = voltage
+            .wait_until_stable(0.05, Duration::from_millis(300), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!((value - 3.0).abs() <= 0.05);
+        let sample = voltage.sample().await.unwrap();
+        assert_eq!(sample.source, Some(0));
+        assert!(sample.latency().is_some());
+        publisher.await.unwrap();
+
+        assert!(matches!(
+            voltage
+                .wait_until_stable(0.05, Duration::from_secs(2), Duration::from_millis(200))
+                .await,
+            Err(crate::attribute::AttributeError::Timeout(_))
+        ));
+    }
+}
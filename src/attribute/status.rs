@@ -1,9 +1,52 @@
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::status_buffer::StatusBuffer;
+use crate::fbs::InstanceStateTransition;
 use crate::AttributeMetadata;
+use crate::InstanceState;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use tokio::sync::mpsc::UnboundedReceiver;
 use zenoh::Session;
 
+/// Stream of per-instance state transitions
+///
+/// Transitions are computed by diffing consecutive StatusBuffer received by the attribute.
+/// The underlying callback is removed when the stream is dropped.
+///
+#[derive(Debug)]
+pub struct InstanceTransitions {
+    /// Registration of the callback that feeds this stream
+    ///
+    _callback_guard: CallbackGuard,
+
+    /// Receiver of the computed transitions
+    ///
+    receiver: UnboundedReceiver<InstanceStateTransition>,
+}
+
+impl InstanceTransitions {
+    /// Wait for the next transition
+    ///
+    pub async fn recv(&mut self) -> Option<InstanceStateTransition> {
+        self.receiver.recv().await
+    }
+}
+
+impl futures::Stream for InstanceTransitions {
+    type Item = InstanceStateTransition;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Objet pour gérer StatusAttribute
 pub struct StatusAttribute {
@@ -15,12 +58,17 @@ pub struct StatusAttribute {
 impl StatusAttribute {
     /// New instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
         // Create inner implementation
-        let inner = StdObjAttribute::<StatusBuffer>::new(session, metadata).await;
+        let inner = StdObjAttribute::<StatusBuffer>::new(session, metadata).await?;
 
         // Return the new StatusAttribute instance
-        Self { inner }
+        Ok(Self { inner })
     }
 
     /// Attend une valeur spécifique de StatusBuffer (via un prédicat)
@@ -41,7 +89,7 @@ impl StatusAttribute {
 
     /// Ajoute un callback déclenché à la réception de StatusBuffer
     #[inline]
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(StatusBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
@@ -98,4 +146,114 @@ impl StatusAttribute {
     }
 
     // ------------------------------------------------------------------------
+
+    /// Returns the last known state of the given instance
+    ///
+    pub async fn instance_state(&self, name: &str) -> Option<InstanceState> {
+        self.inner
+            .get()
+            .await
+            .and_then(|status_buffer| status_buffer.instance_state(name))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the names of the instances currently in the given state
+    ///
+    pub async fn instances_in(&self, state: InstanceState) -> Vec<String> {
+        self.inner
+            .get()
+            .await
+            .map(|status_buffer| status_buffer.instances_in(&state))
+            .unwrap_or_default()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the last error string reported by the given instance
+    ///
+    pub async fn instance_error_string(&self, name: &str) -> Option<String> {
+        self.inner
+            .get()
+            .await
+            .and_then(|status_buffer| status_buffer.instance_error_string(name))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the error strings of all the instances that report one
+    ///
+    pub async fn instance_errors(&self) -> HashMap<String, String> {
+        self.inner
+            .get()
+            .await
+            .map(|status_buffer| {
+                status_buffer
+                    .instance_statuses()
+                    .into_iter()
+                    .filter_map(|inst| match (inst.instance(), inst.error_string()) {
+                        (Some(name), Some(error)) => Some((name.clone(), error.clone())),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Waits until the given instance reaches the given state
+    ///
+    pub async fn wait_for_instance_state(
+        &self,
+        name: &str,
+        state: InstanceState,
+        timeout: std::time::Duration,
+    ) -> Result<(), String> {
+        let name = name.to_string();
+        self.inner
+            .wait_for_value(
+                move |status_buffer| status_buffer.instance_state(&name).as_ref() == Some(&state),
+                Some(timeout),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Creates a stream of per-instance state transitions
+    ///
+    /// The first transitions are computed against the last received status.
+    ///
+    pub async fn transitions(&self) -> InstanceTransitions {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let previous = Arc::new(Mutex::new(self.inner.get().await));
+
+        let callback_guard = self
+            .inner
+            .add_callback(
+                move |status_buffer: StatusBuffer| {
+                    let transitions = {
+                        let mut previous = previous.lock().unwrap();
+                        let transitions = status_buffer.transitions_from(previous.as_ref());
+                        *previous = Some(status_buffer);
+                        transitions
+                    };
+                    for transition in transitions {
+                        let _ = sender.send(transition);
+                    }
+                    Box::pin(async {})
+                },
+                None::<fn(&StatusBuffer) -> bool>,
+            )
+            .await;
+
+        InstanceTransitions {
+            _callback_guard: callback_guard,
+            receiver,
+        }
+    }
+
+    // ------------------------------------------------------------------------
 }
@@ -1,33 +1,57 @@
-use super::{CallbackEntry, CallbackId};
+use super::dispatcher::DispatchConfig;
+use super::dispatcher::DispatchMetrics;
+use super::dispatcher::Dispatcher;
+use super::tasks::AttributeTasks;
+use super::tasks::TaskScope;
+use super::{AttributeError, CallbackEntry, CallbackGuard, CallbackId};
 use crate::fbs::PzaBuffer;
+use crate::reactor::Access;
+use crate::reactor::Connection;
+use crate::reactor::Role;
 use crate::AttributeMetadata;
 use crate::AttributeMode;
-use std::collections::HashMap;
+use crate::Topic;
 use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
+use zenoh::handlers::FifoChannelHandler;
+use zenoh::pubsub::Subscriber;
+use zenoh::sample::Sample;
 use zenoh::Session;
 
+/// Last received value with its arrival time
+///
+type LastValue<B> = Arc<Mutex<Option<(B, SystemTime)>>>;
+
 /// Standard message attribute implementation
 ///
 #[derive(Clone, Debug)]
 pub struct StdObjAttribute<B: PzaBuffer> {
-    /// Global Session
-    session: Session,
+    /// Global Session, replaced when the reactor reopens its session
+    session: Arc<RwLock<Session>>,
 
     /// Metadata for the attribute
     metadata: AttributeMetadata,
 
-    /// Async callbacks storage
-    callbacks: Arc<Mutex<HashMap<CallbackId, CallbackEntry<B>>>>,
+    /// Async callbacks and their queues
+    dispatcher: Arc<Dispatcher<B>>,
 
-    /// Next callback ID
-    next_callback_id: Arc<Mutex<CallbackId>>,
+    /// Topic of the values
+    att_topic: String,
 
     /// Command topic
     cmd_topic: String,
 
     /// Last received value
-    last_value: Arc<Mutex<Option<B>>>,
+    last_value: LastValue<B>,
+
+    /// Role of the connection, commands are refused if it cannot publish
+    role: Arc<RwLock<Option<Role>>>,
+
+    /// Receive loop and session follower, aborted with the last handle
+    tasks: Arc<AttributeTasks>,
 }
 
 impl<B: PzaBuffer> StdObjAttribute<B> {
@@ -35,118 +59,228 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
 
     /// Create a new instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        // Initialize async callbacks storage
-        let callbacks = Arc::new(Mutex::new(HashMap::<CallbackId, CallbackEntry<B>>::new()));
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic, or
+    /// [AttributeError::Session] if the subscriber or the initial value query fails
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let topic = Topic::parse(&metadata.topic)?;
+        let att_topic = topic.att_topic();
+
+        // Initialize async callbacks dispatcher, its workers are attribute tasks
+        let tasks = AttributeTasks::new(metadata.topic.clone());
+        let dispatcher = Dispatcher::new(metadata.topic.clone(), &tasks);
 
         // Trigger the callback mechanism on message reception
-        let att_topic = format!("{}/att", &metadata.topic);
 
-        let subscriber = session
-            .declare_subscriber(&att_topic)
-            .await
-            .expect("Failed to declare subscriber for attribute topic");
+        let subscriber = session.declare_subscriber(&att_topic).await.map_err(|e| {
+            AttributeError::Session(format!("cannot declare subscriber on {}: {}", att_topic, e))
+        })?;
         let last_value = Arc::new(Mutex::new(None));
 
-        tokio::spawn({
-            let callbacks = callbacks.clone();
-            let last_value = last_value.clone();
-            async move {
-                while let Ok(sample) = subscriber.recv_async().await {
-                    // Create Buffer from the received zbytes
-                    let buffer = B::from_zbytes(sample.payload().clone());
-
-                    // Update the last received value
-                    {
-                        let mut last = last_value.lock().await;
-                        *last = Some(buffer.clone());
-                    }
-
-                    // Trigger all async callbacks
-                    let callbacks_map = callbacks.lock().await;
-                    let mut futures = Vec::new();
-
-                    for (_id, callback_entry) in callbacks_map.iter() {
-                        // Check condition if present
-                        let should_trigger = if let Some(condition) = &callback_entry.condition {
-                            condition(&buffer)
-                        } else {
-                            true
-                        };
-
-                        if should_trigger {
-                            futures.push((callback_entry.callback)(buffer.clone()));
-                        }
-                    }
-
-                    // Drop the lock before awaiting futures
-                    drop(callbacks_map);
-
-                    // Execute all callbacks concurrently
-                    futures::future::join_all(futures).await;
-                }
-            }
-        });
+        Self::spawn_listener(&tasks, subscriber, dispatcher.clone(), last_value.clone());
 
         // Wait for the first message if mode is not WriteOnly
         if metadata.mode != AttributeMode::WriteOnly {
-            let query = session
-                .get(&att_topic)
-                .await
-                .expect("Failed to create query for initial value");
-            let result = query
-                .recv_async()
-                .await
-                .expect("Failed to receive initial value from query");
-            let buffer = B::from_zbytes(
-                result
-                    .result()
-                    .expect("Failed to get result from query response")
-                    .payload()
-                    .clone(),
-            );
+            let query = session.get(&att_topic).await.map_err(|e| {
+                AttributeError::Session(format!("cannot query {}: {}", att_topic, e))
+            })?;
+            let result = query.recv_async().await.map_err(|e| {
+                AttributeError::Session(format!("no initial value on {}: {}", att_topic, e))
+            })?;
+            let sample = result.result().map_err(|e| {
+                AttributeError::Session(format!(
+                    "initial value query on {} failed: {}",
+                    att_topic, e
+                ))
+            })?;
+            let buffer = B::from_zbytes(sample.payload().clone());
             let mut last = last_value.lock().await;
-            *last = Some(buffer);
+            *last = Some((buffer, SystemTime::now()));
         }
 
         // Create the command topic
-        let cmd_topic = format!("{}/cmd", &metadata.topic);
+        let cmd_topic = topic.cmd_topic();
 
         // Return attribute
-        Self {
-            session,
+        Ok(Self {
+            session: Arc::new(RwLock::new(session)),
             metadata,
-            callbacks,
-            next_callback_id: Arc::new(Mutex::new(0)),
+            dispatcher,
+            att_topic,
             cmd_topic,
             last_value,
+            role: Arc::new(RwLock::new(None)),
+            tasks,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Dispatch the samples of a subscriber to the last value and the callbacks
+    ///
+    /// The task ends when the subscriber is undeclared (e.g. its session is closed)
+    /// and replaces the previous receive loop of the attribute.
+    fn spawn_listener(
+        tasks: &AttributeTasks,
+        subscriber: Subscriber<FifoChannelHandler<Sample>>,
+        dispatcher: Arc<Dispatcher<B>>,
+        last_value: LastValue<B>,
+    ) {
+        tasks.spawn("listener", async move {
+            while let Ok(sample) = subscriber.recv_async().await {
+                // Create Buffer from the received zbytes
+                let received_at = SystemTime::now();
+                let buffer = B::from_zbytes(sample.payload().clone());
+
+                // Update the last received value
+                {
+                    let mut last = last_value.lock().await;
+                    *last = Some((buffer.clone(), received_at));
+                }
+
+                // Queue the buffer for the callbacks, each one runs in its own task
+                dispatcher.dispatch(buffer).await;
+            }
+        });
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Move the attribute to each new connection published on the channel
+    ///
+    /// The role of the current connection is applied now. On each new one,
+    /// commands use the new session and role and the subscription is declared
+    /// again, callbacks, last value and handles (including clones) are kept.
+    pub fn follow(&self, mut connections: watch::Receiver<Connection>) {
+        *self.role.write().unwrap() = connections.borrow().role.clone();
+        let shared = self.session.clone();
+        let shared_role = self.role.clone();
+        let dispatcher = self.dispatcher.clone();
+        let last_value = self.last_value.clone();
+        let att_topic = self.att_topic.clone();
+        // Weak reference: the follower must not keep the tasks alive
+        let tasks = Arc::downgrade(&self.tasks);
+        self.tasks.spawn("follow", async move {
+            while connections.changed().await.is_ok() {
+                let Connection { session, role } = connections.borrow_and_update().clone();
+                *shared.write().unwrap() = session.clone();
+                *shared_role.write().unwrap() = role;
+                match session.declare_subscriber(&att_topic).await {
+                    Ok(subscriber) => match tasks.upgrade() {
+                        Some(tasks) => Self::spawn_listener(
+                            &tasks,
+                            subscriber,
+                            dispatcher.clone(),
+                            last_value.clone(),
+                        ),
+                        None => return,
+                    },
+                    Err(e) => tracing::error!("Failed to subscribe again to {}: {}", att_topic, e),
+                }
+            }
+        });
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Track the background tasks of this attribute in a reactor scope
+    ///
+    pub fn track_in(&self, scope: &TaskScope) {
+        scope.track(&self.tasks);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of background tasks still running (receive loop, follower and
+    /// callback workers)
+    ///
+    pub fn running_tasks(&self) -> usize {
+        self.tasks.running()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Apply the rights of a role to the commands of this attribute
+    ///
+    /// Shared by the clones and replaced by the role of each new connection
+    /// when the attribute follows a reactor.
+    pub fn restrict_to(&self, role: Option<Role>) {
+        *self.role.write().unwrap() = role;
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Role applied to the commands, if any
+    ///
+    pub fn role(&self) -> Option<Role> {
+        self.role.read().unwrap().clone()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Check that the role can publish on the command topic
+    ///
+    fn check_publish(&self) -> Result<(), AttributeError> {
+        match self.role() {
+            Some(role) if !role.can_publish(&self.cmd_topic) => {
+                Err(AttributeError::PermissionDenied {
+                    role,
+                    access: Access::Publish,
+                    topic: self.cmd_topic.clone(),
+                })
+            }
+            _ => Ok(()),
         }
     }
 
     // ------------------------------------------------------------------------
 
-    /// Send command and do not wait for validation
+    /// Publish a command on the current session
     ///
-    pub async fn shoot(&mut self, buffer: B) {
-        let publisher = self
-            .session
+    /// The session may have been closed by a reopen or a shutdown of the
+    /// reactor, its errors are returned as [AttributeError::Session].
+    async fn send_command(&self, buffer: B) -> Result<(), AttributeError> {
+        self.check_publish()?;
+        let session = self.session.read().unwrap().clone();
+        let publisher = session
             .declare_publisher(&self.cmd_topic)
             .await
-            .expect("Failed to declare publisher");
-        publisher
-            .put(buffer.to_zbytes())
-            .await
-            .expect("Failed to send command");
+            .map_err(|e| {
+                AttributeError::Session(format!(
+                    "cannot declare publisher on {}: {}",
+                    self.cmd_topic, e
+                ))
+            })?;
+        publisher.put(buffer.to_zbytes()).await.map_err(|e| {
+            AttributeError::Session(format!("cannot send command on {}: {}", self.cmd_topic, e))
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Send command and do not wait for validation
+    ///
+    /// The command is dropped with a warning if the role cannot publish or the
+    /// session cannot send it.
+    pub async fn shoot(&mut self, buffer: B) {
+        if let Err(e) = self.send_command(buffer).await {
+            tracing::warn!("{}", e);
+        }
     }
 
     // ------------------------------------------------------------------------
 
     /// Send command and wait for validation
     ///
-    pub async fn set(&mut self, buffer: B) -> Result<(), String> {
+    /// Fails with [AttributeError::PermissionDenied] if the role cannot publish
+    /// and with [AttributeError::Session] if the session cannot send it.
+    pub async fn set(&mut self, buffer: B) -> Result<(), AttributeError> {
         let expected_buffer = buffer.clone();
 
-        self.shoot(buffer).await;
+        self.send_command(buffer).await?;
 
         //
         if self.metadata.mode == AttributeMode::ReadWrite {
@@ -154,7 +288,8 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
                 move |received_buffer| expected_buffer.has_same_message_value(received_buffer),
                 Some(std::time::Duration::from_secs(5)),
             )
-            .await?;
+            .await
+            .map_err(AttributeError::NotConfirmed)?;
         }
 
         Ok(())
@@ -164,6 +299,14 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
 
     /// Get last received value
     pub async fn get(&self) -> Option<B> {
+        let last = self.last_value.lock().await;
+        last.as_ref().map(|(buffer, _)| buffer.clone())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get last received value with the time it was received at
+    pub async fn get_received(&self) -> Option<(B, SystemTime)> {
         let last = self.last_value.lock().await;
         last.clone()
     }
@@ -182,7 +325,7 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
         // Check if the last_value already satisfies the condition
         {
             let last = self.last_value.lock().await;
-            if let Some(ref buffer) = *last {
+            if let Some((ref buffer, _)) = *last {
                 if condition(buffer) {
                     return Ok(buffer.clone());
                 }
@@ -192,8 +335,8 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
         // Use a broadcast channel to avoid the move issue
         let (tx, mut rx) = tokio::sync::broadcast::channel(1);
 
-        // Add temporary callback
-        let callback_id = self
+        // Add temporary callback, removed when the guard is dropped
+        let callback_guard = self
             .add_callback(
                 move |buffer: B| {
                     let buffer_clone = buffer.clone();
@@ -214,7 +357,7 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
         };
 
         // Remove the callback
-        self.remove_callback(callback_id).await;
+        drop(callback_guard);
 
         match result {
             Ok(Ok(buffer)) => Ok(buffer),
@@ -227,7 +370,9 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
 
     /// Add an async callback that will be triggered when receiving buffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    ///
+    /// The callback is removed when the returned guard is dropped.
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(B) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
@@ -235,43 +380,66 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
             + 'static,
         C: Fn(&B) -> bool + Send + Sync + 'static,
     {
-        let mut callbacks = self.callbacks.lock().await;
-        let mut next_id = self.next_callback_id.lock().await;
-
-        let callback_id = *next_id;
-        *next_id += 1;
-
         let callback_entry = CallbackEntry {
             callback: Box::new(callback),
             condition: condition.map(|c| Box::new(c) as Box<dyn Fn(&B) -> bool + Send + Sync>),
         };
-
-        callbacks.insert(callback_id, callback_entry);
-        callback_id
+        self.dispatcher.register(callback_entry)
     }
 
     // ------------------------------------------------------------------------
 
     /// Remove an async callback by its ID
     pub async fn remove_callback(&self, callback_id: CallbackId) -> bool {
-        let mut callbacks = self.callbacks.lock().await;
-        callbacks.remove(&callback_id).is_some()
+        self.dispatcher.remove(callback_id)
     }
 
     // ------------------------------------------------------------------------
 
     /// Clear all async callbacks
     pub async fn clear_callbacks(&self) {
-        let mut callbacks = self.callbacks.lock().await;
-        callbacks.clear();
+        self.dispatcher.clear();
     }
 
     // ------------------------------------------------------------------------
 
     /// Get the number of registered async callbacks
     pub async fn callback_count(&self) -> usize {
-        let callbacks = self.callbacks.lock().await;
-        callbacks.len()
+        self.dispatcher.count()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the queue of the callbacks added from now on
+    ///
+    /// Each callback runs in its own task and receives the samples through a
+    /// bounded queue, see [DispatchConfig].
+    pub fn set_dispatch_config(&self, config: DispatchConfig) {
+        self.dispatcher.set_config(config);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Queue settings of the callbacks added from now on
+    ///
+    pub fn dispatch_config(&self) -> DispatchConfig {
+        self.dispatcher.config()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Delivered, dropped and panicked samples of all the callbacks
+    ///
+    pub fn dispatch_metrics(&self) -> DispatchMetrics {
+        self.dispatcher.metrics()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Delivered, dropped and panicked samples of one callback
+    ///
+    pub fn callback_metrics(&self, callback_id: CallbackId) -> Option<DispatchMetrics> {
+        self.dispatcher.callback_metrics(callback_id)
     }
 
     // ------------------------------------------------------------------------
@@ -289,3 +457,226 @@ impl<B: PzaBuffer> StdObjAttribute<B> {
     }
     // ------------------------------------------------------------------------
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbs::BooleanBuffer;
+    use crate::test_support::open_session;
+    use crate::test_support::platform_and_client;
+    use crate::test_support::wait_for_subscriber;
+    use crate::TopicError;
+
+    fn buffer(value: bool) -> BooleanBuffer {
+        BooleanBuffer::builder()
+            .with_value(value)
+            .with_source(0)
+            .with_random_sequence()
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn follow_moves_subscription_to_new_session() {
+        let (platform, first, endpoint) = platform_and_client().await;
+        let second = open_session(None, Some(&endpoint)).await;
+
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/follow".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let attribute = StdObjAttribute::<BooleanBuffer>::new(first.clone(), metadata)
+            .await
+            .unwrap();
+        let (sessions, _) = watch::channel(Connection::new(first.clone()));
+        attribute.follow(sessions.subscribe());
+        let handle = attribute.clone();
+
+        wait_for_subscriber(&platform, "pza/test/follow/att").await;
+        platform
+            .put("pza/test/follow/att", buffer(true).to_zbytes())
+            .await
+            .unwrap();
+        assert!(handle
+            .wait_for_value(
+                |b| b.value() == Some(true),
+                Some(std::time::Duration::from_secs(5))
+            )
+            .await
+            .is_ok());
+
+        // Move to the second session and close the first one, the value is
+        // published again until the new subscription receives it
+        sessions.send_replace(Connection::new(second.clone()));
+        first.close().await.unwrap();
+        let received = tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            loop {
+                platform
+                    .put("pza/test/follow/att", buffer(false).to_zbytes())
+                    .await
+                    .unwrap();
+                let received = handle
+                    .wait_for_value(
+                        |b| b.value() == Some(false),
+                        Some(std::time::Duration::from_millis(100)),
+                    )
+                    .await;
+                if received.is_ok() {
+                    break;
+                }
+            }
+        })
+        .await;
+        assert!(received.is_ok());
+        assert!(Arc::ptr_eq(&handle.session, &attribute.session));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn role_follows_the_connection() {
+        let session = open_session(None, None).await;
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/role".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let mut attribute = StdObjAttribute::<BooleanBuffer>::new(session.clone(), metadata)
+            .await
+            .unwrap();
+        let (connections, _) =
+            watch::channel(Connection::with_role(session.clone(), Some(Role::Writer)));
+        attribute.follow(connections.subscribe());
+        assert_eq!(attribute.role(), Some(Role::Writer));
+
+        // The new role applies to the existing handles
+        connections.send_replace(Connection::with_role(session.clone(), Some(Role::Logger)));
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while attribute.role() != Some(Role::Logger) {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            attribute.set(buffer(true)).await,
+            Err(AttributeError::PermissionDenied { .. })
+        ));
+        session.close().await.unwrap();
+    }
+
+    /// Wait until the monitor counts `expected` alive tasks
+    async fn wait_task_count(monitor: &crate::TaskMonitor, expected: usize) {
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while monitor.task_count().await != expected {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} monitored tasks", expected));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn commands_on_a_closed_session_are_errors() {
+        let session = open_session(None, None).await;
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/closed".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let mut attribute = StdObjAttribute::<BooleanBuffer>::new(session.clone(), metadata)
+            .await
+            .unwrap();
+        session.close().await.unwrap();
+
+        assert!(matches!(
+            attribute.set(buffer(true)).await,
+            Err(AttributeError::Session(_))
+        ));
+        attribute.shoot(buffer(true)).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn creation_on_a_closed_session_is_an_error() {
+        let session = open_session(None, None).await;
+        session.close().await.unwrap();
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/closed".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::ReadWrite,
+        );
+        assert!(matches!(
+            StdObjAttribute::<BooleanBuffer>::new(session, metadata).await,
+            Err(AttributeError::Session(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn invalid_topic_is_an_error() {
+        let session = open_session(None, None).await;
+        let metadata = AttributeMetadata::from_topic(
+            "bench/voltage".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        assert!(matches!(
+            StdObjAttribute::<BooleanBuffer>::new(session.clone(), metadata).await,
+            Err(AttributeError::Topic(TopicError::MissingPza(_)))
+        ));
+        session.close().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tasks_are_owned_by_the_attribute() {
+        let session = open_session(None, None).await;
+        let (monitor, _events) = crate::TaskMonitor::new("attributes");
+        let scope = TaskScope::default();
+
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/tasks".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let attribute = StdObjAttribute::<BooleanBuffer>::new(session.clone(), metadata)
+            .await
+            .unwrap();
+        let (sessions, _) = watch::channel(Connection::new(session.clone()));
+        attribute.follow(sessions.subscribe());
+        attribute.track_in(&scope);
+
+        // Tasks created before the monitor are reported too
+        scope.monitor_with(monitor.handle_sender());
+        wait_task_count(&monitor, 2).await;
+        assert!(monitor.find("pza/test/tasks/listener").await.is_some());
+        assert!(monitor.find("pza/test/tasks/follow").await.is_some());
+
+        // Tasks live as long as one handle
+        let handle = attribute.clone();
+        drop(attribute);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(handle.running_tasks(), 2);
+        drop(handle);
+        wait_task_count(&monitor, 0).await;
+
+        // The scope stops the tasks of attributes still alive
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/tasks".to_string(),
+            Some("boolean".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let attribute = StdObjAttribute::<BooleanBuffer>::new(session.clone(), metadata)
+            .await
+            .unwrap();
+        attribute.track_in(&scope);
+        let _callback = attribute
+            .add_callback(|_| Box::pin(async {}), None::<fn(&BooleanBuffer) -> bool>)
+            .await;
+        wait_task_count(&monitor, 2).await;
+        assert!(monitor.find("pza/test/tasks/callback/0").await.is_some());
+        scope.abort_all();
+        wait_task_count(&monitor, 0).await;
+        assert_eq!(attribute.running_tasks(), 0);
+
+        monitor.stop().await;
+        session.close().await.unwrap();
+    }
+}
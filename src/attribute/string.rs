@@ -1,4 +1,7 @@
+use super::sample::Sample;
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::StringBuffer;
 use crate::AttributeMetadata;
@@ -14,15 +17,20 @@ pub struct StringAttribute {
 impl StringAttribute {
     /// Create a new instance
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        let inner = StdObjAttribute::<StringBuffer>::new(session, metadata).await;
-        Self { inner }
+    /// # Returns
+    /// An error if the topic of the metadata is not a valid attribute topic
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let inner = StdObjAttribute::<StringBuffer>::new(session, metadata).await?;
+        Ok(Self { inner })
     }
 
     /// Set the value and wait for validation
     ///
     #[inline]
-    pub async fn set(&mut self, value: String) -> Result<(), String> {
+    pub async fn set(&mut self, value: String) -> Result<(), AttributeError> {
         self.inner
             .set(
                 StringBuffer::builder()
@@ -42,6 +50,14 @@ impl StringAttribute {
         self.inner.get().await
     }
 
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub async fn sample(&self) -> Option<Sample<String>> {
+        let (buffer, received_at) = self.inner.get_received().await?;
+        let value = buffer.value().map(str::to_string)?;
+        Some(Sample::from_buffer(&buffer, value, received_at))
+    }
+
     /// Wait for a specific string value to be received
     ///
     #[inline]
@@ -62,7 +78,7 @@ impl StringAttribute {
     /// Add a callback that will be triggered when receiving StringBuffer messages
     /// Optionally, a condition can be provided to filter when the callback is triggered
     #[inline]
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(StringBuffer) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
             + Send
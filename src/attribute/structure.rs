@@ -8,6 +8,8 @@ use tokio::sync::Mutex;
 use zenoh::Session;
 
 use super::std_obj::StdObjAttribute;
+use super::AttributeError;
+use super::CallbackGuard;
 use super::CallbackId;
 use crate::fbs::PzaBuffer;
 use crate::fbs::StructureBuffer;
@@ -46,8 +48,11 @@ impl StructureAttribute {
 
     // ------------------------------------------------------------------------
     ///
-    pub async fn new(session: Session, metadata: AttributeMetadata) -> Self {
-        let inner = StdObjAttribute::<StructureBuffer>::new(session, metadata).await;
+    pub async fn new(
+        session: Session,
+        metadata: AttributeMetadata,
+    ) -> Result<Self, AttributeError> {
+        let inner = StdObjAttribute::<StructureBuffer>::new(session, metadata).await?;
         let flat = Arc::new(Mutex::new(HashMap::new()));
 
         let instance = Self {
@@ -73,7 +78,8 @@ impl StructureAttribute {
                 },
                 None::<fn(&StructureBuffer) -> bool>,
             )
-            .await;
+            .await
+            .detach();
 
         // Initialize flat from current buffer if available
         if let Some(buffer) = instance.inner.get().await {
@@ -81,7 +87,7 @@ impl StructureAttribute {
             Self::update_flat_from_buffer(&mut flat_guard, &buffer, &instance.metadata().topic);
         }
 
-        instance
+        Ok(instance)
     }
 
     // ------------------------------------------------------------------------
@@ -177,7 +183,7 @@ impl StructureAttribute {
 
     /// Registers a callback triggered on StructureBuffer reception
     ///
-    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackId
+    pub async fn add_callback<F, C>(&self, callback: F, condition: Option<C>) -> CallbackGuard
     where
         F: Fn(StructureBuffer) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
         C: Fn(&StructureBuffer) -> bool + Send + Sync + 'static,
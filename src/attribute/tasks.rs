@@ -0,0 +1,227 @@
+use crate::task_monitor::TaskHandle;
+use crate::task_monitor::TaskMonitorLink;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::AbortHandle;
+
+/// Background task of an attribute
+///
+#[derive(Debug)]
+struct OwnedTask {
+    /// Stops the task
+    ///
+    abort: AbortHandle,
+
+    /// Handle not yet given to a task monitor
+    ///
+    handle: Option<TaskHandle>,
+}
+
+/// State of the tasks of an attribute
+///
+#[derive(Debug, Default)]
+struct TasksState {
+    /// Running tasks by kind, a new task replaces the previous one of its kind
+    ///
+    tasks: HashMap<String, OwnedTask>,
+
+    /// Monitor the tasks are reported to, if any
+    ///
+    monitor: Option<TaskMonitorLink>,
+}
+
+/// Background tasks owned by an attribute
+///
+/// Shared by all the clones of an attribute, the tasks are aborted when the
+/// last clone is dropped. Aborting the receive loop drops its subscriber, which
+/// undeclares it.
+///
+#[derive(Debug)]
+pub struct AttributeTasks {
+    /// Prefix of the task names (the attribute topic)
+    ///
+    name: String,
+
+    /// Tasks and monitor
+    ///
+    state: Mutex<TasksState>,
+}
+
+impl AttributeTasks {
+    /// Create an empty set of tasks
+    ///
+    pub fn new<N: Into<String>>(name: N) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.into(),
+            state: Mutex::new(TasksState::default()),
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Spawn a task of the given kind, aborting the previous one of this kind
+    ///
+    /// The task is named `{topic}/{kind}` in the task monitor. Finished tasks
+    /// are forgotten.
+    pub fn spawn<K, F>(&self, kind: K, future: F)
+    where
+        K: Into<String>,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let kind = kind.into();
+        let handle: TaskHandle = tokio::spawn(async move {
+            future.await;
+            Ok(())
+        });
+        let mut task = OwnedTask {
+            abort: handle.abort_handle(),
+            handle: Some(handle),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.tasks.retain(|_, task| !task.abort.is_finished());
+        if let Some(monitor) = &state.monitor {
+            task.handle = self.report(monitor, &kind, task.handle.take());
+        }
+        if let Some(previous) = state.tasks.insert(kind, task) {
+            previous.abort.abort();
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Report the current and future tasks to a task monitor
+    ///
+    pub fn monitor_with(&self, monitor: TaskMonitorLink) {
+        let mut state = self.state.lock().unwrap();
+        for (kind, task) in state.tasks.iter_mut() {
+            task.handle = self.report(&monitor, kind, task.handle.take());
+        }
+        state.monitor = Some(monitor);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Give a task handle to the monitor
+    ///
+    /// # Returns
+    /// The handle if the monitor cannot take it now
+    fn report(
+        &self,
+        monitor: &TaskMonitorLink,
+        kind: &str,
+        handle: Option<TaskHandle>,
+    ) -> Option<TaskHandle> {
+        let handle = handle?;
+        match monitor.try_send((format!("{}/{}", self.name, kind), handle)) {
+            Ok(()) => None,
+            Err(TrySendError::Full((_, handle))) | Err(TrySendError::Closed((_, handle))) => {
+                tracing::warn!("Task {}/{} not reported to the monitor", self.name, kind);
+                Some(handle)
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of tasks still running
+    ///
+    pub fn running(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .tasks
+            .values()
+            .filter(|task| !task.abort.is_finished())
+            .count()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Abort all the tasks
+    ///
+    pub fn abort_all(&self) {
+        for (_, task) in self.state.lock().unwrap().tasks.drain() {
+            task.abort.abort();
+        }
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+impl Drop for AttributeTasks {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+/// State of a task scope
+///
+#[derive(Debug, Default)]
+struct ScopeState {
+    /// Tasks of the attributes created in the scope
+    ///
+    owners: Vec<Weak<AttributeTasks>>,
+
+    /// Monitor given to the attributes of the scope
+    ///
+    monitor: Option<TaskMonitorLink>,
+}
+
+/// Tasks of all the attributes created by a reactor
+///
+/// The scope does not keep the attributes alive, it can abort their tasks and
+/// report them to a task monitor.
+///
+#[derive(Debug, Clone, Default)]
+pub struct TaskScope {
+    state: Arc<Mutex<ScopeState>>,
+}
+
+impl TaskScope {
+    /// Track the tasks of an attribute
+    ///
+    pub fn track(&self, tasks: &Arc<AttributeTasks>) {
+        let monitor = {
+            let mut state = self.state.lock().unwrap();
+            state.owners.retain(|owner| owner.strong_count() > 0);
+            state.owners.push(Arc::downgrade(tasks));
+            state.monitor.clone()
+        };
+        if let Some(monitor) = monitor {
+            tasks.monitor_with(monitor);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Report the tasks of the scope, current and future, to a task monitor
+    ///
+    pub fn monitor_with(&self, monitor: TaskMonitorLink) {
+        let owners = {
+            let mut state = self.state.lock().unwrap();
+            state.monitor = Some(monitor.clone());
+            state.owners.clone()
+        };
+        for owner in owners.iter().filter_map(Weak::upgrade) {
+            owner.monitor_with(monitor.clone());
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Abort the tasks of all the attributes of the scope
+    ///
+    pub fn abort_all(&self) {
+        let owners = std::mem::take(&mut self.state.lock().unwrap().owners);
+        for owner in owners.iter().filter_map(Weak::upgrade) {
+            owner.abort_all();
+        }
+    }
+
+    // ------------------------------------------------------------------------
+}
@@ -1,12 +1,20 @@
 use crate::attribute::bytes::BytesAttribute;
 use crate::attribute::notification::NotificationAttribute;
 use crate::attribute::number::NumberAttribute;
+use crate::attribute::ro_stream::RoStreamAttribute;
 use crate::attribute::status::StatusAttribute;
+use crate::attribute::std_obj::StdObjAttribute;
+use crate::attribute::tasks::TaskScope;
 use crate::attribute::AttributeError;
 use crate::attribute_metadata::AttributeMetadata;
+use crate::fbs::PzaBuffer;
+use crate::reactor::Access;
+use crate::reactor::Connection;
 use crate::reactor::Reactor;
+use crate::AttributeMode;
 use crate::BooleanAttribute;
 use crate::StringAttribute;
+use tokio::sync::watch;
 
 /// Macro to create a metadata not found error
 macro_rules! meta_data_not_found {
@@ -40,62 +48,79 @@ impl AttributeBuilder {
 
     // ------------------------------------------------------------------------
 
-    /// BOOLEAN
+    /// Metadata of the attribute, checked against the expected type and the role
     ///
-    pub async fn try_into_boolean(self) -> Result<BooleanAttribute, AttributeError> {
-        // Check if metadata is available
+    /// Readable attributes that the role cannot subscribe to are refused: the
+    /// router would drop the subscription silently and the attribute would
+    /// never receive any value.
+    fn checked_metadata(&self, r#type: &str) -> Result<AttributeMetadata, AttributeError> {
         let metadata = self
             .metadata
-            .ok_or_else(|| meta_data_not_found!("boolean"))?;
+            .clone()
+            .ok_or_else(|| meta_data_not_found!(r#type))?;
 
-        // Check if the attribute is a boolean type
-        if metadata.r#type != "boolean" {
+        if metadata.r#type != r#type {
             return Err(AttributeError::InvalidType(
-                "boolean".to_string(),
+                r#type.to_string(),
                 metadata.r#type.clone(),
             ));
         }
 
-        // Create and return the BooleanAttribute
-        Ok(BooleanAttribute::new(self.reactor.session, metadata).await)
+        if metadata.mode != AttributeMode::WriteOnly {
+            if let Some(role) = self.reactor.role() {
+                if !role.can_subscribe(&metadata.topic) {
+                    return Err(AttributeError::PermissionDenied {
+                        role,
+                        access: Access::Subscribe,
+                        topic: metadata.topic.clone(),
+                    });
+                }
+            }
+        }
+        Ok(metadata)
     }
 
     // ------------------------------------------------------------------------
 
-    /// NUMBER
+    /// Move the attribute along the reactor connection and stop its tasks on
+    /// reactor shutdown
     ///
-    pub async fn try_into_number(self) -> Result<NumberAttribute, AttributeError> {
-        let metadata = self
-            .metadata
-            .ok_or_else(|| meta_data_not_found!("number"))?;
+    fn attach<I: ReactorLink>(&self, inner: &I) {
+        inner.follow(self.reactor.connection_updates());
+        inner.track_in(self.reactor.task_scope());
+    }
 
-        if metadata.r#type != "number" {
-            return Err(AttributeError::InvalidType(
-                "number".to_string(),
-                metadata.r#type.clone(),
-            ));
-        }
+    // ------------------------------------------------------------------------
 
-        Ok(NumberAttribute::new(self.reactor.session, metadata).await)
+    /// BOOLEAN
+    ///
+    pub async fn try_into_boolean(self) -> Result<BooleanAttribute, AttributeError> {
+        let metadata = self.checked_metadata("boolean")?;
+        let attribute = BooleanAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
     }
 
     // ------------------------------------------------------------------------
 
-    /// STRING
+    /// NUMBER
     ///
-    pub async fn try_into_string(self) -> Result<StringAttribute, AttributeError> {
-        let metadata = self
-            .metadata
-            .ok_or_else(|| meta_data_not_found!("string"))?;
+    pub async fn try_into_number(self) -> Result<NumberAttribute, AttributeError> {
+        let metadata = self.checked_metadata("number")?;
+        let attribute = NumberAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
+    }
 
-        if metadata.r#type != "string" {
-            return Err(AttributeError::InvalidType(
-                "string".to_string(),
-                metadata.r#type.clone(),
-            ));
-        }
+    // ------------------------------------------------------------------------
 
-        Ok(StringAttribute::new(self.reactor.session, metadata).await)
+    /// STRING
+    ///
+    pub async fn try_into_string(self) -> Result<StringAttribute, AttributeError> {
+        let metadata = self.checked_metadata("string")?;
+        let attribute = StringAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
     }
 
     // ------------------------------------------------------------------------
@@ -103,18 +128,10 @@ impl AttributeBuilder {
     /// BYTES
     ///
     pub async fn try_into_bytes(self) -> Result<BytesAttribute, AttributeError> {
-        let metadata = self
-            .metadata
-            .ok_or_else(|| meta_data_not_found!("bytes"))?;
-
-        if metadata.r#type != "bytes" {
-            return Err(AttributeError::InvalidType(
-                "bytes".to_string(),
-                metadata.r#type.clone(),
-            ));
-        }
-
-        Ok(BytesAttribute::new(self.reactor.session, metadata).await)
+        let metadata = self.checked_metadata("bytes")?;
+        let attribute = BytesAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
     }
 
     // ------------------------------------------------------------------------
@@ -122,18 +139,10 @@ impl AttributeBuilder {
     /// STATUS
     ///
     pub async fn try_into_status(self) -> Result<StatusAttribute, AttributeError> {
-        let metadata = self
-            .metadata
-            .ok_or_else(|| meta_data_not_found!("status"))?;
-
-        if metadata.r#type != "status" {
-            return Err(AttributeError::InvalidType(
-                "status".to_string(),
-                metadata.r#type.clone(),
-            ));
-        }
-
-        Ok(StatusAttribute::new(self.reactor.session, metadata).await)
+        let metadata = self.checked_metadata("status")?;
+        let attribute = StatusAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
     }
 
     // ------------------------------------------------------------------------
@@ -141,19 +150,38 @@ impl AttributeBuilder {
     /// NOTIFICATION
     ///
     pub async fn try_into_notification(self) -> Result<NotificationAttribute, AttributeError> {
-        let metadata = self
-            .metadata
-            .ok_or_else(|| meta_data_not_found!("notification"))?;
+        let metadata = self.checked_metadata("notification")?;
+        let attribute = NotificationAttribute::new(self.reactor.current_session(), metadata).await?;
+        self.attach(&attribute.inner);
+        Ok(attribute)
+    }
 
-        if metadata.r#type != "notification" {
-            return Err(AttributeError::InvalidType(
-                "notification".to_string(),
-                metadata.r#type.clone(),
-            ));
-        }
+    // ------------------------------------------------------------------------
+}
+
+/// Inner object of an attribute that follows the reactor connection
+///
+trait ReactorLink {
+    fn follow(&self, connections: watch::Receiver<Connection>);
+    fn track_in(&self, scope: &TaskScope);
+}
 
-        Ok(NotificationAttribute::new(self.reactor.session, metadata).await)
+impl<B: PzaBuffer> ReactorLink for StdObjAttribute<B> {
+    fn follow(&self, connections: watch::Receiver<Connection>) {
+        StdObjAttribute::follow(self, connections)
     }
 
-    // ------------------------------------------------------------------------
+    fn track_in(&self, scope: &TaskScope) {
+        StdObjAttribute::track_in(self, scope)
+    }
+}
+
+impl<B: PzaBuffer> ReactorLink for RoStreamAttribute<B> {
+    fn follow(&self, connections: watch::Receiver<Connection>) {
+        RoStreamAttribute::follow(self, connections)
+    }
+
+    fn track_in(&self, scope: &TaskScope) {
+        RoStreamAttribute::track_in(self, scope)
+    }
 }
@@ -1,3 +1,4 @@
+use crate::reactor::ReactorConfig;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,10 +13,10 @@ pub struct TestConfig {
 pub struct BenchmarkConfig {
     pub tests: TestConfig,
     pub number_attributes: u16,
-    pub ip: String,
-    pub port: u16,
-    pub ca_certificate: String,
-    pub namespace: Option<String>,
+    /// Connection settings, at the top level of the file (`ip` is accepted
+    /// for `address`)
+    #[serde(flatten)]
+    pub reactor: ReactorConfig,
 }
 
 impl TestConfig {
@@ -24,3 +25,39 @@ impl TestConfig {
         self.bytes + (self.kilobytes * 1024) + (self.megabytes * 1024 * 1024)
     }
 }
+
+impl BenchmarkConfig {
+    /// Connection settings of the benchmark as a reactor configuration
+    pub fn reactor_config(&self) -> ReactorConfig {
+        self.reactor.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_settings_are_a_reactor_config() {
+        let config: BenchmarkConfig = serde_json::from_str(
+            r#"{
+                "tests": { "bytes": 1, "kilobytes": 0, "megabytes": 0, "total_messages": 10 },
+                "number_attributes": 2,
+                "ip": "127.0.0.1",
+                "port": 7447,
+                "ca_certificate": "root_ca_certificate.pem",
+                "connect_private_key_passphrase": "secret"
+            }"#,
+        )
+        .unwrap();
+        let reactor = config.reactor_config();
+        assert_eq!(reactor.address.as_deref(), Some("127.0.0.1"));
+        assert_eq!(reactor.port, Some(7447));
+        assert_eq!(
+            reactor.ca_certificate.as_deref(),
+            Some("root_ca_certificate.pem")
+        );
+        assert!(reactor.connect_private_key_passphrase.is_some());
+        assert_eq!(reactor.namespace, None);
+    }
+}
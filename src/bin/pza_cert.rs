@@ -0,0 +1,676 @@
+//! pza-cert: certificate management for Panduza
+//!
+//! The root CA lives in the machine-wide panduza directory (`%PROGRAMDATA%/panduza` on
+//! Windows, the user data directory elsewhere). Client credentials are written in the
+//! user `.panduza/{certificate,keys}` directories, as expected by `ReactorBuilder`.
+//! The certificate revocation list is kept next to the root CA.
+//!
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use panduza::security::bundle::Identity;
+use panduza::security::bundle::InstallOptions;
+use panduza::security::certificate::generate_cert_server_with_san;
+use panduza::security::certificate::generate_csr_client;
+use panduza::security::certificate::generate_root_ca;
+use panduza::security::certificate::generate_root_ca_with_key;
+use panduza::security::certificate::renew_certificate;
+use panduza::security::certificate::sign_csr_with_ca;
+use panduza::security::certificate::CertParams;
+use panduza::security::inspect::inspect_file;
+use panduza::security::inspect::read_pem_file;
+use panduza::security::inspect::verify_chain;
+use panduza::security::inspect::verify_chain_with_crl;
+use panduza::security::inspect::CertificateSummary;
+use panduza::security::key_protection::encrypt_key;
+use panduza::security::key_protection::is_encrypted_pem;
+use panduza::security::key_protection::load_key_file;
+use panduza::security::revocation::crl_path;
+use panduza::security::revocation::generate_crl;
+use panduza::security::revocation::load_crl;
+use panduza::security::revocation::parse_crl;
+use panduza::security::revocation::refresh_crl;
+use panduza::security::revocation::revoke_serial;
+use panduza::security::revocation::save_crl;
+use panduza::security::utils::default_certificate_paths;
+use panduza::security::utils::ensure_panduza_programdata_dirs;
+use panduza::security::utils::generate_and_store_client_credentials;
+use panduza::security::utils::generate_key;
+use panduza::security::utils::write_atomic;
+use panduza::security::utils::write_panduza_file;
+use panduza::security::utils::write_panduza_file_programdata;
+use panduza::security::utils::write_private_atomic;
+use panduza::security::utils::PanduzaFileType;
+use std::path::Path;
+use std::path::PathBuf;
+
+const ROOT_CA_PRIVATE_KEY: &str = "root_ca_private_key.pem";
+const ROOT_CA_CERTIFICATE: &str = "root_ca_certificate.pem";
+/// Passphrase of the stored private keys, as read by ReactorBuilder
+const KEY_PASSPHRASE_ENV: &str = "PZA_CONNECT_PRIVATE_KEY_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(name = "pza-cert", version, about = "Panduza certificate management")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the root CA in the machine-wide panduza directory
+    InitCa {
+        /// Replace an existing root CA (every issued certificate becomes invalid)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Issue a server (router) certificate signed by the root CA
+    IssueServer {
+        /// Base name of the written files: <name>_certificate.pem and <name>_private_key.pem
+        #[arg(long, default_value = "platform")]
+        name: String,
+        /// Common name of the certificate
+        #[arg(long, default_value = "platform.local")]
+        cn: String,
+        /// Addresses and domains of the router
+        #[arg(long, num_args = 1.., default_values_t = default_san())]
+        san: Vec<String>,
+        /// Validity in days
+        #[arg(long, default_value_t = 365)]
+        days: i32,
+    },
+
+    /// Issue a client certificate for a role in the user .panduza directory
+    IssueClient {
+        /// Role of the user: writer, reader, logger, platform, admin...
+        #[arg(long)]
+        role: String,
+        /// Addresses and domains of the router
+        #[arg(long, num_args = 1.., default_values_t = default_san())]
+        san: Vec<String>,
+        /// Validity in days
+        #[arg(long, default_value_t = 365)]
+        days: i32,
+        /// Also install the credentials as the default ones used by ReactorBuilder
+        #[arg(long)]
+        default: bool,
+    },
+
+    /// Generate a private key and a certificate signing request in the user .panduza directory
+    Csr {
+        /// Base name of the written files: <name>_private_key.pem and <name>.csr
+        #[arg(long, default_value = "client")]
+        name: String,
+        /// Addresses and domains of the router
+        #[arg(long, num_args = 1.., default_values_t = default_san())]
+        san: Vec<String>,
+        /// Requested validity in days
+        #[arg(long, default_value_t = 365)]
+        days: i32,
+    },
+
+    /// Sign a certificate signing request with the root CA
+    SignCsr {
+        /// Path to the .csr file
+        csr: PathBuf,
+        /// Role granted to the certificate (common name <role>.local)
+        #[arg(long)]
+        role: String,
+        /// Validity in days
+        #[arg(long, default_value_t = 365)]
+        days: i32,
+        /// Output certificate (default: <csr>_certificate.pem next to the request)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Print the content of a certificate and check it against a root CA
+    Inspect {
+        /// Path to the certificate
+        file: PathBuf,
+        /// Root CA certificate (default: the user default CA, if present)
+        #[arg(long)]
+        ca: Option<PathBuf>,
+        /// Revocation list (default: the CRL next to the root CA, if present)
+        #[arg(long)]
+        crl: Option<PathBuf>,
+    },
+
+    /// Revoke a certificate and update the revocation list
+    Revoke {
+        /// Serial number (hexadecimal) or path to the certificate
+        target: String,
+        /// Reason recorded in the revocation list
+        #[arg(long, value_enum)]
+        reason: Option<Reason>,
+    },
+
+    /// Sign the revocation list again (new validity window) and print it
+    Crl {
+        /// Only print the current list
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// Re-issue a certificate with a new validity, keeping its identity and key
+    Renew {
+        /// Path to the certificate to renew
+        file: PathBuf,
+        /// Validity in days
+        #[arg(long, default_value_t = 365)]
+        days: i32,
+        /// Output certificate (default: replace the input file)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Encrypt a private key file in place with a passphrase (PKCS#8)
+    ProtectKey {
+        /// Path to the private key
+        file: PathBuf,
+        /// Environment variable holding the passphrase
+        #[arg(long, default_value = KEY_PASSPHRASE_ENV)]
+        passphrase_env: String,
+    },
+
+    /// Export a client identity (certificate, key and root CA) as a single bundle
+    Export {
+        /// Base name of the identity in the user .panduza directory
+        #[arg(long, default_value = "client")]
+        name: String,
+        /// Bundle format
+        #[arg(long, value_enum, default_value_t = BundleFormat::P12)]
+        format: BundleFormat,
+        /// Output bundle
+        #[arg(long)]
+        out: PathBuf,
+        /// Environment variable holding the bundle password (required for p12)
+        #[arg(long, default_value = "PZA_BUNDLE_PASSWORD")]
+        password_env: String,
+    },
+
+    /// Import a PKCS#12 or PEM bundle in the user .panduza directory
+    Import {
+        /// Path to the bundle (.p12, .pfx or .pem)
+        file: PathBuf,
+        /// Base name of the installed files (client: default credentials)
+        #[arg(long, default_value = "client")]
+        name: String,
+        /// Environment variable holding the bundle password
+        #[arg(long, default_value = "PZA_BUNDLE_PASSWORD")]
+        password_env: String,
+        /// Install the root CA of the bundle for a name other than client
+        #[arg(long)]
+        root_ca: bool,
+        /// Replace a different root CA already installed
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Formats of an exported identity
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BundleFormat {
+    /// PKCS#12 (.p12/.pfx), protected by a password
+    P12,
+    /// Concatenated PEM, the key is encrypted when a password is set
+    Pem,
+}
+
+/// Revocation reasons offered on the command line
+#[derive(Clone, Copy, ValueEnum)]
+enum Reason {
+    Unspecified,
+    KeyCompromise,
+    AffiliationChanged,
+    Superseded,
+    CessationOfOperation,
+}
+
+impl From<Reason> for rcgen::RevocationReason {
+    fn from(reason: Reason) -> Self {
+        match reason {
+            Reason::Unspecified => rcgen::RevocationReason::Unspecified,
+            Reason::KeyCompromise => rcgen::RevocationReason::KeyCompromise,
+            Reason::AffiliationChanged => rcgen::RevocationReason::AffiliationChanged,
+            Reason::Superseded => rcgen::RevocationReason::Superseded,
+            Reason::CessationOfOperation => rcgen::RevocationReason::CessationOfOperation,
+        }
+    }
+}
+
+fn default_san() -> Vec<String> {
+    vec!["localhost".to_string(), "127.0.0.1".to_string()]
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::InitCa { force } => init_ca(force),
+        Command::IssueServer {
+            name,
+            cn,
+            san,
+            days,
+        } => issue_server(&name, cn, san, days),
+        Command::IssueClient {
+            role,
+            san,
+            days,
+            default,
+        } => issue_client(&role, san, days, default),
+        Command::Csr { name, san, days } => csr(&name, san, days),
+        Command::SignCsr {
+            csr,
+            role,
+            days,
+            out,
+        } => sign_csr(&csr, &role, days, out),
+        Command::Inspect { file, ca, crl } => inspect(&file, ca, crl),
+        Command::Revoke { target, reason } => revoke(&target, reason),
+        Command::Crl { show } => crl(show),
+        Command::Renew { file, days, out } => renew(&file, days, out),
+        Command::ProtectKey {
+            file,
+            passphrase_env,
+        } => protect_key(&file, &passphrase_env),
+        Command::Export {
+            name,
+            format,
+            out,
+            password_env,
+        } => export(&name, format, &out, &password_env),
+        Command::Import {
+            file,
+            name,
+            password_env,
+            root_ca,
+            force,
+        } => import(
+            &file,
+            &name,
+            &password_env,
+            InstallOptions { root_ca, force },
+        ),
+    }
+}
+
+/// Paths of the root CA private key and certificate
+fn root_ca_paths() -> Result<(PathBuf, PathBuf)> {
+    let (keys_dir, cert_dir) =
+        ensure_panduza_programdata_dirs().context("Cannot create the panduza directories")?;
+    Ok((
+        keys_dir.join(ROOT_CA_PRIVATE_KEY),
+        cert_dir.join(ROOT_CA_CERTIFICATE),
+    ))
+}
+
+/// Load the root CA, with a clear error if `init-ca` was not run
+fn load_root_ca() -> Result<(rcgen::Certificate, rcgen::KeyPair)> {
+    let (key_path, _) = root_ca_paths()?;
+    if !key_path.exists() {
+        return Err(anyhow!(
+            "No root CA found at {} (run `pza-cert init-ca` first)",
+            key_path.display()
+        ));
+    }
+    let key_passphrase = secret_from_env(KEY_PASSPHRASE_ENV);
+    let key = load_key_file(&key_path, key_passphrase.as_deref())
+        .with_context(|| format!("Failed to load the root CA key {}", key_path.display()))?;
+    Ok((generate_root_ca_with_key(&key), key))
+}
+
+/// Check that a name can be used in a file name
+fn check_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Invalid {} {:?} (letters, digits, '-' and '_' only)",
+            kind,
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn init_ca(force: bool) -> Result<()> {
+    let (key_path, _) = root_ca_paths()?;
+    if key_path.exists() && !force {
+        return Err(anyhow!(
+            "A root CA already exists at {} (use --force to replace it)",
+            key_path.display()
+        ));
+    }
+
+    let (ca, ca_key) = generate_root_ca();
+    let key_path = write_panduza_file_programdata(
+        PanduzaFileType::Key,
+        ROOT_CA_PRIVATE_KEY,
+        &ca_key.serialize_pem(),
+    )?;
+    let cert_path = write_panduza_file_programdata(
+        PanduzaFileType::Certificate,
+        ROOT_CA_CERTIFICATE,
+        &ca.pem(),
+    )?;
+    // Clients trust the CA through their default path
+    let user_cert_path =
+        write_panduza_file(PanduzaFileType::Certificate, ROOT_CA_CERTIFICATE, &ca.pem())?;
+
+    println!("Root CA private key : {}", key_path.display());
+    println!("Root CA certificate : {}", cert_path.display());
+    println!("User CA certificate : {}", user_cert_path.display());
+    Ok(())
+}
+
+fn issue_server(name: &str, cn: String, san: Vec<String>, days: i32) -> Result<()> {
+    check_name("name", name)?;
+    let (ca, ca_key) = load_root_ca()?;
+    let (cert, key) = generate_cert_server_with_san(
+        &ca,
+        &ca_key,
+        CertParams {
+            san,
+            validity_days: days,
+            common_name: cn,
+        },
+    );
+
+    let cert_path = write_panduza_file_programdata(
+        PanduzaFileType::Certificate,
+        &format!("{}_certificate.pem", name),
+        &cert.pem(),
+    )?;
+    let key_path = write_panduza_file_programdata(
+        PanduzaFileType::Key,
+        &format!("{}_private_key.pem", name),
+        &key.serialize_pem(),
+    )?;
+
+    println!("Server certificate : {}", cert_path.display());
+    println!("Server private key : {}", key_path.display());
+    Ok(())
+}
+
+fn issue_client(role: &str, san: Vec<String>, days: i32, default: bool) -> Result<()> {
+    check_name("role", role)?;
+    load_root_ca()?;
+    let (ca_path, cert_path, key_path) = generate_and_store_client_credentials(role, san, days)
+        .map_err(|e| anyhow!("Failed to issue the {} certificate: {}", role, e))?;
+
+    println!("Root CA certificate : {}", ca_path);
+    println!("Client certificate  : {}", cert_path);
+    println!("Client private key  : {}", key_path);
+
+    if default {
+        let cert = std::fs::read_to_string(&cert_path)?;
+        let key = std::fs::read_to_string(&key_path)?;
+        let ca = std::fs::read_to_string(&ca_path)?;
+        write_panduza_file(
+            PanduzaFileType::Certificate,
+            "client_certificate.pem",
+            &cert,
+        )?;
+        write_panduza_file(PanduzaFileType::Key, "client_private_key.pem", &key)?;
+        write_panduza_file(PanduzaFileType::Certificate, ROOT_CA_CERTIFICATE, &ca)?;
+        println!("Installed as the default client credentials");
+    }
+    Ok(())
+}
+
+fn csr(name: &str, san: Vec<String>, days: i32) -> Result<()> {
+    check_name("name", name)?;
+    let key = generate_key().context("Failed to generate the private key")?;
+    let csr = generate_csr_client(&key, san, days);
+    let csr_pem = csr.pem().context("Failed to encode the signing request")?;
+
+    let key_path = write_panduza_file(
+        PanduzaFileType::Key,
+        &format!("{}_private_key.pem", name),
+        &key.serialize_pem(),
+    )?;
+    let csr_path = write_panduza_file(PanduzaFileType::Csr, &format!("{}.csr", name), &csr_pem)?;
+
+    println!("Private key     : {}", key_path.display());
+    println!("Signing request : {}", csr_path.display());
+    Ok(())
+}
+
+fn sign_csr(csr: &Path, role: &str, days: i32, out: Option<PathBuf>) -> Result<()> {
+    check_name("role", role)?;
+    let csr_pem = std::fs::read_to_string(csr)
+        .with_context(|| format!("Cannot read signing request {}", csr.display()))?;
+    let (ca, ca_key) = load_root_ca()?;
+    let cert = sign_csr_with_ca(&csr_pem, &ca, &ca_key, &format!("{}.local", role), days)
+        .map_err(|e| anyhow!("Failed to sign {}: {}", csr.display(), e))?;
+
+    let out = out.unwrap_or_else(|| {
+        let stem = csr.file_stem().unwrap_or_default().to_string_lossy();
+        csr.with_file_name(format!("{}_certificate.pem", stem))
+    });
+    std::fs::write(&out, cert.pem())
+        .with_context(|| format!("Cannot write certificate {}", out.display()))?;
+
+    println!("Certificate : {}", out.display());
+    Ok(())
+}
+
+fn print_summary(summary: &CertificateSummary) {
+    println!("Subject      : {}", summary.subject);
+    println!(
+        "Role         : {}",
+        summary.role().unwrap_or("(no common name)")
+    );
+    println!("Issuer       : {}", summary.issuer);
+    println!("Serial       : {}", summary.serial);
+    println!("SAN          : {}", summary.san.join(", "));
+    println!("Not before   : {}", summary.not_before);
+    println!("Not after    : {}", summary.not_after);
+    println!("CA           : {}", summary.is_ca);
+    println!("Key usages   : {}", summary.key_usages.join(", "));
+    println!("Ext. usages  : {}", summary.extended_key_usages.join(", "));
+    if summary.is_expired() {
+        println!("Status       : EXPIRED");
+    } else {
+        println!(
+            "Status       : valid, expires in {} days",
+            summary.expires_in().whole_days()
+        );
+    }
+}
+
+fn inspect(file: &Path, ca: Option<PathBuf>, crl: Option<PathBuf>) -> Result<()> {
+    let summary = inspect_file(file)?;
+    print_summary(&summary);
+
+    let ca = ca.or_else(|| {
+        default_certificate_paths()
+            .map(|(ca, _, _)| ca)
+            .filter(|ca| ca.exists())
+    });
+    if let Some(ca) = ca {
+        let cert_pem = read_pem_file(file)?;
+        let ca_pem = read_pem_file(&ca)?;
+        match verify_chain(&cert_pem, &ca_pem) {
+            Ok(_) => println!("Chain        : OK ({})", ca.display()),
+            Err(e) => {
+                println!("Chain        : FAILED ({})", ca.display());
+                return Err(e.into());
+            }
+        }
+
+        let crl = match crl {
+            Some(crl) => crl,
+            None => crl_path()?,
+        };
+        if let Some(crl_pem) = load_crl(&crl)? {
+            match verify_chain_with_crl(&cert_pem, &ca_pem, &crl_pem) {
+                Ok(_) => println!("Revocation   : not revoked ({})", crl.display()),
+                Err(e) => {
+                    println!("Revocation   : FAILED ({})", crl.display());
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn revoke(target: &str, reason: Option<Reason>) -> Result<()> {
+    // A path to a certificate, or directly a serial number
+    let serial = if Path::new(target).exists() {
+        inspect_file(target)?.serial
+    } else {
+        target.to_string()
+    };
+    let (ca, ca_key) = load_root_ca()?;
+    let path = crl_path()?;
+    let current = load_crl(&path)?;
+    let crl_pem = revoke_serial(
+        current.as_deref(),
+        &serial,
+        reason.map(Into::into),
+        &ca,
+        &ca_key,
+    )?;
+    save_crl(&path, &crl_pem)?;
+
+    println!("Revoked serial : {}", serial);
+    println!("CRL            : {}", path.display());
+    Ok(())
+}
+
+fn crl(show: bool) -> Result<()> {
+    let (ca, ca_key) = load_root_ca()?;
+    let path = crl_path()?;
+    let crl_pem = match (load_crl(&path)?, show) {
+        (Some(current), true) => current,
+        (None, true) => return Err(anyhow!("No CRL found at {}", path.display())),
+        (Some(current), false) => refresh_crl(&current, &ca, &ca_key)?,
+        (None, false) => generate_crl(&ca, &ca_key, &[], 1)?,
+    };
+    if !show {
+        save_crl(&path, &crl_pem)?;
+    }
+
+    let list = parse_crl(&crl_pem, &ca.pem())?;
+    println!("CRL          : {}", path.display());
+    println!("Number       : {}", list.crl_number);
+    println!("This update  : {}", list.this_update);
+    if let Some(next_update) = list.next_update {
+        println!("Next update  : {}", next_update);
+    }
+    for entry in &list.revoked {
+        println!("Revoked      : {} on {}", entry.serial, entry.revoked_at);
+    }
+    Ok(())
+}
+
+fn renew(file: &Path, days: i32, out: Option<PathBuf>) -> Result<()> {
+    let cert_pem = read_pem_file(file)?;
+    let (ca, ca_key) = load_root_ca()?;
+    let cert = renew_certificate(&cert_pem, &ca, &ca_key, days)
+        .map_err(|e| anyhow!("Failed to renew {}: {}", file.display(), e))?;
+
+    let out = out.unwrap_or_else(|| file.to_path_buf());
+    write_atomic(&out, &cert.pem())
+        .with_context(|| format!("Cannot write certificate {}", out.display()))?;
+
+    println!("Renewed certificate : {}", out.display());
+    Ok(())
+}
+
+fn protect_key(file: &Path, passphrase_env: &str) -> Result<()> {
+    if is_encrypted_pem(&read_pem_file(file)?) {
+        return Err(anyhow!("{} is already encrypted", file.display()));
+    }
+    let passphrase = std::env::var(passphrase_env)
+        .map_err(|_| anyhow!("Set the passphrase in the {} variable", passphrase_env))?;
+    if passphrase.is_empty() {
+        return Err(anyhow!("The passphrase in {} is empty", passphrase_env));
+    }
+
+    let key = load_key_file(file, None)?;
+    let encrypted = encrypt_key(&key, &passphrase)?;
+    write_private_atomic(file, &encrypted)
+        .with_context(|| format!("Cannot write private key {}", file.display()))?;
+
+    println!("Encrypted private key : {}", file.display());
+    Ok(())
+}
+
+/// Value of an optional secret variable, an empty value counts as unset
+fn secret_from_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn export(name: &str, format: BundleFormat, out: &Path, password_env: &str) -> Result<()> {
+    check_name("name", name)?;
+    let key_passphrase = secret_from_env(KEY_PASSPHRASE_ENV);
+    let identity = Identity::from_panduza(name, key_passphrase.as_deref())
+        .with_context(|| format!("Cannot load the {} identity", name))?;
+    let password = secret_from_env(password_env);
+
+    match format {
+        BundleFormat::P12 => {
+            let password = password.ok_or_else(|| {
+                anyhow!("Set the bundle password in the {} variable", password_env)
+            })?;
+            let p12 = identity.to_pkcs12(&password)?;
+            std::fs::write(out, p12)
+                .with_context(|| format!("Cannot write bundle {}", out.display()))?;
+        }
+        BundleFormat::Pem => {
+            if password.is_none() {
+                println!("Warning: the private key of the bundle is not encrypted");
+            }
+            let pem = identity.to_pem_bundle(password.as_deref())?;
+            write_private_atomic(out, &pem)
+                .with_context(|| format!("Cannot write bundle {}", out.display()))?;
+        }
+    }
+
+    println!("Exported identity : {}", out.display());
+    Ok(())
+}
+
+fn import(file: &Path, name: &str, password_env: &str, options: InstallOptions) -> Result<()> {
+    check_name("name", name)?;
+    let data =
+        std::fs::read(file).with_context(|| format!("Cannot read bundle {}", file.display()))?;
+    let password = secret_from_env(password_env);
+
+    let identity = match std::str::from_utf8(&data) {
+        Ok(pem) if pem.contains("-----BEGIN ") => {
+            Identity::from_pem_bundle(pem, password.as_deref())?
+        }
+        _ => {
+            let password = password.ok_or_else(|| {
+                anyhow!("Set the bundle password in the {} variable", password_env)
+            })?;
+            Identity::from_pkcs12(&data, &password)?
+        }
+    };
+
+    let installed = identity.install(
+        name,
+        secret_from_env(KEY_PASSPHRASE_ENV).as_deref(),
+        options,
+    )?;
+    match installed.ca_certificate {
+        Some(ca) => println!("Root CA certificate : {}", ca.display()),
+        None if !identity.chain().is_empty() => {
+            println!("Root CA certificate : not installed (use --root-ca)")
+        }
+        None => {}
+    }
+    println!("Client certificate  : {}", installed.certificate.display());
+    println!("Client private key  : {}", installed.private_key.display());
+    Ok(())
+}
@@ -0,0 +1,404 @@
+//! Synchronous facade over the async client
+//!
+//! Every object of this module shares a dedicated Tokio runtime created by
+//! [ReactorBuilder::build]. Methods block the calling thread until the
+//! underlying async operation completes.
+//!
+//! These objects must not be used from inside an async context (a Tokio
+//! runtime would panic when blocking on it).
+//!
+use crate::attribute::AttributeError;
+use crate::reactor::Passphrase;
+use crate::reactor::Pem;
+use crate::reactor::ReactorConfig;
+use crate::reactor::SessionMode;
+use crate::reactor::Transport;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Synchronous wrappers of the typed attributes
+///
+pub mod attribute;
+pub use attribute::BooleanAttribute;
+pub use attribute::BytesAttribute;
+pub use attribute::NotificationAttribute;
+pub use attribute::NumberAttribute;
+pub use attribute::StatusAttribute;
+pub use attribute::StringAttribute;
+
+/// Blocking version of [crate::reactor::ReactorBuilder]
+///
+#[derive(Debug, Clone, Default)]
+pub struct ReactorBuilder {
+    /// Async builder configured by this object
+    ///
+    pub inner: crate::reactor::ReactorBuilder,
+}
+
+impl ReactorBuilder {
+    /// Creates a new ReactorBuilder instance
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the address to connect to
+    ///
+    pub fn address(mut self, address: String) -> Self {
+        self.inner = self.inner.address(address);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the port number to connect to
+    ///
+    pub fn port(mut self, port: u16) -> Self {
+        self.inner = self.inner.port(port);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the CA certificate file path
+    ///
+    pub fn ca_certificate(mut self, ca_certificate: String) -> Self {
+        self.inner = self.inner.ca_certificate(ca_certificate);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the client certificate file path
+    ///
+    pub fn connect_certificate(mut self, connect_certificate: String) -> Self {
+        self.inner = self.inner.connect_certificate(connect_certificate);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the client private key file path
+    ///
+    pub fn connect_private_key(mut self, connect_private_key: String) -> Self {
+        self.inner = self.inner.connect_private_key(connect_private_key);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the CA certificate content (PEM text or `rcgen::Certificate`)
+    ///
+    pub fn ca_certificate_pem<P: Into<Pem>>(mut self, ca_certificate: P) -> Self {
+        self.inner = self.inner.ca_certificate_pem(ca_certificate);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the client certificate content (PEM text or `rcgen::Certificate`)
+    ///
+    pub fn connect_certificate_pem<P: Into<Pem>>(mut self, connect_certificate: P) -> Self {
+        self.inner = self.inner.connect_certificate_pem(connect_certificate);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the client private key content (PEM text or `rcgen::KeyPair`)
+    ///
+    pub fn connect_private_key_pem<P: Into<Pem>>(mut self, connect_private_key: P) -> Self {
+        self.inner = self.inner.connect_private_key_pem(connect_private_key);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the passphrase of an encrypted client private key
+    ///
+    pub fn connect_private_key_passphrase<P: Into<Passphrase>>(mut self, passphrase: P) -> Self {
+        self.inner = self.inner.connect_private_key_passphrase(passphrase);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the namespace for the connection
+    ///
+    pub fn namespace(mut self, namespace: String) -> Self {
+        self.inner = self.inner.namespace(namespace);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the maximum time allowed to open the session
+    ///
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.inner = self.inner.connect_timeout(connect_timeout);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Adds an endpoint tried for failover
+    ///
+    pub fn endpoint(mut self, endpoint: String) -> Self {
+        self.inner = self.inner.endpoint(endpoint);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the link protocol
+    ///
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.inner = self.inner.transport(transport);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets the Zenoh session mode
+    ///
+    pub fn mode(mut self, mode: SessionMode) -> Self {
+        self.inner = self.inner.mode(mode);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Enables or disables multicast scouting on the local network
+    ///
+    pub fn scouting(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.scouting(enabled);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Sets a raw Zenoh configuration value at the given dotted path
+    ///
+    pub fn zenoh_override<V: Into<serde_json::Value>>(mut self, path: &str, value: V) -> Self {
+        self.inner = self.inner.zenoh_override(path, value);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Creates a builder from a configuration, on top of the default values
+    ///
+    pub fn from_config(config: ReactorConfig) -> Self {
+        Self {
+            inner: crate::reactor::ReactorBuilder::from_config(config),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Creates a builder from a JSON5 configuration file and `PZA_*` variables
+    ///
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(Self {
+            inner: crate::reactor::ReactorBuilder::from_config_file(path)?,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Creates a builder from a named profile and `PZA_*` variables
+    ///
+    pub fn from_profile(name: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            inner: crate::reactor::ReactorBuilder::from_profile(name)?,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Creates the internal runtime and connects the reactor
+    ///
+    pub fn build(self) -> anyhow::Result<Reactor> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("panduza-blocking")
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create Tokio runtime: {}", e))?;
+        let inner = runtime.block_on(self.inner.build())?;
+        Ok(Reactor {
+            runtime: Arc::new(runtime),
+            inner,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+/// Blocking version of [crate::Reactor]
+///
+#[derive(Clone, Debug)]
+pub struct Reactor {
+    /// Runtime shared by the reactor and all its attributes
+    ///
+    runtime: Arc<Runtime>,
+
+    /// Async reactor
+    ///
+    inner: crate::Reactor,
+}
+
+impl Reactor {
+    /// Returns a ReactorBuilder for creating Reactor instances
+    ///
+    pub fn builder() -> ReactorBuilder {
+        ReactorBuilder::new()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async reactor wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::Reactor {
+        &self.inner
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Runtime used to drive the async operations
+    ///
+    pub fn runtime(&self) -> &Arc<Runtime> {
+        &self.runtime
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Role derived from the client certificate, see [crate::Reactor::role]
+    ///
+    pub fn role(&self) -> Option<crate::reactor::Role> {
+        self.inner.role()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Create an attribute builder finding metadata in structure attribute
+    ///
+    pub fn find_attribute<A: Into<String>>(&self, pattern: A) -> AttributeBuilder {
+        let inner = self.runtime.block_on(self.inner.find_attribute(pattern));
+        AttributeBuilder {
+            runtime: self.runtime.clone(),
+            inner,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Return the last structure received as json string
+    ///
+    pub fn structure_json(&self) -> Option<String> {
+        self.runtime
+            .block_on(self.inner.structure.get_as_json_string())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Create a new status attribute on "pza/_/status"
+    ///
+    pub fn new_status_attribute(&self) -> StatusAttribute {
+        let inner = self.runtime.block_on(self.inner.new_status_attribute());
+        StatusAttribute::new(self.runtime.clone(), inner)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Create a new notification attribute on "pza/_/notifications"
+    ///
+    pub fn new_notification_attribute(&self) -> NotificationAttribute {
+        let inner = self
+            .runtime
+            .block_on(self.inner.new_notification_attribute());
+        NotificationAttribute::new(self.runtime.clone(), inner)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Stop the attribute tasks and close the session, see [crate::Reactor::shutdown]
+    ///
+    pub fn shutdown(&self) -> anyhow::Result<()> {
+        self.runtime.block_on(self.inner.shutdown())
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+/// Blocking version of [crate::AttributeBuilder]
+///
+#[derive(Clone)]
+pub struct AttributeBuilder {
+    /// Runtime shared with the reactor
+    ///
+    runtime: Arc<Runtime>,
+
+    /// Async builder
+    ///
+    inner: crate::AttributeBuilder,
+}
+
+impl AttributeBuilder {
+    /// BOOLEAN
+    ///
+    pub fn try_into_boolean(self) -> Result<BooleanAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_boolean())?;
+        Ok(BooleanAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// NUMBER
+    ///
+    pub fn try_into_number(self) -> Result<NumberAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_number())?;
+        Ok(NumberAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// STRING
+    ///
+    pub fn try_into_string(self) -> Result<StringAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_string())?;
+        Ok(StringAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// BYTES
+    ///
+    pub fn try_into_bytes(self) -> Result<BytesAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_bytes())?;
+        Ok(BytesAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// STATUS
+    ///
+    pub fn try_into_status(self) -> Result<StatusAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_status())?;
+        Ok(StatusAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// NOTIFICATION
+    ///
+    pub fn try_into_notification(self) -> Result<NotificationAttribute, AttributeError> {
+        let inner = self.runtime.block_on(self.inner.try_into_notification())?;
+        Ok(NotificationAttribute::new(self.runtime, inner))
+    }
+
+    // ------------------------------------------------------------------------
+}
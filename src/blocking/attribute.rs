@@ -0,0 +1,672 @@
+use crate::attribute::notification::NotificationFilter;
+use crate::attribute::AttributeError;
+use crate::attribute::CallbackGuard;
+use crate::attribute::Sample;
+use crate::fbs::status_buffer::StatusBuffer;
+use crate::fbs::BooleanBuffer;
+use crate::fbs::BytesBuffer;
+use crate::fbs::NotificationBuffer;
+use crate::fbs::NumberBuffer;
+use crate::fbs::StringBuffer;
+use crate::AttributeMetadata;
+use crate::InstanceState;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Run a user callback on the blocking pool of the runtime
+///
+/// The attribute dispatcher only awaits the returned future: the callback
+/// never runs under its locks and may call the blocking API or drop a guard.
+/// A panic is resumed in the future so the dispatcher counts it.
+fn run_blocking<T, F>(
+    callback: &Arc<F>,
+    value: Option<T>,
+) -> Pin<Box<dyn Future<Output = ()> + Send>>
+where
+    T: Send + 'static,
+    F: Fn(T) + Send + Sync + 'static,
+{
+    let callback = callback.clone();
+    Box::pin(async move {
+        let Some(value) = value else {
+            return;
+        };
+        if let Err(error) = tokio::task::spawn_blocking(move || callback(value)).await {
+            if error.is_panic() {
+                std::panic::resume_unwind(error.into_panic());
+            }
+        }
+    })
+}
+
+/// Blocking version of [crate::BooleanAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct BooleanAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::BooleanAttribute,
+}
+
+impl BooleanAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::BooleanAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value
+    ///
+    pub fn get(&self) -> Option<bool> {
+        self.runtime
+            .block_on(self.inner.get())
+            .and_then(|buffer| buffer.value())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub fn sample(&self) -> Option<Sample<bool>> {
+        self.runtime.block_on(self.inner.sample())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the value and wait for validation
+    ///
+    pub fn set(&mut self, value: bool) -> Result<(), AttributeError> {
+        self.runtime.block_on(self.inner.set(value))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait until the attribute reports the given value
+    ///
+    pub fn wait_for_value(&self, value: bool, timeout: Option<Duration>) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.inner.wait_for_value(
+                move |buffer: &BooleanBuffer| buffer.value() == Some(value),
+                timeout,
+            ))
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each received value
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, callback: F) -> CallbackGuard
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(self.inner.add_callback(
+            move |buffer: BooleanBuffer| run_blocking(&callback, buffer.value()),
+            None::<fn(&BooleanBuffer) -> bool>,
+        ))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::BooleanAttribute {
+        &self.inner
+    }
+}
+
+/// Blocking version of [crate::NumberAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct NumberAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::NumberAttribute,
+}
+
+impl NumberAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::NumberAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value
+    ///
+    pub fn get(&self) -> Option<f64> {
+        self.runtime
+            .block_on(self.inner.get())
+            .and_then(|buffer| buffer.value())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub fn sample(&self) -> Option<Sample<f64>> {
+        self.runtime.block_on(self.inner.sample())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the value and wait for validation
+    ///
+    pub fn set(&mut self, value: f64) -> Result<(), AttributeError> {
+        self.runtime.block_on(self.inner.set(value))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait until the attribute reports the given value
+    ///
+    pub fn wait_for_value(&self, value: f64, timeout: Option<Duration>) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.inner.wait_for_value(
+                move |buffer: &NumberBuffer| buffer.value() == Some(value),
+                timeout,
+            ))
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each received value
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, callback: F) -> CallbackGuard
+    where
+        F: Fn(f64) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(self.inner.add_callback(
+            move |buffer: NumberBuffer| run_blocking(&callback, buffer.value()),
+            None::<fn(&NumberBuffer) -> bool>,
+        ))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::NumberAttribute {
+        &self.inner
+    }
+}
+
+/// Blocking version of [crate::StringAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct StringAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::StringAttribute,
+}
+
+impl StringAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::StringAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value
+    ///
+    pub fn get(&self) -> Option<String> {
+        self.runtime
+            .block_on(self.inner.get())
+            .and_then(|buffer| buffer.value().map(|value| value.to_string()))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub fn sample(&self) -> Option<Sample<String>> {
+        self.runtime.block_on(self.inner.sample())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the value and wait for validation
+    ///
+    pub fn set(&mut self, value: String) -> Result<(), AttributeError> {
+        self.runtime.block_on(self.inner.set(value))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait until the attribute reports the given value
+    ///
+    pub fn wait_for_value(&self, value: String, timeout: Option<Duration>) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.inner.wait_for_value(
+                move |buffer: &StringBuffer| buffer.value() == Some(value.as_str()),
+                timeout,
+            ))
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each received value
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, callback: F) -> CallbackGuard
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(self.inner.add_callback(
+            move |buffer: StringBuffer| run_blocking(&callback, buffer.value().map(str::to_string)),
+            None::<fn(&StringBuffer) -> bool>,
+        ))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::StringAttribute {
+        &self.inner
+    }
+}
+
+/// Blocking version of [crate::BytesAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct BytesAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::BytesAttribute,
+}
+
+impl BytesAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::BytesAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value
+    ///
+    pub fn get(&self) -> Option<Bytes> {
+        self.runtime
+            .block_on(self.inner.get())
+            .and_then(|buffer| buffer.value())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received value with its timestamp, source and sequence
+    ///
+    pub fn sample(&self) -> Option<Sample<Bytes>> {
+        self.runtime.block_on(self.inner.sample())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the value and wait for validation
+    ///
+    pub fn set(&mut self, value: Bytes) -> Result<(), AttributeError> {
+        self.runtime.block_on(self.inner.set(value))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait until the attribute reports the given value
+    ///
+    pub fn wait_for_value(&self, value: Bytes, timeout: Option<Duration>) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.inner.wait_for_value(
+                move |buffer: &BytesBuffer| buffer.value() == Some(value.clone()),
+                timeout,
+            ))
+            .map(|_| ())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each received value
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, callback: F) -> CallbackGuard
+    where
+        F: Fn(Bytes) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(self.inner.add_callback(
+            move |buffer: BytesBuffer| run_blocking(&callback, buffer.value()),
+            None::<fn(&BytesBuffer) -> bool>,
+        ))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::BytesAttribute {
+        &self.inner
+    }
+}
+
+/// Blocking version of [crate::StatusAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct StatusAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::StatusAttribute,
+}
+
+impl StatusAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::StatusAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get the last received status
+    ///
+    pub fn get(&self) -> Option<StatusBuffer> {
+        self.runtime.block_on(self.inner.inner.get())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the last known state of the given instance
+    ///
+    pub fn instance_state(&self, name: &str) -> Option<InstanceState> {
+        self.runtime.block_on(self.inner.instance_state(name))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the names of the instances currently in the given state
+    ///
+    pub fn instances_in(&self, state: InstanceState) -> Vec<String> {
+        self.runtime.block_on(self.inner.instances_in(state))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the error strings of all the instances that report one
+    ///
+    pub fn instance_errors(&self) -> HashMap<String, String> {
+        self.runtime.block_on(self.inner.instance_errors())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Waits until all instances are in the "running" state
+    ///
+    pub fn wait_for_all_instances_to_be_running(&self, timeout: Duration) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.wait_for_all_instances_to_be_running(timeout))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Waits until the given instance reaches the given state
+    ///
+    pub fn wait_for_instance_state(
+        &self,
+        name: &str,
+        state: InstanceState,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        self.runtime
+            .block_on(self.inner.wait_for_instance_state(name, state, timeout))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered with each received status
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, callback: F) -> CallbackGuard
+    where
+        F: Fn(StatusBuffer) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(self.inner.add_callback(
+            move |buffer: StatusBuffer| run_blocking(&callback, Some(buffer)),
+            None::<fn(&StatusBuffer) -> bool>,
+        ))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::StatusAttribute {
+        &self.inner
+    }
+}
+
+/// Blocking version of [crate::NotificationAttribute]
+///
+#[derive(Clone, Debug)]
+pub struct NotificationAttribute {
+    runtime: Arc<Runtime>,
+    inner: crate::NotificationAttribute,
+}
+
+impl NotificationAttribute {
+    /// Wrap an async attribute
+    ///
+    pub fn new(runtime: Arc<Runtime>, inner: crate::NotificationAttribute) -> Self {
+        Self { runtime, inner }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Wait for a notification that passes the filter
+    ///
+    pub fn wait_for_notification(
+        &self,
+        filter: NotificationFilter,
+        timeout: Option<Duration>,
+    ) -> Result<NotificationBuffer, String> {
+        self.runtime
+            .block_on(self.inner.wait_for_notification(filter, timeout))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Add a callback triggered for notifications that pass the filter
+    ///
+    /// The callback runs on the blocking pool of the internal runtime, so it
+    /// can use the blocking API, and stays registered as long as the returned
+    /// guard lives.
+    ///
+    pub fn add_callback<F>(&self, filter: NotificationFilter, callback: F) -> CallbackGuard
+    where
+        F: Fn(NotificationBuffer) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.runtime.block_on(
+            self.inner
+                .add_filtered_callback(filter, move |notification: NotificationBuffer| {
+                    run_blocking(&callback, Some(notification))
+                }),
+        )
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Forward the notifications that pass the filter to the `tracing` ecosystem
+    ///
+    pub fn forward_to_tracing(&self, filter: NotificationFilter) -> CallbackGuard {
+        self.runtime.block_on(self.inner.forward_to_tracing(filter))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Get attribute metadata
+    ///
+    pub fn metadata(&self) -> &AttributeMetadata {
+        self.inner.metadata()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Async attribute wrapped by this object
+    ///
+    pub fn inner(&self) -> &crate::NotificationAttribute {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbs::PzaBuffer;
+    use crate::test_support::platform_and_client;
+    use crate::test_support::wait_for_subscriber;
+    use crate::AttributeMode;
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+
+    fn number(value: f64) -> zenoh::bytes::ZBytes {
+        NumberBuffer::builder()
+            .with_value(value)
+            .with_source(0)
+            .with_random_sequence()
+            .build()
+            .unwrap()
+            .to_zbytes()
+    }
+
+    #[test]
+    fn callbacks_can_use_the_blocking_api() {
+        let runtime = Arc::new(
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+        );
+        let (platform, client, _) = runtime.block_on(platform_and_client());
+        let metadata = AttributeMetadata::from_topic(
+            "pza/test/blocking/voltage".to_string(),
+            Some("number".to_string()),
+            AttributeMode::WriteOnly,
+        );
+        let inner = runtime
+            .block_on(crate::NumberAttribute::new(client.clone(), metadata))
+            .unwrap();
+        let attribute = NumberAttribute::new(runtime.clone(), inner);
+        runtime.block_on(wait_for_subscriber(
+            &platform,
+            "pza/test/blocking/voltage/att",
+        ));
+
+        // Read the attribute from the callback
+        let (sender, receiver) = mpsc::channel();
+        let reader = attribute.clone();
+        let guard = attribute.add_callback(move |value| {
+            let _ = sender.send((value, reader.get()));
+        });
+
+        // Drop its own guard from the callback
+        let slot = Arc::new(Mutex::new(None::<CallbackGuard>));
+        let own = slot.clone();
+        *slot.lock().unwrap() = Some(attribute.add_callback(move |_| {
+            own.lock().unwrap().take();
+        }));
+
+        runtime
+            .block_on(async {
+                platform
+                    .put("pza/test/blocking/voltage/att", number(2.5))
+                    .await
+            })
+            .unwrap();
+        let (value, read) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(value, 2.5);
+        assert_eq!(read, Some(2.5));
+
+        let removed = (0..500).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            runtime.block_on(attribute.inner.inner.callback_count()) == 1
+        });
+        assert!(removed);
+        drop(guard);
+
+        // The runtime must not be dropped from one of its own threads
+        drop(attribute);
+        while Arc::strong_count(&runtime) > 1 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        runtime.block_on(async {
+            client.close().await.unwrap();
+            platform.close().await.unwrap();
+        });
+    }
+}
@@ -33,11 +33,12 @@ pub use notification_buffer::NotificationBufferBuilder;
 pub use notification_buffer::NotificationType;
 
 pub mod status_buffer;
+pub use status_buffer::InstanceStateTransition;
 pub use status_buffer::InstanceStatusBuffer;
 pub use status_buffer::StatusBuffer;
 pub use status_buffer::StatusBufferBuilder;
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 use zenoh::bytes::ZBytes;
@@ -111,6 +112,18 @@ pub trait PzaBuffer: Clone + Default + Send + Sync + 'static {
     ///
     fn sequence(&self) -> Option<u16>;
 
+    /// Returns the timestamp of the header, set by the sender
+    ///
+    /// # Returns
+    /// None if there is no timestamp or if it is out of the `SystemTime` range
+    fn timestamp(&self) -> Option<SystemTime> {
+        let msg = self.as_message();
+        let timestamp = msg.header()?.timestamp()?;
+        let since_the_epoch = Duration::from_secs(timestamp.secs())
+            .checked_add(Duration::from_nanos(timestamp.nanos().into()))?;
+        UNIX_EPOCH.checked_add(since_the_epoch)
+    }
+
     ///
     ///
     fn as_message(&self) -> Message;
@@ -119,3 +132,44 @@ pub trait PzaBuffer: Clone + Default + Send + Sync + 'static {
     ///
     fn has_same_message_value<B: PzaBuffer>(&self, other_buffer: &B) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use panduza_generated::panduza::Header;
+    use panduza_generated::panduza::HeaderArgs;
+    use panduza_generated::panduza::MessageArgs;
+
+    fn buffer_with_timestamp(timestamp: Timestamp) -> NumberBuffer {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let header = Header::create(
+            &mut builder,
+            &HeaderArgs {
+                timestamp: Some(&timestamp),
+                source: 0,
+                sequence: 0,
+            },
+        );
+        let message = Message::create(
+            &mut builder,
+            &MessageArgs {
+                header: Some(header),
+                ..Default::default()
+            },
+        );
+        builder.finish(message, None);
+        NumberBuffer::from_zbytes(ZBytes::from(builder.finished_data().to_vec()))
+    }
+
+    #[test]
+    fn timestamp_out_of_range_is_none() {
+        let buffer = buffer_with_timestamp(Timestamp::new(u64::MAX, 999_999_999));
+        assert_eq!(buffer.timestamp(), None);
+
+        let buffer = buffer_with_timestamp(Timestamp::new(12, 1_500_000_000));
+        assert_eq!(
+            buffer.timestamp(),
+            Some(UNIX_EPOCH + Duration::from_millis(13_500))
+        );
+    }
+}
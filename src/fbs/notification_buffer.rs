@@ -156,6 +156,11 @@ impl PzaBuffer for NotificationBuffer {
 }
 
 impl NotificationBuffer {
+    /// Returns a builder for NotificationBuffer
+    pub fn builder() -> NotificationBufferBuilder {
+        NotificationBufferBuilder::default()
+    }
+
     /// Returns the Notification object from the buffer, if present.
     pub fn notification(&self) -> Option<Notification> {
         self.as_message().payload_as_notification()
@@ -177,3 +182,32 @@ impl NotificationBuffer {
         self.notification().and_then(|n| n.message())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(notification_type: NotificationType) -> NotificationBuffer {
+        NotificationBuffer::builder()
+            .with_notification_type(notification_type)
+            .with_source(0)
+            .with_sequence(0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn wire_type_values() {
+        for (notification_type, wire) in [
+            (NotificationType::Alert, 0),
+            (NotificationType::Error, 1),
+            (NotificationType::Info, 2),
+            (NotificationType::Warning, 3),
+        ] {
+            let buffer = notification(notification_type);
+            assert_eq!(buffer.notification().unwrap().type_(), wire);
+            assert_eq!(buffer.notification_type(), Some(notification_type));
+        }
+        assert!(NotificationType::try_from(4).is_err());
+    }
+}
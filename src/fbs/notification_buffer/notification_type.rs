@@ -1,4 +1,10 @@
-#[derive(Clone, Debug, PartialEq)]
+/// Type of a notification
+///
+/// The discriminants are the values of the `type` field on the wire: Alert and
+/// Error keep the values they have always been sent with.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
 pub enum NotificationType {
     ///
     /// An action triggered an alert.
@@ -6,7 +12,7 @@ pub enum NotificationType {
     /// An alert is a internal warning that has been managed by the system but
     /// require user attention because it may be the result of a misusage.
     ///
-    Alert,
+    Alert = 0,
 
     ///
     /// An action triggered an error.
@@ -14,15 +20,52 @@ pub enum NotificationType {
     /// An error is a critical failure of an instance driver.
     /// The given driver is not able to process any more data and will try to reboot.
     ///
-    Error,
+    Error = 1,
+
+    ///
+    /// Information about the life of an instance.
+    ///
+    /// Nothing went wrong, the notification is only here to trace events.
+    ///
+    Info = 2,
+
+    ///
+    /// A situation that is not an error yet but may degrade the instance.
+    ///
+    /// The driver keeps working normally.
+    ///
+    Warning = 3,
+}
+
+impl NotificationType {
+    /// Severity of the notification type, higher is more critical
+    ///
+    /// Info < Warning < Alert < Error
+    ///
+    pub fn severity(&self) -> u8 {
+        match self {
+            NotificationType::Info => 0,
+            NotificationType::Warning => 1,
+            NotificationType::Alert => 2,
+            NotificationType::Error => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NotificationType::Alert => write!(f, "Alert"),
+            NotificationType::Error => write!(f, "Error"),
+            NotificationType::Info => write!(f, "Info"),
+            NotificationType::Warning => write!(f, "Warning"),
+        }
+    }
 }
 
 impl From<NotificationType> for u16 {
     fn from(notification_type: NotificationType) -> Self {
-        match notification_type {
-            NotificationType::Alert => 1,
-            NotificationType::Error => 2,
-        }
+        notification_type as u16
     }
 }
 
@@ -31,8 +74,10 @@ impl TryFrom<u16> for NotificationType {
 
     fn try_from(value: u16) -> Result<Self, <Self as TryFrom<u16>>::Error> {
         match value {
-            1 => Ok(NotificationType::Alert),
-            2 => Ok(NotificationType::Error),
+            0 => Ok(NotificationType::Alert),
+            1 => Ok(NotificationType::Error),
+            2 => Ok(NotificationType::Info),
+            3 => Ok(NotificationType::Warning),
             _ => Err(format!("Invalid NotificationType value: {}", value)),
         }
     }
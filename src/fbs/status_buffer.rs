@@ -223,9 +223,166 @@ impl PzaBuffer for StatusBuffer {
     // ------------------------------------------------------------------------
 }
 
+/// State change of one instance between two consecutive status buffers
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstanceStateTransition {
+    /// Name of the instance
+    ///
+    pub instance: String,
+
+    /// Previous state, None if the instance was not reported before
+    ///
+    pub previous: Option<InstanceState>,
+
+    /// Current state, None if the instance is no longer reported
+    ///
+    pub current: Option<InstanceState>,
+
+    /// Error string reported with the current state
+    ///
+    pub error_string: Option<String>,
+}
+
+impl InstanceStateTransition {
+    /// True if the transition is allowed by the instance FSM
+    ///
+    /// Appearance and disappearance of an instance are always legal.
+    ///
+    pub fn is_legal(&self) -> bool {
+        match (&self.previous, &self.current) {
+            (Some(previous), Some(current)) => previous.can_transition_to(current),
+            _ => true,
+        }
+    }
+}
+
 impl StatusBuffer {
     // ------------------------------------------------------------------------
 
+    /// Decode the status of all the named instances of the buffer
+    ///
+    pub fn instance_statuses(&self) -> Vec<InstanceStatusBuffer> {
+        let msg = self.as_message();
+        msg.payload_as_status()
+            .and_then(|status| status.instances())
+            .map(|instances| {
+                instances
+                    .iter()
+                    .filter_map(|inst| {
+                        inst.instance().map(|name| {
+                            InstanceStatusBuffer::from_args(
+                                name.to_string(),
+                                inst.state(),
+                                inst.error_string().map(|e| e.to_string()),
+                            )
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the status of the given instance, if reported
+    ///
+    pub fn find_instance(&self, name: &str) -> Option<InstanceStatusBuffer> {
+        self.instance_statuses()
+            .into_iter()
+            .find(|inst| inst.instance().map(|n| n.as_str()) == Some(name))
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the state of the given instance, if reported and known
+    ///
+    pub fn instance_state(&self, name: &str) -> Option<InstanceState> {
+        self.find_instance(name).and_then(|inst| inst.state())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the error string of the given instance, if any
+    ///
+    pub fn instance_error_string(&self, name: &str) -> Option<String> {
+        self.find_instance(name)
+            .and_then(|inst| inst.error_string().cloned())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns the names of the instances in the given state
+    ///
+    pub fn instances_in(&self, state: &InstanceState) -> Vec<String> {
+        self.instance_statuses()
+            .into_iter()
+            .filter(|inst| inst.state().as_ref() == Some(state))
+            .filter_map(|inst| inst.instance().cloned())
+            .collect()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Computes the state transitions between a previous buffer and this one
+    ///
+    /// Instances whose state is missing or unknown are skipped: an unknown
+    /// previous state is reported as `None` and an instance with an unknown
+    /// current state is neither changed nor removed.
+    ///
+    pub fn transitions_from(
+        &self,
+        previous: Option<&StatusBuffer>,
+    ) -> Vec<InstanceStateTransition> {
+        let previous_statuses = previous.map(|p| p.instance_statuses()).unwrap_or_default();
+        let current_statuses = self.instance_statuses();
+
+        let find = |list: &[InstanceStatusBuffer], name: &String| {
+            list.iter()
+                .find(|inst| inst.instance() == Some(name))
+                .cloned()
+        };
+
+        let mut transitions = Vec::new();
+
+        // New instances and state changes
+        for current in current_statuses.iter() {
+            let (name, current_state) = match (current.instance(), current.state()) {
+                (Some(name), Some(state)) => (name, state),
+                _ => continue,
+            };
+            let previous_state = find(&previous_statuses, name).and_then(|p| p.state());
+            if previous_state.as_ref() != Some(&current_state) {
+                transitions.push(InstanceStateTransition {
+                    instance: name.clone(),
+                    previous: previous_state,
+                    current: Some(current_state),
+                    error_string: current.error_string().cloned(),
+                });
+            }
+        }
+
+        // Instances no longer reported
+        for previous in previous_statuses.iter() {
+            let (name, previous_state) = match (previous.instance(), previous.state()) {
+                (Some(name), Some(state)) => (name, state),
+                _ => continue,
+            };
+            if find(&current_statuses, name).is_none() {
+                transitions.push(InstanceStateTransition {
+                    instance: name.clone(),
+                    previous: Some(previous_state),
+                    current: None,
+                    error_string: None,
+                });
+            }
+        }
+
+        transitions
+    }
+
+    // ------------------------------------------------------------------------
+
     /// Returns true if all instances in the status buffer are in the "running" state.
     ///
     pub fn all_instances_are_running(&self) -> bool {
@@ -233,7 +390,7 @@ impl StatusBuffer {
         if let Some(status) = msg.payload_as_status() {
             if let Some(instances) = status.instances() {
                 for inst in instances {
-                    if inst.state() != InstanceState::Running as u16 {
+                    if inst.state() != u16::from(InstanceState::Running) {
                         return false;
                     }
                 }
@@ -245,3 +402,132 @@ impl StatusBuffer {
 
     // ------------------------------------------------------------------------
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(instances: Vec<(&str, InstanceState, Option<&str>)>) -> StatusBuffer {
+        StatusBufferBuilder::default()
+            .with_instance_status_list(
+                instances
+                    .into_iter()
+                    .map(|(name, state, error)| {
+                        InstanceStatusBuffer::from_args(
+                            name.to_string(),
+                            state.into(),
+                            error.map(|e| e.to_string()),
+                        )
+                    })
+                    .collect(),
+            )
+            .with_source(0)
+            .with_sequence(0)
+            .build()
+            .expect("Failed to build status buffer")
+    }
+
+    #[test]
+    fn test_instance_queries() {
+        let buffer = status(vec![
+            ("a", InstanceState::Running, None),
+            ("b", InstanceState::Error, Some("connection lost")),
+            ("c", InstanceState::Running, None),
+        ]);
+
+        assert_eq!(buffer.instance_state("b"), Some(InstanceState::Error));
+        assert_eq!(buffer.instance_state("z"), None);
+        assert_eq!(
+            buffer.instance_error_string("b"),
+            Some("connection lost".to_string())
+        );
+        assert_eq!(
+            buffer.instances_in(&InstanceState::Running),
+            vec!["a".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_transitions_from() {
+        let previous = status(vec![
+            ("a", InstanceState::Booting, None),
+            ("b", InstanceState::Running, None),
+            ("c", InstanceState::Running, None),
+        ]);
+        let current = status(vec![
+            ("a", InstanceState::Connecting, None),
+            ("b", InstanceState::Running, None),
+            ("d", InstanceState::Error, Some("boom")),
+        ]);
+
+        let transitions = current.transitions_from(Some(&previous));
+
+        assert_eq!(
+            transitions,
+            vec![
+                InstanceStateTransition {
+                    instance: "a".to_string(),
+                    previous: Some(InstanceState::Booting),
+                    current: Some(InstanceState::Connecting),
+                    error_string: None,
+                },
+                InstanceStateTransition {
+                    instance: "d".to_string(),
+                    previous: None,
+                    current: Some(InstanceState::Error),
+                    error_string: Some("boom".to_string()),
+                },
+                InstanceStateTransition {
+                    instance: "c".to_string(),
+                    previous: Some(InstanceState::Running),
+                    current: None,
+                    error_string: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transitions_skip_unknown_states() {
+        let raw = |instances: Vec<(&str, u16)>| {
+            StatusBufferBuilder::default()
+                .with_instance_status_list(
+                    instances
+                        .into_iter()
+                        .map(|(name, state)| {
+                            InstanceStatusBuffer::from_args(name.to_string(), state, None)
+                        })
+                        .collect(),
+                )
+                .with_source(0)
+                .with_sequence(0)
+                .build()
+                .expect("Failed to build status buffer")
+        };
+        let running = u16::from(InstanceState::Running);
+        let previous = raw(vec![("a", running), ("b", 42), ("c", 42)]);
+        let current = raw(vec![("a", 42), ("b", running)]);
+
+        // "a" is not removed nor changed, "c" is not reported as removed
+        assert_eq!(
+            current.transitions_from(Some(&previous)),
+            vec![InstanceStateTransition {
+                instance: "b".to_string(),
+                previous: None,
+                current: Some(InstanceState::Running),
+                error_string: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_illegal_transition() {
+        let previous = status(vec![("a", InstanceState::Booting, None)]);
+        let current = status(vec![("a", InstanceState::Running, None)]);
+
+        let transitions = current.transitions_from(Some(&previous));
+
+        assert_eq!(transitions.len(), 1);
+        assert!(!transitions[0].is_legal());
+    }
+}
@@ -53,7 +53,42 @@ impl InstanceStatusBuffer {
     /// * `state` - A InstanceState representing the new state of the instance.
     ///
     pub fn set_state(&mut self, state: InstanceState) {
-        self.state = Some(state as u16);
+        self.state = Some(state.into());
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Method to get the raw state as encoded on the wire
+    ///
+    pub fn raw_state(&self) -> Option<u16> {
+        self.state
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Method to get the instance state
+    ///
+    /// Returns None if the state is missing or unknown.
+    ///
+    pub fn state(&self) -> Option<InstanceState> {
+        self.state
+            .and_then(|state| InstanceState::try_from(state).ok())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Method to set the error string
+    ///
+    pub fn set_error_string(&mut self, error_string: String) {
+        self.error_string = Some(error_string);
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Method to get the error string
+    ///
+    pub fn error_string(&self) -> Option<&String> {
+        self.error_string.as_ref()
     }
 
     // ------------------------------------------------------------------------
@@ -66,6 +101,8 @@ impl InstanceStatusBuffer {
         }
     }
 
+    // ------------------------------------------------------------------------
+
     pub fn to_fbs_args<'a>(&self, builder: &mut FlatBufferBuilder<'a>) -> InstanceStatusArgs<'a> {
         InstanceStatusArgs {
             instance: self.instance.as_ref().map(|s| builder.create_string(s)),
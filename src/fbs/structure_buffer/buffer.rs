@@ -1,5 +1,6 @@
 use super::StructureBufferBuilder;
 use crate::fbs::panduza_generated::panduza::Message;
+use crate::fbs::PzaBufferBuilder;
 use crate::PzaBuffer;
 use bytes::Bytes;
 use serde_json;
@@ -83,6 +84,20 @@ impl StructureBuffer {
 
     //------------------------------------------------------------------------------
 
+    /// Create a structure buffer from its json representation (see `as_json`)
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        StructureBufferBuilder::from_json(value)?.build()
+    }
+
+    //------------------------------------------------------------------------------
+
+    /// Create a structure buffer from a json5 string
+    pub fn from_json5(json5_str: &str) -> Result<Self, String> {
+        StructureBufferBuilder::from_json5(json5_str)?.build()
+    }
+
+    //------------------------------------------------------------------------------
+
     pub fn as_json(&self) -> serde_json::Value {
         // Get the message from the buffer
         let msg = self.as_message();
@@ -402,4 +417,93 @@ mod tests {
         assert!(!attribute.contains_key("mode"));
         assert!(!attribute.contains_key("tags"));
     }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let json = serde_json::json!({
+            "instance_1": {
+                "_node": "instance",
+                "class_foo": {
+                    "_node": "class",
+                    "tags": ["tag1", "tag2"],
+                    "attribute_1": {
+                        "_node": "attribute",
+                        "type": "string",
+                        "mode": "RW"
+                    }
+                },
+                "attribute_2": {
+                    "_node": "attribute",
+                    "type": "number",
+                    "mode": "RO"
+                }
+            },
+            "instance_2": {
+                "_node": "instance"
+            }
+        });
+
+        let buffer = StructureBuffer::from_json(&json).expect("Failed to convert json");
+
+        assert_eq!(buffer.as_json(), json);
+    }
+
+    #[test]
+    fn test_from_json5() {
+        let buffer = StructureBuffer::from_json5(
+            r#"{
+                // Comments and trailing commas are allowed
+                device: {
+                    _node: "instance",
+                    enable: { _node: "attribute", type: "boolean", mode: "WO", },
+                },
+            }"#,
+        )
+        .expect("Failed to convert json5");
+
+        let json = buffer.as_json();
+        assert_eq!(json["device"]["enable"]["type"], "boolean");
+        assert_eq!(json["device"]["enable"]["mode"], "WO");
+    }
+
+    #[test]
+    fn test_from_json_validation() {
+        // Attribute without mode
+        let missing_mode = serde_json::json!({
+            "device": {
+                "_node": "instance",
+                "value": { "_node": "attribute", "type": "number" }
+            }
+        });
+        assert!(StructureBuffer::from_json(&missing_mode).is_err());
+
+        // Class at the root
+        let class_at_root = serde_json::json!({
+            "class_foo": { "_node": "class" }
+        });
+        assert!(StructureBuffer::from_json(&class_at_root).is_err());
+
+        // Child under an attribute
+        let child_of_attribute = serde_json::json!({
+            "device": {
+                "_node": "instance",
+                "value": {
+                    "_node": "attribute",
+                    "type": "number",
+                    "mode": "RO",
+                    "sub": { "_node": "class" }
+                }
+            }
+        });
+        assert!(StructureBuffer::from_json(&child_of_attribute).is_err());
+
+        // Nested instance
+        let nested_instance = serde_json::json!({
+            "device": {
+                "_node": "instance",
+                "other": { "_node": "instance" }
+            }
+        });
+        assert!(StructureBuffer::from_json(&nested_instance).is_err());
+    }
 }
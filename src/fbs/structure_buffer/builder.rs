@@ -13,6 +13,7 @@ use bytes::Bytes;
 use flatbuffers::FlatBufferBuilder;
 use flatbuffers::WIPOffset;
 use rand::random;
+use serde_json::Value as JsonValue;
 use std::fmt::Debug;
 
 /// Builder for StructureBuffer
@@ -291,4 +292,134 @@ impl StructureBufferBuilder {
         new_child.insert_node(path, node);
         self.insert_child(new_child);
     }
+
+    // -------------------------------------------------------------------------------
+
+    /// Create a builder from the json format produced by `StructureBuffer::as_json`
+    ///
+    /// Each root key must be an instance node. Nodes are inserted one by one
+    /// with `insert_node`, so the resulting tree follows the json hierarchy.
+    pub fn from_json(value: &JsonValue) -> Result<Self, String> {
+        let instances = value
+            .as_object()
+            .ok_or("structure json must be an object".to_string())?;
+
+        let mut root = StructureBufferBuilder::default();
+        for (name, node_value) in instances {
+            root.insert_json_node(Vec::new(), None, name, node_value)?;
+        }
+        Ok(root)
+    }
+
+    // -------------------------------------------------------------------------------
+
+    /// Create a builder from a json5 string (comments and trailing commas allowed)
+    pub fn from_json5(json5_str: &str) -> Result<Self, String> {
+        let value: JsonValue = json5::from_str(json5_str)
+            .map_err(|e| format!("Failed to parse json5 structure: {}", e))?;
+        Self::from_json(&value)
+    }
+
+    // -------------------------------------------------------------------------------
+
+    /// Validate a json node and insert it (and its children) under `path`
+    fn insert_json_node(
+        &mut self,
+        path: Vec<String>,
+        parent_node: Option<&str>,
+        name: &str,
+        value: &JsonValue,
+    ) -> Result<(), String> {
+        let mut node_path = path.clone();
+        node_path.push(name.to_string());
+        let location = node_path.join("/");
+
+        let obj = value
+            .as_object()
+            .ok_or(format!("node '{}' must be an object", location))?;
+
+        // Node kind, must be consistent with the parent kind
+        let node = match obj.get("_node").and_then(|v| v.as_str()) {
+            Some(n) if n.eq_ignore_ascii_case("instance") => "Instance",
+            Some(n) if n.eq_ignore_ascii_case("class") => "Class",
+            Some(n) if n.eq_ignore_ascii_case("attribute") => "Attribute",
+            Some(n) => return Err(format!("node '{}' has an unknown kind '{}'", location, n)),
+            None => return Err(format!("node '{}' has no '_node' field", location)),
+        };
+        match (parent_node, node) {
+            (None, "Instance") => {}
+            (None, _) => {
+                return Err(format!(
+                    "node '{}' must be an instance, found {}",
+                    location, node
+                ))
+            }
+            (Some("Attribute"), _) => {
+                return Err(format!(
+                    "node '{}' cannot be a child of an attribute",
+                    location
+                ))
+            }
+            (Some(_), "Instance") => {
+                return Err(format!(
+                    "node '{}' is an instance but is not at the root",
+                    location
+                ))
+            }
+            _ => {}
+        }
+
+        let mut builder = StructureBufferBuilder::default()
+            .with_name(name.to_string())
+            .with_node(node.to_string());
+
+        // Tags
+        if let Some(tags) = obj.get("tags") {
+            let tags = tags
+                .as_array()
+                .ok_or(format!("tags of node '{}' must be an array", location))?
+                .iter()
+                .map(|t| {
+                    t.as_str()
+                        .map(|t| t.to_string())
+                        .ok_or(format!("tags of node '{}' must be strings", location))
+                })
+                .collect::<Result<Vec<String>, String>>()?;
+            builder = builder.with_tags(tags);
+        }
+
+        // Type and mode, mandatory on attributes and forbidden elsewhere
+        let r#type = obj.get("type").and_then(|v| v.as_str());
+        let mode = obj.get("mode").and_then(|v| v.as_str());
+        if node == "Attribute" {
+            let r#type = r#type.ok_or(format!("attribute '{}' has no type", location))?;
+            let mode = mode.ok_or(format!("attribute '{}' has no mode", location))?;
+            if !matches!(mode, "RO" | "WO" | "RW") {
+                return Err(format!(
+                    "attribute '{}' has an invalid mode '{}'",
+                    location, mode
+                ));
+            }
+            builder = builder
+                .with_type(r#type.to_string())
+                .with_mode(mode.to_string());
+        } else if r#type.is_some() || mode.is_some() {
+            return Err(format!(
+                "node '{}' is not an attribute but has a type or a mode",
+                location
+            ));
+        }
+
+        self.insert_node(path, builder);
+
+        // Every other field is a child node
+        for (child_name, child_value) in obj {
+            if matches!(child_name.as_str(), "_node" | "tags" | "type" | "mode") {
+                continue;
+            }
+            self.insert_json_node(node_path.clone(), Some(node), child_name, child_value)?;
+        }
+
+        Ok(())
+    }
 }
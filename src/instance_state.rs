@@ -3,7 +3,7 @@ use std::fmt::Display;
 
 /// States of the main Interface FSM
 ///
-#[derive(Default, Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum InstanceState {
     #[default]
     Undefined,
@@ -33,18 +33,116 @@ impl Display for InstanceState {
     }
 }
 
-// impl From<InstanceState> for u16 {
-//     fn from(state: InstanceState) -> Self {
-//         match state {
-//             InstanceState::Undefined => 0,
-//             InstanceState::Booting => 1,
-//             InstanceState::Connecting => 2,
-//             InstanceState::Initializating => 3,
-//             InstanceState::Running => 4,
-//             InstanceState::Warning => 5,
-//             InstanceState::Error => 6,
-//             InstanceState::Cleaning => 7,
-//             InstanceState::Stopping => 8,
-//         }
-//     }
-// }
+/// Wire encoding of the states (InstanceStatus::state)
+///
+/// This is the single authoritative mapping between states and their u16 values.
+///
+impl From<InstanceState> for u16 {
+    fn from(state: InstanceState) -> Self {
+        match state {
+            InstanceState::Undefined => 0,
+            InstanceState::Booting => 1,
+            InstanceState::Connecting => 2,
+            InstanceState::Initializating => 3,
+            InstanceState::Running => 4,
+            InstanceState::Warning => 5,
+            InstanceState::Error => 6,
+            InstanceState::Cleaning => 7,
+            InstanceState::Stopping => 8,
+        }
+    }
+}
+
+impl TryFrom<u16> for InstanceState {
+    type Error = String;
+
+    fn try_from(value: u16) -> Result<Self, <Self as TryFrom<u16>>::Error> {
+        match value {
+            0 => Ok(InstanceState::Undefined),
+            1 => Ok(InstanceState::Booting),
+            2 => Ok(InstanceState::Connecting),
+            3 => Ok(InstanceState::Initializating),
+            4 => Ok(InstanceState::Running),
+            5 => Ok(InstanceState::Warning),
+            6 => Ok(InstanceState::Error),
+            7 => Ok(InstanceState::Cleaning),
+            8 => Ok(InstanceState::Stopping),
+            _ => Err(format!("Invalid InstanceState value: {}", value)),
+        }
+    }
+}
+
+impl InstanceState {
+    // ------------------------------------------------------------------------
+
+    /// States that can legally follow this one in the instance FSM
+    ///
+    /// ```text
+    /// Undefined -> Booting -> Connecting -> Initializating -> Running <-> Warning
+    /// Running | Warning -> Cleaning -> Booting (reboot)
+    /// any active state -> Error -> Cleaning
+    /// any state except Undefined -> Stopping (final)
+    /// ```
+    ///
+    pub fn allowed_next_states(&self) -> &'static [InstanceState] {
+        use InstanceState::*;
+        match self {
+            Undefined => &[Booting],
+            Booting => &[Connecting, Error, Stopping],
+            Connecting => &[Initializating, Error, Stopping],
+            Initializating => &[Running, Error, Stopping],
+            Running => &[Warning, Error, Cleaning, Stopping],
+            Warning => &[Running, Error, Cleaning, Stopping],
+            Error => &[Cleaning, Stopping],
+            Cleaning => &[Booting, Error, Stopping],
+            Stopping => &[],
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// True if the FSM can move from this state to `next`
+    ///
+    /// Staying in the same state is not a transition and is always accepted.
+    ///
+    pub fn can_transition_to(&self, next: &InstanceState) -> bool {
+        self == next || self.allowed_next_states().contains(next)
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InstanceState;
+
+    const ALL_STATES: [InstanceState; 9] = [
+        InstanceState::Undefined,
+        InstanceState::Booting,
+        InstanceState::Connecting,
+        InstanceState::Initializating,
+        InstanceState::Running,
+        InstanceState::Warning,
+        InstanceState::Error,
+        InstanceState::Cleaning,
+        InstanceState::Stopping,
+    ];
+
+    #[test]
+    fn test_u16_round_trip() {
+        for state in ALL_STATES {
+            let value: u16 = state.into();
+            assert_eq!(InstanceState::try_from(value), Ok(state));
+        }
+        assert!(InstanceState::try_from(9).is_err());
+    }
+
+    #[test]
+    fn test_transitions() {
+        assert!(InstanceState::Booting.can_transition_to(&InstanceState::Connecting));
+        assert!(InstanceState::Initializating.can_transition_to(&InstanceState::Running));
+        assert!(InstanceState::Running.can_transition_to(&InstanceState::Running));
+        assert!(!InstanceState::Booting.can_transition_to(&InstanceState::Running));
+        assert!(!InstanceState::Stopping.can_transition_to(&InstanceState::Booting));
+    }
+}
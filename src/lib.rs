@@ -45,8 +45,10 @@ pub use attribute_metadata::AttributeMetadata;
 pub mod attribute;
 pub use attribute::boolean::BooleanAttribute;
 pub use attribute::bytes::BytesAttribute;
+pub use attribute::derived::DerivedAttribute;
 pub use attribute::notification::NotificationAttribute;
 pub use attribute::number::NumberAttribute;
+pub use attribute::sample::Sample;
 pub use attribute::status::StatusAttribute;
 pub use attribute::string::StringAttribute;
 pub use attribute::structure::StructureAttribute;
@@ -56,10 +58,18 @@ pub use attribute::structure::StructureAttribute;
 pub mod task_monitor;
 pub use task_monitor::TaskMonitor;
 
+/// Alarms and watchdogs on attributes
+///
+pub mod watch;
+pub use watch::Watch;
+
 /// Module to manage topic helper
 ///
 mod topic;
 pub use topic::Topic;
+pub use topic::TopicBuilder;
+pub use topic::TopicError;
+pub use topic::TopicSuffix;
 
 /// FlatBuffers: Serialization and Deserialization
 ///
@@ -90,3 +100,11 @@ pub use executor::Executor;
 ///
 pub mod security;
 
+/// Synchronous facade over the reactor and the attributes
+///
+pub mod blocking;
+
+/// Local zenoh sessions shared by the unit tests
+///
+#[cfg(test)]
+mod test_support;
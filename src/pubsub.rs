@@ -1,6 +1,7 @@
 // mod mqtt;
 mod zenoh;
 
+use crate::reactor::ReactorConfig;
 use bytes::Bytes;
 use std::fmt::Debug;
 use thiserror::Error as ThisError;
@@ -20,8 +21,11 @@ pub enum Error {
 }
 
 #[derive(Debug, Clone)]
-///1
+/// Connection options
 ///
+/// Converted into a [ReactorConfig] to open the session, prefer it for new code.
+///
+#[deprecated(note = "use reactor::ReactorConfig instead")]
 pub struct Options {
     pub ip: String,
     pub port: u16,
@@ -31,6 +35,7 @@ pub struct Options {
     pub namespace: Option<String>,
 }
 
+#[allow(deprecated)]
 impl Default for Options {
     fn default() -> Self {
         Options {
@@ -44,6 +49,7 @@ impl Default for Options {
     }
 }
 
+#[allow(deprecated)]
 impl Options {
     pub fn new<T: Into<String>>(
         ip: T,
@@ -64,6 +70,21 @@ impl Options {
     }
 }
 
+#[allow(deprecated)]
+impl From<Options> for ReactorConfig {
+    fn from(options: Options) -> Self {
+        ReactorConfig {
+            address: Some(options.ip),
+            port: Some(options.port),
+            ca_certificate: Some(options.root_ca_certificate),
+            connect_certificate: Some(options.connect_certificate),
+            connect_private_key: Some(options.connect_private_key),
+            namespace: options.namespace,
+            ..Default::default()
+        }
+    }
+}
+
 // impl Default for Options {
 //     fn default() -> Self {
 //         Self {
@@ -100,3 +121,34 @@ pub use zenoh::new_connection;
 // pub use zenoh::Publisher;
 // pub use zenoh::Subscriber;
 // pub use zenoh::ZenohListener;
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_convert_into_reactor_config() {
+        let config = ReactorConfig::from(Options::default());
+        assert_eq!(config.address.as_deref(), Some("127.0.0.1"));
+        assert_eq!(config.port, Some(7447));
+        assert_eq!(
+            config.connect_certificate.as_deref(),
+            Some("./credentials/certificates/writer_certificate.pem")
+        );
+        assert_eq!(config.namespace, None);
+
+        let config = ReactorConfig::from(Options::new(
+            "10.0.0.2",
+            7448,
+            "ca.pem",
+            "cert.pem",
+            "key.pem",
+            Some("bench"),
+        ));
+        assert_eq!(config.address.as_deref(), Some("10.0.0.2"));
+        assert_eq!(config.ca_certificate.as_deref(), Some("ca.pem"));
+        assert_eq!(config.connect_private_key.as_deref(), Some("key.pem"));
+        assert_eq!(config.namespace.as_deref(), Some("bench"));
+    }
+}
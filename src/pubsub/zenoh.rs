@@ -1,6 +1,7 @@
-use super::Options;
+use crate::reactor::ReactorBuilder;
+use crate::reactor::ReactorConfig;
 use thiserror::Error as ThisError;
-use zenoh::{open, Config, Session};
+use zenoh::Session;
 
 #[derive(ThisError, Debug, Clone)]
 pub enum SessionError {
@@ -8,58 +9,18 @@ pub enum SessionError {
     SessionError { cause: String },
 }
 
-/// Set client Options
-///
-fn client_config(options: Options) -> Config {
-    let conf = format!(
-        r#"{{
-            "mode": "client",
-            "connect": {{
-                "endpoints": ["quic/{}:{}"]
-            }},
-            "transport": {{
-                "link": {{
-                    "tls": {{
-                        "root_ca_certificate": "{}",
-                        "enable_mtls": true,
-                        "connect_private_key": "{}",
-                        "connect_certificate": "{}"
-                    }}
-                }}, 
-            }}
-        }}"#,
-        options.ip,
-        options.port,
-        options.root_ca_certificate,
-        options.connect_private_key,
-        options.connect_certificate
-    );
-
-    // println!("Zenoh client config: {}", conf);
-    let config = Config::from_json5(&conf).unwrap();
-
-    config
-}
-
 /// Start a Zenoh session
 ///
-pub async fn new_connection(options: Options) -> Result<Session, SessionError> {
-    // println!("0.1");
-    let config = client_config(options);
-
-    // println!("0.2");
-    match open(config).await {
-        Ok(session) => {
-            // println!("0.3");
-            Ok(session)
-        }
-        Err(e) => {
-            // println!("0.4");
-            Err(SessionError::SessionError {
-                cause: e.to_string(),
-            })
-        }
-    }
+/// Accepts a [ReactorConfig] or the deprecated `pubsub::Options`. Fields missing
+/// in `options` fall back to the ReactorBuilder defaults.
+///
+pub async fn new_connection<O: Into<ReactorConfig>>(options: O) -> Result<Session, SessionError> {
+    ReactorBuilder::from_config(options.into())
+        .create_zenoh_session()
+        .await
+        .map_err(|e| SessionError::SessionError {
+            cause: e.to_string(),
+        })
 }
 
 // #[derive(Debug)]
@@ -2,32 +2,70 @@ use crate::attribute::notification::NotificationAttribute;
 use crate::attribute::status::StatusAttribute;
 
 use crate::attribute::structure::StructureAttribute;
+use crate::attribute::tasks::TaskScope;
 use crate::attribute_builder::AttributeBuilder;
+use crate::security::inspect::CertificateSummary;
+use crate::security::rotation::renew_client_certificate;
+use crate::security::rotation::CertificateSigner;
+use crate::task_monitor::TaskMonitor;
 use crate::AttributeMetadata;
 use crate::AttributeMode;
+use std::sync::Arc;
+use tokio::sync::watch;
 use zenoh::Session;
 
 /// Builder module for creating Reactor instances
 pub mod builder;
 pub use builder::ReactorBuilder;
 
+/// Connection settings loadable from files, profiles and environment
+pub mod config;
+pub use config::ReactorConfig;
+
+/// In-memory credentials
+pub mod credential;
+pub use credential::Passphrase;
+pub use credential::Pem;
+
+/// Rights granted by the certificate common name
+pub mod role;
+pub use role::Access;
+pub use role::Role;
+
+/// Session and role shared with the attributes
+pub mod connection;
+pub use connection::Connection;
+
+/// Link protocol and session mode choices
+pub mod transport;
+pub use transport::SessionMode;
+pub use transport::Transport;
+
 /// The reactor is the main structure that will handle the connections and the events
 ///
 /// All the attribute and objects will be powered by the reactor
 ///
 #[derive(Clone, Debug)]
 pub struct Reactor {
-    /// The zenoh session
-    pub session: Session,
+    /// Session and role, published to the attributes when the session is reopened
+    ///
+    /// Shared by the clones of the reactor.
+    connections: Arc<watch::Sender<Connection>>,
 
     /// The structure attribute
     pub structure: StructureAttribute,
+
+    /// Background tasks of the attributes created by this reactor
+    tasks: TaskScope,
+
+    /// Monitor the attribute tasks are reported to, if any
+    task_monitor: Option<TaskMonitor>,
 }
 
 /// PartialEq implementation that checks if session IDs are equal
 impl PartialEq for Reactor {
     fn eq(&self, other: &Self) -> bool {
-        self.session.zid() == other.session.zid()
+        self.current_session().zid() == other.current_session().zid()
     }
 }
 
@@ -52,6 +90,14 @@ impl Reactor {
     /// # Returns
     /// A new Reactor instance with an initialized StructureAttribute
     pub async fn new(session: Session) -> Self {
+        Self::from_connection(Connection::new(session)).await
+    }
+
+    /// Creates a new Reactor instance on a session opened with a role
+    ///
+    pub(crate) async fn from_connection(connection: Connection) -> Self {
+        let session = connection.session.clone();
+
         // Create metadata for the structure attribute
         let metadata = AttributeMetadata::from_topic(
             "pza/_/structure".to_string(),
@@ -60,9 +106,159 @@ impl Reactor {
         );
 
         // Create the structure attribute wrapping StdObjAttribute<StructureBuffer>
-        let structure = StructureAttribute::new(session.clone(), metadata).await;
+        let structure = StructureAttribute::new(session.clone(), metadata)
+            .await
+            .expect("Invalid structure topic");
+
+        // Attributes follow the session when it is reopened
+        let (connections, _) = watch::channel(connection);
+        structure.inner.follow(connections.subscribe());
+
+        // Their tasks are stopped by the reactor on shutdown
+        let tasks = TaskScope::default();
+        structure.inner.track_in(&tasks);
+
+        Self {
+            connections: Arc::new(connections),
+            structure,
+            tasks,
+            task_monitor: None,
+        }
+    }
+
+    /// Report the background tasks of the attributes to a task monitor
+    ///
+    /// Tasks of the attributes already created are reported too. Each attribute
+    /// has a `{topic}/listener` task and a `{topic}/follow` task.
+    pub fn set_task_monitor(&mut self, monitor: TaskMonitor) {
+        self.tasks.monitor_with(monitor.handle_sender());
+        self.task_monitor = Some(monitor);
+    }
+
+    /// Monitor the attribute tasks are reported to, if any
+    ///
+    pub fn task_monitor(&self) -> Option<&TaskMonitor> {
+        self.task_monitor.as_ref()
+    }
+
+    /// Tasks of the attributes created by this reactor
+    ///
+    pub(crate) fn task_scope(&self) -> &TaskScope {
+        &self.tasks
+    }
+
+    /// Stop the background tasks of all the attributes and close the session
+    ///
+    /// Attribute handles do not receive values anymore and their commands fail
+    /// with [crate::attribute::AttributeError::Session]. The task monitor, if
+    /// any, reports the end of the tasks and is left running for its owner.
+    ///
+    /// # Returns
+    /// An error if the session cannot be closed properly
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.tasks.abort_all();
+        self.current_session()
+            .close()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to close the session: {}", e))
+    }
 
-        Self { session, structure }
+    /// Session currently used by the reactor and its attributes
+    ///
+    pub fn current_session(&self) -> Session {
+        self.connections.borrow().session.clone()
+    }
+
+    /// Channel notified with the new session and role each time the session
+    /// is reopened
+    ///
+    pub fn connection_updates(&self) -> watch::Receiver<Connection> {
+        self.connections.subscribe()
+    }
+
+    /// Open a new session with the builder settings and move to it
+    ///
+    /// Attribute handles created from this reactor (and its clones) stay valid:
+    /// they subscribe again and send their commands on the new session, with
+    /// the rights of the new role. The old session is closed once replaced.
+    ///
+    /// # Arguments
+    /// * `builder` - Settings of the new session, typically with renewed credentials
+    ///
+    /// # Returns
+    /// An error if the new session cannot be opened, the current one is then kept
+    pub async fn reopen(&mut self, builder: &ReactorBuilder) -> anyhow::Result<()> {
+        let role = builder.preflight_credentials()?;
+        let session = builder.create_zenoh_session().await?;
+
+        let old = self
+            .connections
+            .send_replace(Connection::with_role(session, role));
+        if let Err(e) = old.session.close().await {
+            tracing::warn!("Failed to close the previous session: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Renew the client certificate of the builder and reopen the session with it
+    ///
+    /// The certificate file is replaced atomically, the private key is kept.
+    /// Credentials given in memory cannot be renewed this way.
+    ///
+    /// # Arguments
+    /// * `builder` - Settings used to open the current session
+    /// * `signer` - Authority that signs the new certificate
+    /// * `validity_days` - Validity of the new certificate
+    ///
+    /// # Returns
+    /// The summary of the new certificate
+    pub async fn rotate_credentials(
+        &mut self,
+        builder: &ReactorBuilder,
+        signer: &dyn CertificateSigner,
+        validity_days: i32,
+    ) -> anyhow::Result<CertificateSummary> {
+        let (cert_path, key_path) = builder.credential_paths()?;
+        let passphrase = builder
+            .connect_private_key_passphrase
+            .as_ref()
+            .map(Passphrase::as_str);
+        let summary =
+            renew_client_certificate(&cert_path, &key_path, passphrase, signer, validity_days)?;
+        self.reopen(builder).await?;
+        Ok(summary)
+    }
+
+    /// Role derived from the common name of the client certificate
+    ///
+    /// `None` when the session does not use TLS, no restriction is then applied.
+    pub fn role(&self) -> Option<Role> {
+        self.connections.borrow().role.clone()
+    }
+
+    /// Check if the role allows an operation on a topic
+    ///
+    /// # Returns
+    /// Always true when there is no role (no TLS)
+    pub fn allows(&self, access: Access, topic: &str) -> bool {
+        self.connections
+            .borrow()
+            .role
+            .as_ref()
+            .map(|role| role.allows(access, topic))
+            .unwrap_or(true)
+    }
+
+    /// Warn when a platform attribute will never receive data
+    ///
+    fn warn_if_subscription_denied(&self, topic: &str) {
+        if !self.allows(Access::Subscribe, topic) {
+            tracing::warn!(
+                "Role {} cannot subscribe to {}, no value will be received",
+                self.role().map(|r| r.to_string()).unwrap_or_default(),
+                topic
+            );
+        }
     }
 
     /// Create a new status attribute on "pza/_/status"
@@ -75,8 +271,14 @@ impl Reactor {
             Some("status".to_string()),
             AttributeMode::ReadOnly,
         );
+        self.warn_if_subscription_denied(&metadata.topic);
 
-        StatusAttribute::new(self.session.clone(), metadata).await
+        let attribute = StatusAttribute::new(self.current_session(), metadata)
+            .await
+            .expect("Invalid status topic");
+        attribute.inner.follow(self.connection_updates());
+        attribute.inner.track_in(&self.tasks);
+        attribute
     }
 
     /// Create a new notification attribute on "pza/_/notifications"
@@ -89,8 +291,14 @@ impl Reactor {
             Some("notification".to_string()),
             AttributeMode::ReadOnly,
         );
+        self.warn_if_subscription_denied(&metadata.topic);
 
-        NotificationAttribute::new(self.session.clone(), metadata).await
+        let attribute = NotificationAttribute::new(self.current_session(), metadata)
+            .await
+            .expect("Invalid notification topic");
+        attribute.inner.follow(self.connection_updates());
+        attribute.inner.track_in(&self.tasks);
+        attribute
     }
 
     /// Create an attribute builder finding metadata in structure attribute
@@ -1,8 +1,26 @@
-use crate::security::utils::get_default_certificate_paths;
+use crate::security::inspect::read_pem_file;
+use crate::security::inspect::verify_chain;
+use crate::security::key_protection::is_encrypted_pem;
+use crate::security::key_protection::open_key_pem;
+use crate::security::utils::default_certificate_paths;
 
+use super::config::merge_json;
+use super::Connection;
+use super::Passphrase;
+use super::Pem;
 use super::Reactor;
+use super::ReactorConfig;
+use super::Role;
+use super::SessionMode;
+use super::Transport;
+use crate::task_monitor::TaskMonitor;
 use anyhow::Result;
 use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 use zenoh::Session as ZenohSession;
 
 /// Builder pattern for creating Reactor instances
@@ -15,31 +33,62 @@ pub struct ReactorBuilder {
     pub address: Option<String>,
     /// Port number to connect to
     pub port: Option<u16>,
-    /// Path to CA certificate file
+    /// Path to CA certificate file (default location if None)
     pub ca_certificate: Option<String>,
-    /// Path to client certificate file
+    /// Path to client certificate file (default location if None)
     pub connect_certificate: Option<String>,
-    /// Path to client private key file
+    /// Path to client private key file (default location if None)
     pub connect_private_key: Option<String>,
+    /// In-memory CA certificate, takes precedence over the path
+    pub ca_certificate_pem: Option<Pem>,
+    /// In-memory client certificate, takes precedence over the path
+    pub connect_certificate_pem: Option<Pem>,
+    /// In-memory client private key, takes precedence over the path
+    pub connect_private_key_pem: Option<Pem>,
+    /// Passphrase of the client private key, when it is encrypted
+    pub connect_private_key_passphrase: Option<Passphrase>,
     /// Namespace for the connection
     pub namespace: Option<String>,
+    /// Maximum time to open the session (no limit if None)
+    pub connect_timeout: Option<Duration>,
+    /// Additional endpoints tried for failover, after `address:port`
+    pub endpoints: Vec<String>,
+    /// Link protocol of the endpoints given without explicit protocol
+    pub transport: Transport,
+    /// Zenoh session mode
+    pub mode: SessionMode,
+    /// Multicast scouting on the local network (Zenoh default if None)
+    pub scouting: Option<bool>,
+    /// Raw Zenoh configuration merged on top of the generated one
+    pub zenoh_overrides: Value,
+    /// Monitor of the attribute background tasks (not monitored if None)
+    pub task_monitor: Option<TaskMonitor>,
 }
 
 impl Default for ReactorBuilder {
     /// Creates a new ReactorBuilder with default values
+    ///
+    /// Credentials are not resolved here: the default paths of the user
+    /// `.panduza` directory are looked up at build time, without creating anything.
     fn default() -> Self {
-        let (root_ca_path, client_cert_path, client_key_path) = get_default_certificate_paths();
-        let root_ca_path = root_ca_path.into_os_string().into_string().unwrap();
-        let client_cert_path = client_cert_path.into_os_string().into_string().unwrap();
-        let client_key_path = client_key_path.into_os_string().into_string().unwrap();
-
         Self {
             address: None,
             port: None,
-            ca_certificate: Some(root_ca_path),
-            connect_certificate: Some(client_cert_path),
-            connect_private_key: Some(client_key_path),
+            ca_certificate: None,
+            connect_certificate: None,
+            connect_private_key: None,
+            ca_certificate_pem: None,
+            connect_certificate_pem: None,
+            connect_private_key_pem: None,
+            connect_private_key_passphrase: None,
             namespace: None,
+            connect_timeout: None,
+            endpoints: Vec::new(),
+            transport: Transport::default(),
+            mode: SessionMode::default(),
+            scouting: None,
+            zenoh_overrides: Value::Null,
+            task_monitor: None,
         }
     }
 }
@@ -107,6 +156,53 @@ impl ReactorBuilder {
 
     // ----------------------------------------------------------------------------
 
+    /// Sets the CA certificate content
+    ///
+    /// # Arguments
+    /// * `ca_certificate` - PEM text or an `rcgen::Certificate`
+    pub fn ca_certificate_pem<P: Into<Pem>>(mut self, ca_certificate: P) -> Self {
+        self.ca_certificate_pem = Some(ca_certificate.into());
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the client certificate content
+    ///
+    /// # Arguments
+    /// * `connect_certificate` - PEM text or an `rcgen::Certificate`
+    pub fn connect_certificate_pem<P: Into<Pem>>(mut self, connect_certificate: P) -> Self {
+        self.connect_certificate_pem = Some(connect_certificate.into());
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the client private key content
+    ///
+    /// # Arguments
+    /// * `connect_private_key` - PEM text or an `rcgen::KeyPair`
+    pub fn connect_private_key_pem<P: Into<Pem>>(mut self, connect_private_key: P) -> Self {
+        self.connect_private_key_pem = Some(connect_private_key.into());
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the passphrase of an encrypted client private key
+    ///
+    /// The key is decrypted in memory when the session is opened, the file is
+    /// left encrypted.
+    ///
+    /// # Arguments
+    /// * `passphrase` - The passphrase used to encrypt the key
+    pub fn connect_private_key_passphrase<P: Into<Passphrase>>(mut self, passphrase: P) -> Self {
+        self.connect_private_key_passphrase = Some(passphrase.into());
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
     /// Sets the namespace for the connection
     ///
     /// # Arguments
@@ -118,15 +214,232 @@ impl ReactorBuilder {
 
     // ----------------------------------------------------------------------------
 
+    /// Sets the maximum time allowed to open the session
+    ///
+    /// # Arguments
+    /// * `connect_timeout` - The timeout duration
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Adds an endpoint tried for failover
+    ///
+    /// Endpoints are tried in order, after the one built from `address` and `port`.
+    ///
+    /// # Arguments
+    /// * `endpoint` - `host:port` (uses the builder transport) or a full Zenoh locator like `tcp/host:port`
+    pub fn endpoint(mut self, endpoint: String) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Replaces the failover endpoints
+    ///
+    /// # Arguments
+    /// * `endpoints` - List of `host:port` or full Zenoh locators
+    pub fn endpoints(mut self, endpoints: Vec<String>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the link protocol
+    ///
+    /// `Transport::Tcp` disables TLS and must only be used with a local mock platform.
+    ///
+    /// # Arguments
+    /// * `transport` - The transport to use
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the Zenoh session mode
+    ///
+    /// # Arguments
+    /// * `mode` - Client or peer
+    pub fn mode(mut self, mode: SessionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Enables or disables multicast scouting on the local network
+    ///
+    /// With scouting enabled, no endpoint is required.
+    ///
+    /// # Arguments
+    /// * `enabled` - True to discover the platform on the local network
+    pub fn scouting(mut self, enabled: bool) -> Self {
+        self.scouting = Some(enabled);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets a raw Zenoh configuration value
+    ///
+    /// # Arguments
+    /// * `path` - Dotted path in the Zenoh configuration (e.g. `transport.link.tx.lease`)
+    /// * `value` - The JSON value to set
+    pub fn zenoh_override<V: Into<Value>>(mut self, path: &str, value: V) -> Self {
+        let overlay = path
+            .rsplit('.')
+            .fold(value.into(), |value, key| json!({ key: value }));
+        merge_json(&mut self.zenoh_overrides, overlay);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Merges a raw Zenoh configuration object on top of the generated one
+    ///
+    /// # Arguments
+    /// * `overrides` - JSON object with the Zenoh configuration structure
+    pub fn zenoh_overrides(mut self, overrides: Value) -> Self {
+        merge_json(&mut self.zenoh_overrides, overrides);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Sets the monitor of the attribute background tasks
+    ///
+    /// # Arguments
+    /// * `monitor` - Monitor reporting the receive loops of the attributes
+    pub fn task_monitor(mut self, monitor: TaskMonitor) -> Self {
+        self.task_monitor = Some(monitor);
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Creates a builder from a configuration, on top of the default values
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to apply
+    pub fn from_config(config: ReactorConfig) -> Self {
+        Self::new().apply_config(config)
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Creates a builder from a JSON5 configuration file
+    ///
+    /// `PZA_*` environment variables are applied on top of the file.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the configuration file
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_config(ReactorConfig::from_file(path)?).env_overlay()
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Creates a builder from a named profile stored in `~/.panduza/profiles/`
+    ///
+    /// `PZA_*` environment variables are applied on top of the profile.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the profile (e.g. `lab-a`, `ci`)
+    pub fn from_profile(name: &str) -> Result<Self> {
+        Self::from_config(ReactorConfig::from_profile(name)?).env_overlay()
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Overrides the fields set in the configuration
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to apply
+    pub fn apply_config(mut self, config: ReactorConfig) -> Self {
+        if let Some(address) = config.address {
+            self.address = Some(address);
+        }
+        if let Some(port) = config.port {
+            self.port = Some(port);
+        }
+        if let Some(ca_certificate) = config.ca_certificate {
+            self.ca_certificate = Some(ca_certificate);
+        }
+        if let Some(connect_certificate) = config.connect_certificate {
+            self.connect_certificate = Some(connect_certificate);
+        }
+        if let Some(connect_private_key) = config.connect_private_key {
+            self.connect_private_key = Some(connect_private_key);
+        }
+        if let Some(ca_certificate_pem) = config.ca_certificate_pem {
+            self.ca_certificate_pem = Some(ca_certificate_pem);
+        }
+        if let Some(connect_certificate_pem) = config.connect_certificate_pem {
+            self.connect_certificate_pem = Some(connect_certificate_pem);
+        }
+        if let Some(connect_private_key_pem) = config.connect_private_key_pem {
+            self.connect_private_key_pem = Some(connect_private_key_pem);
+        }
+        if let Some(passphrase) = config.connect_private_key_passphrase {
+            self.connect_private_key_passphrase = Some(passphrase);
+        }
+        if let Some(namespace) = config.namespace {
+            self.namespace = Some(namespace);
+        }
+        if let Some(connect_timeout_ms) = config.connect_timeout_ms {
+            self.connect_timeout = Some(Duration::from_millis(connect_timeout_ms));
+        }
+        if let Some(endpoints) = config.endpoints {
+            self.endpoints = endpoints;
+        }
+        if let Some(transport) = config.transport {
+            self.transport = transport;
+        }
+        if let Some(mode) = config.mode {
+            self.mode = mode;
+        }
+        if let Some(scouting) = config.scouting {
+            self.scouting = Some(scouting);
+        }
+        if let Some(zenoh) = config.zenoh {
+            merge_json(&mut self.zenoh_overrides, zenoh);
+        }
+        self
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Overrides the fields defined by `PZA_*` environment variables
+    ///
+    /// # Returns
+    /// A Result containing the builder or an error if a variable is malformed
+    pub fn env_overlay(self) -> Result<Self> {
+        Ok(self.apply_config(ReactorConfig::from_env()?))
+    }
+
+    // ----------------------------------------------------------------------------
+
     /// Builds and returns a Reactor instance
     ///
     /// Creates a Zenoh session with the configured parameters and uses it to create a Reactor.
+    /// The role of the reactor comes from the common name of the client certificate.
     ///
     /// # Returns
     /// A Result containing the Reactor instance or an error
     pub async fn build(self) -> Result<Reactor> {
+        let role = self.preflight_credentials()?;
         let session = self.create_zenoh_session().await?;
-        Ok(Reactor::new(session).await)
+        let mut reactor = Reactor::from_connection(Connection::with_role(session, role)).await;
+        if let Some(monitor) = self.task_monitor {
+            reactor.set_task_monitor(monitor);
+        }
+        Ok(reactor)
     }
 
     // ----------------------------------------------------------------------------
@@ -135,11 +448,24 @@ impl ReactorBuilder {
     ///
     /// # Returns
     /// A Result containing the Zenoh session or an error
-    async fn create_zenoh_session(&self) -> Result<ZenohSession> {
-        let config_json = self.build_zenoh_config()?;
-        let config = zenoh::Config::from_json5(&config_json)
+    pub(crate) async fn create_zenoh_session(&self) -> Result<ZenohSession> {
+        let config_value = self.build_zenoh_config()?;
+        let config = zenoh::Config::from_json5(&config_value.to_string())
             .map_err(|e| anyhow::anyhow!("Failed to parse Zenoh config: {}", e))?;
-        let session = zenoh::open(config).await.map_err(|e| {
+        // In-memory credentials must not appear in error messages
+        let config_json = redact_secrets(config_value).to_string();
+        let open = zenoh::open(config);
+        let result = match self.connect_timeout {
+            Some(duration) => tokio::time::timeout(duration, open).await.map_err(|_| {
+                anyhow::anyhow!(
+                    "Timeout ({:?}) while opening Zenoh session. Configuration used: {}",
+                    duration,
+                    config_json
+                )
+            })?,
+            None => open.await,
+        };
+        let session = result.map_err(|e| {
             anyhow::anyhow!(
                 "Failed to open Zenoh session: {}. Configuration used: {}",
                 e,
@@ -151,51 +477,469 @@ impl ReactorBuilder {
 
     // ----------------------------------------------------------------------------
 
-    /// Builds the Zenoh configuration JSON
+    /// Builds the Zenoh configuration as a JSON value
     ///
     /// # Returns
-    /// A Result containing the configuration JSON string or an error
-    fn build_zenoh_config(&self) -> Result<String> {
-        let address = self
-            .address
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Address is required"))?;
-        let port = self
-            .port
-            .ok_or_else(|| anyhow::anyhow!("Port is required"))?;
-
-        let ca_cert_path = self
-            .ca_certificate
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("CA certificate path is required"))?;
-        let connect_cert_path = self
-            .connect_certificate
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Connect certificate path is required"))?;
-        let connect_key_path = self
-            .connect_private_key
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Connect private key path is required"))?;
+    /// A Result containing the configuration or an error
+    fn build_zenoh_config(&self) -> Result<Value> {
+        let endpoints = self.resolve_endpoints()?;
 
-        // Use file paths directly as specified in requirements
-        // Warning: fields refer to paths (not the content)
-        let config = json!({
-            "mode": "client",
+        let mut config = json!({
+            "mode": self.mode.as_str(),
             "connect": {
-                "endpoints": [format!("quic/{}:{}", address, port)]
-            },
-            "transport": {
-                "link": {
-                    "tls": {
-                        "root_ca_certificate": ca_cert_path,
-                        "enable_mtls": true,
-                        "connect_private_key": connect_key_path,
-                        "connect_certificate": connect_cert_path
-                    }
-                }
+                "endpoints": endpoints
             }
         });
 
-        Ok(config.to_string())
+        if let Some(scouting) = self.scouting {
+            config["scouting"] = json!({ "multicast": { "enabled": scouting } });
+        }
+
+        if self.uses_tls(&endpoints) {
+            let mut credentials = self.resolve_credentials()?;
+            credentials.connect_private_key = credentials
+                .connect_private_key
+                .unlock(self.connect_private_key_passphrase.as_ref())?;
+
+            let mut tls = Map::new();
+            tls.insert("enable_mtls".to_string(), json!(true));
+            credentials
+                .ca_certificate
+                .insert(&mut tls, "root_ca_certificate");
+            credentials
+                .connect_private_key
+                .insert(&mut tls, "connect_private_key");
+            credentials
+                .connect_certificate
+                .insert(&mut tls, "connect_certificate");
+
+            config["transport"] = json!({ "link": { "tls": tls } });
+        }
+
+        merge_json(&mut config, self.zenoh_overrides.clone());
+
+        Ok(config)
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// True if at least one link needs the TLS credentials
+    ///
+    /// # Arguments
+    /// * `endpoints` - The resolved locators
+    fn uses_tls(&self, endpoints: &[String]) -> bool {
+        self.transport.is_secure()
+            || endpoints
+                .iter()
+                .any(|e| e.starts_with("quic/") || e.starts_with("tls/"))
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Resolves each credential from its content, its path or its default path
+    ///
+    /// Default paths are only computed here, nothing is created on disk.
+    ///
+    /// # Returns
+    /// A Result containing the credentials or an error if one cannot be resolved
+    fn resolve_credentials(&self) -> Result<Credentials> {
+        let (default_ca, default_cert, default_key) = match default_certificate_paths() {
+            Some((ca, cert, key)) => (Some(ca), Some(cert), Some(key)),
+            None => (None, None, None),
+        };
+        Ok(Credentials {
+            ca_certificate: CredentialSource::resolve(
+                &self.ca_certificate_pem,
+                &self.ca_certificate,
+                default_ca,
+                "CA certificate",
+            )?,
+            connect_certificate: CredentialSource::resolve(
+                &self.connect_certificate_pem,
+                &self.connect_certificate,
+                default_cert,
+                "Connect certificate",
+            )?,
+            connect_private_key: CredentialSource::resolve(
+                &self.connect_private_key_pem,
+                &self.connect_private_key,
+                default_key,
+                "Connect private key",
+            )?,
+        })
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Checks the client certificate before opening the session
+    ///
+    /// Fails fast with a clear error when the certificate cannot be read, is
+    /// expired or is not signed by the configured CA, or when the private key
+    /// is encrypted and cannot be opened with the configured passphrase. Logs a warning when the
+    /// certificate expires within 30 days.
+    ///
+    /// # Returns
+    /// A Result containing the role of the certificate (`None` without TLS)
+    /// or an error if the credentials are not usable
+    pub(crate) fn preflight_credentials(&self) -> Result<Option<Role>> {
+        if !self.uses_tls(&self.resolve_endpoints()?) {
+            return Ok(None);
+        }
+        let credentials = self.resolve_credentials()?;
+        credentials
+            .connect_private_key
+            .unlock(self.connect_private_key_passphrase.as_ref())?;
+        let ca_pem = credentials.ca_certificate.read("CA certificate")?;
+        let cert_pem = credentials
+            .connect_certificate
+            .read("Connect certificate")?;
+
+        let summary = verify_chain(&cert_pem, &ca_pem)
+            .map_err(|e| anyhow::anyhow!("Client certificate rejected: {}", e))?;
+
+        if summary.expires_within(time::Duration::days(EXPIRY_WARNING_DAYS)) {
+            tracing::warn!(
+                "Client certificate {:?} expires on {}",
+                summary.subject,
+                summary.not_after
+            );
+        }
+        Ok(Role::from_certificate(&summary))
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Paths of the client certificate and private key files
+    ///
+    /// # Returns
+    /// A Result containing the paths or an error if a credential is given in memory
+    pub(crate) fn credential_paths(&self) -> Result<(PathBuf, PathBuf)> {
+        let credentials = self.resolve_credentials()?;
+        match (
+            credentials.connect_certificate,
+            credentials.connect_private_key,
+        ) {
+            (CredentialSource::Path(cert), CredentialSource::Path(key)) => {
+                Ok((PathBuf::from(cert), PathBuf::from(key)))
+            }
+            _ => Err(anyhow::anyhow!(
+                "In-memory credentials cannot be renewed in place"
+            )),
+        }
+    }
+
+    // ----------------------------------------------------------------------------
+
+    /// Builds the ordered list of Zenoh locators to connect to
+    ///
+    /// # Returns
+    /// A Result containing the locators or an error if none can be built
+    fn resolve_endpoints(&self) -> Result<Vec<String>> {
+        let mut endpoints = Vec::new();
+
+        if let Some(address) = &self.address {
+            let port = self
+                .port
+                .ok_or_else(|| anyhow::anyhow!("Port is required"))?;
+            endpoints.push(format!("{}/{}:{}", self.transport, address, port));
+        }
+
+        for endpoint in &self.endpoints {
+            if endpoint.contains('/') {
+                endpoints.push(endpoint.clone());
+            } else {
+                endpoints.push(format!("{}/{}", self.transport, endpoint));
+            }
+        }
+
+        if endpoints.is_empty() && self.scouting != Some(true) {
+            return Err(anyhow::anyhow!(
+                "Address is required (or an endpoint, or scouting enabled)"
+            ));
+        }
+
+        Ok(endpoints)
+    }
+}
+
+/// Delay before expiration from which a warning is logged at build time
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Origin of a TLS credential
+///
+enum CredentialSource {
+    /// In-memory PEM content
+    Pem(Pem),
+    /// Path to a PEM file
+    Path(String),
+}
+
+/// TLS credentials resolved for the session
+///
+struct Credentials {
+    ca_certificate: CredentialSource,
+    connect_certificate: CredentialSource,
+    connect_private_key: CredentialSource,
+}
+
+impl CredentialSource {
+    /// In-memory content first, then the path, then the default path
+    ///
+    fn resolve(
+        pem: &Option<Pem>,
+        path: &Option<String>,
+        default_path: Option<PathBuf>,
+        name: &str,
+    ) -> Result<Self> {
+        if let Some(pem) = pem {
+            return Ok(CredentialSource::Pem(pem.clone()));
+        }
+        path.clone()
+            .or_else(|| default_path.map(|p| p.to_string_lossy().into_owned()))
+            .map(CredentialSource::Path)
+            .ok_or_else(|| anyhow::anyhow!("{} path is required", name))
+    }
+
+    /// Insert the credential in the Zenoh TLS configuration
+    ///
+    /// In-memory PEM content is passed base64 encoded (`<key>_base64`), so it never
+    /// touches the file system.
+    fn insert(&self, tls: &mut Map<String, Value>, key: &str) {
+        match self {
+            CredentialSource::Pem(pem) => {
+                tls.insert(format!("{}_base64", key), json!(pem.to_base64()));
+            }
+            CredentialSource::Path(path) => {
+                tls.insert(key.to_string(), json!(path));
+            }
+        }
+    }
+
+    /// Decrypt the credential in memory if it is an encrypted private key
+    ///
+    /// Plain keys are kept as they are, a file that cannot be read is left to
+    /// Zenoh, which reports the error.
+    fn unlock(self, passphrase: Option<&Passphrase>) -> Result<Self> {
+        let pem = match &self {
+            CredentialSource::Pem(pem) => pem.as_str().to_string(),
+            CredentialSource::Path(path) => match read_pem_file(path) {
+                Ok(pem) => pem,
+                Err(_) => return Ok(self),
+            },
+        };
+        if !is_encrypted_pem(&pem) {
+            return Ok(self);
+        }
+        open_key_pem(&pem, passphrase.map(Passphrase::as_str))
+            .map(|pem| CredentialSource::Pem(Pem::new(pem)))
+            .map_err(|e| anyhow::anyhow!("Connect private key unavailable: {}", e))
+    }
+
+    /// PEM content of the credential
+    ///
+    fn read(&self, name: &str) -> Result<String> {
+        match self {
+            CredentialSource::Pem(pem) => Ok(pem.as_str().to_string()),
+            CredentialSource::Path(path) => {
+                read_pem_file(path).map_err(|e| anyhow::anyhow!("{} unavailable: {}", name, e))
+            }
+        }
+    }
+}
+
+/// Replace inline secrets of a Zenoh configuration by a placeholder
+///
+fn redact_secrets(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    if key.ends_with("_base64") || key.ends_with("_raw") {
+                        (key, json!("<redacted>"))
+                    } else {
+                        (key, redact_secrets(value))
+                    }
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_of(builder: ReactorBuilder) -> Value {
+        builder.build_zenoh_config().unwrap()
+    }
+
+    #[test]
+    fn default_config_is_quic_client_with_mtls() {
+        let config = config_of(
+            ReactorBuilder::new()
+                .address("127.0.0.1".to_string())
+                .port(7447),
+        );
+        assert_eq!(config["mode"], "client");
+        assert_eq!(
+            config["connect"]["endpoints"],
+            json!(["quic/127.0.0.1:7447"])
+        );
+        assert_eq!(config["transport"]["link"]["tls"]["enable_mtls"], true);
+        assert!(config.get("scouting").is_none());
+    }
+
+    #[test]
+    fn failover_endpoints_and_plain_tcp() {
+        let config = config_of(
+            ReactorBuilder::new()
+                .transport(Transport::Tcp)
+                .address("10.0.0.1".to_string())
+                .port(7447)
+                .endpoint("10.0.0.2:7447".to_string())
+                .endpoint("udp/10.0.0.3:7447".to_string()),
+        );
+        assert_eq!(
+            config["connect"]["endpoints"],
+            json!([
+                "tcp/10.0.0.1:7447",
+                "tcp/10.0.0.2:7447",
+                "udp/10.0.0.3:7447"
+            ])
+        );
+        assert!(config.get("transport").is_none());
+    }
+
+    #[test]
+    fn peer_mode_scouting_and_overrides() {
+        let config = config_of(
+            ReactorBuilder::new()
+                .mode(SessionMode::Peer)
+                .scouting(true)
+                .zenoh_override("transport.link.tx.lease", 5000)
+                .zenoh_override("scouting.multicast.interface", "eth0"),
+        );
+        assert_eq!(config["mode"], "peer");
+        assert_eq!(config["connect"]["endpoints"], json!([]));
+        assert_eq!(config["scouting"]["multicast"]["enabled"], true);
+        assert_eq!(config["scouting"]["multicast"]["interface"], "eth0");
+        assert_eq!(config["transport"]["link"]["tx"]["lease"], 5000);
+        assert_eq!(config["transport"]["link"]["tls"]["enable_mtls"], true);
+    }
+
+    #[test]
+    fn in_memory_credentials() {
+        let (ca, ca_key) = crate::security::certificate::generate_root_ca();
+        let builder = ReactorBuilder::new()
+            .address("127.0.0.1".to_string())
+            .port(7447)
+            .ca_certificate_pem(&ca)
+            .connect_certificate_pem("-----BEGIN CERTIFICATE-----\n")
+            .connect_private_key_pem(&ca_key);
+        assert!(!format!("{:?}", builder).contains("PRIVATE KEY"));
+
+        let config = config_of(builder.clone());
+        let tls = &config["transport"]["link"]["tls"];
+        assert!(tls.get("root_ca_certificate").is_none());
+        assert_eq!(
+            tls["root_ca_certificate_base64"],
+            Pem::from(&ca).to_base64()
+        );
+        assert_eq!(
+            tls["connect_private_key_base64"],
+            Pem::from(&ca_key).to_base64()
+        );
+
+        let redacted = redact_secrets(builder.build_zenoh_config().unwrap());
+        assert_eq!(
+            redacted["transport"]["link"]["tls"]["connect_private_key_base64"],
+            "<redacted>"
+        );
+    }
+
+    #[test]
+    fn encrypted_private_key() {
+        use crate::security::key_protection::encrypt_key;
+
+        let (ca, ca_key) = crate::security::certificate::generate_root_ca();
+        let builder = ReactorBuilder::new()
+            .address("127.0.0.1".to_string())
+            .port(7447)
+            .ca_certificate_pem(&ca)
+            .connect_certificate_pem(&ca)
+            .connect_private_key_pem(encrypt_key(&ca_key, "secret").unwrap());
+        assert!(builder.build_zenoh_config().is_err());
+
+        let builder = builder.connect_private_key_passphrase("secret");
+        assert!(!format!("{:?}", builder).contains("secret"));
+        let config = config_of(builder);
+        assert_eq!(
+            config["transport"]["link"]["tls"]["connect_private_key_base64"],
+            Pem::new(ca_key.serialize_pem()).to_base64()
+        );
+    }
+
+    #[test]
+    fn preflight_rejects_foreign_and_expired_certificates() {
+        use crate::security::certificate::generate_cert_client_with_san;
+        use crate::security::certificate::generate_root_ca;
+        use crate::security::certificate::CertParams;
+
+        let params = |validity_days| CertParams {
+            san: vec!["localhost".into()],
+            validity_days,
+            common_name: "writer.local".into(),
+        };
+        let (ca, ca_key) = generate_root_ca();
+        let (cert, key) = generate_cert_client_with_san(&ca, &ca_key, params(365));
+        let builder = ReactorBuilder::new()
+            .address("127.0.0.1".to_string())
+            .port(7447)
+            .connect_private_key_pem(&key);
+
+        let valid = builder
+            .clone()
+            .ca_certificate_pem(&ca)
+            .connect_certificate_pem(&cert);
+        assert_eq!(valid.preflight_credentials().unwrap(), Some(Role::Writer));
+
+        let (other_ca, _) = generate_root_ca();
+        let foreign = builder
+            .clone()
+            .ca_certificate_pem(&other_ca)
+            .connect_certificate_pem(&cert);
+        let error = foreign.preflight_credentials().unwrap_err().to_string();
+        assert!(error.contains("not signed by the CA"), "{}", error);
+
+        let (expired, _) = generate_cert_client_with_san(&ca, &ca_key, params(-2));
+        let expired = builder
+            .ca_certificate_pem(&ca)
+            .connect_certificate_pem(&expired);
+        let error = expired.preflight_credentials().unwrap_err().to_string();
+        assert!(error.contains("expired"), "{}", error);
+
+        let missing = ReactorBuilder::new()
+            .transport(Transport::Tls)
+            .address("127.0.0.1".to_string())
+            .port(7447)
+            .ca_certificate("/nonexistent/ca.pem".to_string());
+        let error = missing.preflight_credentials().unwrap_err().to_string();
+        assert!(error.contains("CA certificate unavailable"), "{}", error);
+
+        let plain = ReactorBuilder::new()
+            .transport(Transport::Tcp)
+            .address("127.0.0.1".to_string())
+            .port(7447);
+        assert_eq!(plain.preflight_credentials().unwrap(), None);
+    }
+
+    #[test]
+    fn missing_endpoint_is_an_error() {
+        assert!(ReactorBuilder::new().build_zenoh_config().is_err());
+        assert!(ReactorBuilder::new()
+            .address("127.0.0.1".to_string())
+            .build_zenoh_config()
+            .is_err());
     }
 }
@@ -0,0 +1,36 @@
+use super::Role;
+use zenoh::Session;
+
+/// Session of a reactor with the role it was opened with
+///
+/// The reactor publishes a new connection each time it reopens its session, the
+/// attributes follow it to move to the new session and apply the new role
+/// together.
+///
+#[derive(Clone, Debug)]
+pub struct Connection {
+    /// The zenoh session
+    pub session: Session,
+
+    /// Role of the client certificate, `None` without TLS
+    pub role: Option<Role>,
+}
+
+impl Connection {
+    /// Connection without role (no TLS), no restriction is applied
+    ///
+    pub fn new(session: Session) -> Self {
+        Self {
+            session,
+            role: None,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Connection with the role of its client certificate
+    ///
+    pub fn with_role(session: Session, role: Option<Role>) -> Self {
+        Self { session, role }
+    }
+}
@@ -0,0 +1,106 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rcgen::Certificate;
+use rcgen::KeyPair;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+/// PEM content of a certificate or a private key, kept in memory
+///
+/// The content is never written to disk: it is passed to Zenoh through the
+/// `*_base64` TLS settings. `Debug` does not print the content.
+///
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pem(String);
+
+impl Pem {
+    /// Wrap PEM content
+    ///
+    pub fn new<S: Into<String>>(content: S) -> Self {
+        Self(content.into())
+    }
+
+    /// PEM text
+    ///
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Base64 encoding of the PEM text, as expected by Zenoh
+    ///
+    pub(crate) fn to_base64(&self) -> String {
+        BASE64.encode(self.0.as_bytes())
+    }
+}
+
+impl fmt::Debug for Pem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pem(<{} bytes>)", self.0.len())
+    }
+}
+
+impl From<String> for Pem {
+    fn from(content: String) -> Self {
+        Self(content)
+    }
+}
+
+impl From<&str> for Pem {
+    fn from(content: &str) -> Self {
+        Self(content.to_string())
+    }
+}
+
+impl From<&Certificate> for Pem {
+    fn from(certificate: &Certificate) -> Self {
+        Self(certificate.pem())
+    }
+}
+
+impl From<&KeyPair> for Pem {
+    fn from(key: &KeyPair) -> Self {
+        Self(key.serialize_pem())
+    }
+}
+
+/// Passphrase of an encrypted private key, kept in memory
+///
+/// `Debug` does not print the content.
+///
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    /// Wrap a passphrase
+    ///
+    pub fn new<S: Into<String>>(passphrase: S) -> Self {
+        Self(passphrase.into())
+    }
+
+    /// Passphrase text
+    ///
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(<redacted>)")
+    }
+}
+
+impl From<String> for Passphrase {
+    fn from(passphrase: String) -> Self {
+        Self(passphrase)
+    }
+}
+
+impl From<&str> for Passphrase {
+    fn from(passphrase: &str) -> Self {
+        Self(passphrase.to_string())
+    }
+}
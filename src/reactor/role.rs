@@ -0,0 +1,160 @@
+use crate::security::inspect::CertificateSummary;
+use crate::Topic;
+use std::fmt;
+
+/// Instance name of the platform topics (`pza/_/structure`, `pza/_/status`...)
+///
+const PLATFORM_INSTANCE: &str = "_";
+
+/// Role granted by the platform, carried by the certificate common name
+///
+/// A client certificate issued for `writer.local` gives the `writer` role.
+/// The router enforces the access rules, this type only mirrors them so that
+/// the client can refuse an operation instead of losing it silently.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// The platform itself, no restriction
+    Platform,
+    /// Pub/sub on all topics except the platform topics
+    Writer,
+    /// Sub on all topics, no publication
+    Logger,
+    /// Connect only, no pub/sub
+    Default,
+    /// Role not known by this client (e.g. `admin`), its rights are left to
+    /// the router
+    Unknown(String),
+}
+
+/// Operation checked against a role
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Publish,
+    Subscribe,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Publish => f.write_str("publish"),
+            Access::Subscribe => f.write_str("subscribe"),
+        }
+    }
+}
+
+impl Role {
+    /// Role from a certificate common name (`{role}.local`)
+    ///
+    pub fn from_common_name(common_name: &str) -> Self {
+        let role = common_name.split('.').next().unwrap_or_default();
+        match role {
+            "platform" => Role::Platform,
+            "writer" => Role::Writer,
+            "logger" | "reader" => Role::Logger,
+            "default" => Role::Default,
+            other => Role::Unknown(other.to_string()),
+        }
+    }
+
+    /// Role from a certificate summary, `None` without common name
+    ///
+    pub fn from_certificate(summary: &CertificateSummary) -> Option<Self> {
+        summary.common_name.as_deref().map(Role::from_common_name)
+    }
+
+    /// Name of the role, as found in the common name
+    ///
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::Platform => "platform",
+            Role::Writer => "writer",
+            Role::Logger => "logger",
+            Role::Default => "default",
+            Role::Unknown(name) => name,
+        }
+    }
+
+    /// True if the role can publish on the given topic
+    ///
+    pub fn can_publish(&self, topic: &str) -> bool {
+        match self {
+            Role::Platform | Role::Unknown(_) => true,
+            Role::Writer => !is_platform_topic(topic),
+            Role::Logger | Role::Default => false,
+        }
+    }
+
+    /// True if the role can subscribe to the given topic
+    ///
+    pub fn can_subscribe(&self, _topic: &str) -> bool {
+        match self {
+            Role::Platform | Role::Writer | Role::Logger | Role::Unknown(_) => true,
+            Role::Default => false,
+        }
+    }
+
+    /// True if the role allows the operation on the given topic
+    ///
+    pub fn allows(&self, access: Access, topic: &str) -> bool {
+        match access {
+            Access::Publish => self.can_publish(topic),
+            Access::Subscribe => self.can_subscribe(topic),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// True for the topics owned by the platform
+///
+fn is_platform_topic(topic: &str) -> bool {
+    Topic::parse(topic)
+        .map(|topic| topic.instance == PLATFORM_INSTANCE)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_from_common_name() {
+        assert_eq!(Role::from_common_name("writer.local"), Role::Writer);
+        assert_eq!(Role::from_common_name("logger.local"), Role::Logger);
+        assert_eq!(Role::from_common_name("reader.local"), Role::Logger);
+        assert_eq!(Role::from_common_name("default.local"), Role::Default);
+        assert_eq!(Role::from_common_name("platform.local"), Role::Platform);
+        assert_eq!(
+            Role::from_common_name("Panduza"),
+            Role::Unknown("Panduza".to_string())
+        );
+    }
+
+    #[test]
+    fn capabilities() {
+        let attribute = "pza/tester/boolean/rw/cmd";
+        let structure = "pza/_/structure/cmd";
+
+        assert!(Role::Writer.can_publish(attribute));
+        assert!(!Role::Writer.can_publish(structure));
+        assert!(Role::Writer.can_subscribe(structure));
+
+        assert!(!Role::Logger.can_publish(attribute));
+        assert!(Role::Logger.allows(Access::Subscribe, attribute));
+
+        assert!(!Role::Default.allows(Access::Publish, attribute));
+        assert!(!Role::Default.allows(Access::Subscribe, attribute));
+
+        assert!(Role::Platform.can_publish(structure));
+        // Unknown roles are checked by the router only
+        let admin = Role::from_common_name("admin.local");
+        assert!(admin.can_publish(structure));
+        assert!(admin.can_subscribe(attribute));
+    }
+}
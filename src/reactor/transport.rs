@@ -0,0 +1,102 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Link protocol used to reach the platform
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// QUIC with mutual TLS (default)
+    #[default]
+    Quic,
+    /// TCP with mutual TLS
+    Tls,
+    /// Plain TCP without encryption (local mock platform only)
+    Tcp,
+}
+
+impl Transport {
+    /// Zenoh locator prefix of this transport
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Quic => "quic",
+            Transport::Tls => "tls",
+            Transport::Tcp => "tcp",
+        }
+    }
+
+    /// True if the transport needs certificates
+    ///
+    pub fn is_secure(&self) -> bool {
+        !matches!(self, Transport::Tcp)
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Transport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "quic" => Ok(Transport::Quic),
+            "tls" => Ok(Transport::Tls),
+            "tcp" => Ok(Transport::Tcp),
+            _ => Err(format!(
+                "Unknown transport {:?} (expected quic, tls or tcp)",
+                s
+            )),
+        }
+    }
+}
+
+/// Zenoh session mode
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMode {
+    /// Connect to a router (default)
+    #[default]
+    Client,
+    /// Connect directly to other peers
+    Peer,
+}
+
+impl SessionMode {
+    /// Zenoh name of this mode
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionMode::Client => "client",
+            SessionMode::Peer => "peer",
+        }
+    }
+}
+
+impl fmt::Display for SessionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SessionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "client" => Ok(SessionMode::Client),
+            "peer" => Ok(SessionMode::Peer),
+            _ => Err(format!(
+                "Unknown session mode {:?} (expected client or peer)",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use super::inspect::inspect_pem;
+use super::inspect::matches_key;
+use super::inspect::read_pem_file;
+use super::inspect::InspectError;
+use super::key_protection::decode_key;
+use super::key_protection::encrypt_key;
+use super::key_protection::KeyProtectionError;
+use super::key_protection::ENCRYPTED_KEY_LABEL;
+use super::utils::panduza_dirs;
+use super::utils::write_panduza_file;
+use super::utils::PanduzaFileType;
+use p12_keystore::Certificate as P12Certificate;
+use p12_keystore::KeyStore;
+use p12_keystore::KeyStoreEntry;
+use p12_keystore::PrivateKeyChain;
+use pkcs8::der::pem::LineEnding;
+use rcgen::KeyPair;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use thiserror::Error;
+use x509_parser::pem::Pem;
+
+/// PEM label of a certificate
+///
+const CERTIFICATE_LABEL: &str = "CERTIFICATE";
+
+/// PEM label of a plain PKCS#8 key
+///
+const KEY_LABEL: &str = "PRIVATE KEY";
+
+/// File name of the root CA in the user certificate directory
+///
+const ROOT_CA_CERTIFICATE: &str = "root_ca_certificate.pem";
+
+/// Name of the default credentials used by `ReactorBuilder`
+///
+const DEFAULT_IDENTITY: &str = "client";
+
+/// Error while importing or exporting a client identity
+///
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error(transparent)]
+    Inspect(#[from] InspectError),
+
+    #[error(transparent)]
+    KeyProtection(#[from] KeyProtectionError),
+
+    #[error("Invalid PEM bundle: {0}")]
+    Pem(String),
+
+    #[error("Invalid PKCS#12 bundle: {0}")]
+    Pkcs12(String),
+
+    #[error("Bundle has no private key")]
+    MissingKey,
+
+    #[error("Bundle has no certificate matching its private key")]
+    MissingCertificate,
+
+    #[error("Certificate does not match the private key")]
+    KeyMismatch,
+
+    #[error("Cannot write {path:?} because {cause}")]
+    Io { path: String, cause: String },
+
+    #[error("A different root CA is already installed at {path:?}")]
+    RootCaMismatch { path: String },
+}
+
+/// Options of [Identity::install]
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallOptions {
+    /// Install the root CA of the chain for an identity other than `client`
+    pub root_ca: bool,
+    /// Replace a different root CA already installed
+    pub force: bool,
+}
+
+/// Paths written by [Identity::install]
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledIdentity {
+    /// Root CA certificate, `None` when the bundle has no CA
+    pub ca_certificate: Option<PathBuf>,
+    /// Client certificate
+    pub certificate: PathBuf,
+    /// Client private key
+    pub private_key: PathBuf,
+}
+
+/// Client identity: a certificate, its private key and the CA chain
+///
+/// The key is kept in memory as plain PEM and only encrypted when it is
+/// written. `Debug` does not print it.
+///
+#[derive(Clone)]
+pub struct Identity {
+    certificate: String,
+    private_key: String,
+    chain: Vec<String>,
+}
+
+impl Identity {
+    /// Identity from PEM contents, the key may be encrypted
+    ///
+    /// # Arguments
+    /// * `certificate` - Client certificate
+    /// * `private_key` - Private key of the certificate
+    /// * `chain` - CA certificates, the root CA last
+    /// * `passphrase` - Passphrase of the key, when it is encrypted
+    pub fn new(
+        certificate: String,
+        private_key: &str,
+        chain: Vec<String>,
+        passphrase: Option<&str>,
+    ) -> Result<Self, BundleError> {
+        let key = decode_key(private_key, passphrase)?;
+        if !matches_key(&certificate, &key)? {
+            return Err(BundleError::KeyMismatch);
+        }
+        Ok(Self {
+            certificate,
+            private_key: key.serialize_pem(),
+            chain,
+        })
+    }
+
+    /// Identity from PEM files
+    ///
+    pub fn from_files<C: AsRef<Path>, K: AsRef<Path>>(
+        certificate: C,
+        private_key: K,
+        ca_certificate: Option<&Path>,
+        passphrase: Option<&str>,
+    ) -> Result<Self, BundleError> {
+        let chain = match ca_certificate {
+            Some(path) => split_certificates(&read_pem_file(path)?)?,
+            None => Vec::new(),
+        };
+        Self::new(
+            read_pem_file(certificate)?,
+            &read_pem_file(private_key)?,
+            chain,
+            passphrase,
+        )
+    }
+
+    /// Identity stored in the user `.panduza` directories
+    ///
+    /// Reads `{name}_certificate.pem`, `{name}_private_key.pem` and the root CA
+    /// when it exists. The name `client` gives the default credentials.
+    pub fn from_panduza(name: &str, passphrase: Option<&str>) -> Result<Self, BundleError> {
+        let (keys_dir, cert_dir) = panduza_dirs().ok_or_else(|| BundleError::Io {
+            path: ".panduza".to_string(),
+            cause: "Unable to find user folder".to_string(),
+        })?;
+        let ca_path = cert_dir.join(ROOT_CA_CERTIFICATE);
+        Self::from_files(
+            cert_dir.join(format!("{}_certificate.pem", name)),
+            keys_dir.join(format!("{}_private_key.pem", name)),
+            ca_path.exists().then_some(ca_path.as_path()),
+            passphrase,
+        )
+    }
+
+    /// PEM content of the client certificate
+    ///
+    pub fn certificate(&self) -> &str {
+        &self.certificate
+    }
+
+    /// Plain PEM content of the private key
+    ///
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+
+    /// PEM contents of the CA certificates, the root CA last
+    ///
+    pub fn chain(&self) -> &[String] {
+        &self.chain
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Concatenated PEM bundle: certificate, CA chain, then the private key
+    ///
+    /// # Arguments
+    /// * `passphrase` - Encrypt the key of the bundle (PKCS#8) when given
+    pub fn to_pem_bundle(&self, passphrase: Option<&str>) -> Result<String, BundleError> {
+        let key = match passphrase {
+            Some(passphrase) => encrypt_key(&self.key_pair()?, passphrase)?,
+            None => self.private_key.clone(),
+        };
+        let mut bundle = String::new();
+        for pem in std::iter::once(&self.certificate)
+            .chain(&self.chain)
+            .chain(std::iter::once(&key))
+        {
+            bundle.push_str(pem.trim_end());
+            bundle.push('\n');
+        }
+        Ok(bundle)
+    }
+
+    /// Identity from a concatenated PEM bundle
+    ///
+    /// The certificate matching the key is the client certificate, the other
+    /// ones form the CA chain in their order of appearance.
+    pub fn from_pem_bundle(content: &str, passphrase: Option<&str>) -> Result<Self, BundleError> {
+        let mut certificates = Vec::new();
+        let mut private_key = None;
+        for block in Pem::iter_from_buffer(content.as_bytes()) {
+            let block = block.map_err(|e| BundleError::Pem(e.to_string()))?;
+            match block.label.as_str() {
+                CERTIFICATE_LABEL => {
+                    certificates.push(encode_pem(CERTIFICATE_LABEL, &block.contents)?)
+                }
+                KEY_LABEL | ENCRYPTED_KEY_LABEL => {
+                    if private_key.is_some() {
+                        return Err(BundleError::Pem("more than one private key".to_string()));
+                    }
+                    private_key = Some(encode_pem(&block.label, &block.contents)?);
+                }
+                other => {
+                    return Err(BundleError::Pem(format!("unsupported block {:?}", other)));
+                }
+            }
+        }
+
+        let private_key = private_key.ok_or(BundleError::MissingKey)?;
+        let key = decode_key(&private_key, passphrase)?;
+        Self::from_parts(certificates, key)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// PKCS#12 (`.p12`/`.pfx`) bundle protected by a password
+    ///
+    /// The entry is named after the certificate common name and holds the
+    /// certificate followed by the CA chain.
+    pub fn to_pkcs12(&self, password: &str) -> Result<Vec<u8>, BundleError> {
+        let mut chain = Vec::new();
+        for pem in std::iter::once(&self.certificate).chain(&self.chain) {
+            chain.push(
+                P12Certificate::from_der(&decode_pem(pem)?.contents)
+                    .map_err(|e| BundleError::Pkcs12(e.to_string()))?,
+            );
+        }
+
+        let leaf = decode_pem(&self.certificate)?;
+        let local_key_id = leaf
+            .parse_x509()
+            .map_err(|e| InspectError::X509(e.to_string()))?
+            .raw_serial()
+            .to_vec();
+        let alias = inspect_pem(&self.certificate)?
+            .common_name
+            .unwrap_or_else(|| "client".to_string());
+
+        let key_chain = PrivateKeyChain::new(self.key_pair()?.serialize_der(), local_key_id, chain);
+        let mut store = KeyStore::new();
+        store.add_entry(&alias, KeyStoreEntry::PrivateKeyChain(key_chain));
+        store
+            .writer(password)
+            .write()
+            .map_err(|e| BundleError::Pkcs12(e.to_string()))
+    }
+
+    /// Identity from a PKCS#12 bundle
+    ///
+    /// Uses the first key entry, standalone certificates of the bundle are
+    /// added to the CA chain.
+    pub fn from_pkcs12(data: &[u8], password: &str) -> Result<Self, BundleError> {
+        let store = KeyStore::from_pkcs12(data, password)
+            .map_err(|e| BundleError::Pkcs12(e.to_string()))?;
+        let (_, key_chain) = store.private_key_chain().ok_or(BundleError::MissingKey)?;
+
+        let mut certificates = Vec::new();
+        for certificate in key_chain.chain() {
+            certificates.push(encode_pem(CERTIFICATE_LABEL, certificate.as_der())?);
+        }
+        for (_, entry) in store.entries() {
+            if let KeyStoreEntry::Certificate(certificate) = entry {
+                let pem = encode_pem(CERTIFICATE_LABEL, certificate.as_der())?;
+                if !certificates.contains(&pem) {
+                    certificates.push(pem);
+                }
+            }
+        }
+
+        let key = KeyPair::from_pem(&encode_pem(KEY_LABEL, key_chain.key())?)
+            .map_err(|e| KeyProtectionError::Key(e.to_string()))?;
+        Self::from_parts(certificates, key)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Write the identity in the user `.panduza` directories
+    ///
+    /// The certificate and key go to `{name}_certificate.pem` and
+    /// `{name}_private_key.pem`. The name `client` installs the default
+    /// credentials used by `ReactorBuilder`, along with the last certificate of
+    /// the chain as root CA; other names only install it with
+    /// [InstallOptions::root_ca]. A different root CA already installed is only
+    /// replaced with [InstallOptions::force], nothing is written otherwise.
+    ///
+    /// # Arguments
+    /// * `name` - Base name of the written files
+    /// * `passphrase` - Store the key encrypted (PKCS#8) when given
+    /// * `options` - Root CA handling
+    pub fn install(
+        &self,
+        name: &str,
+        passphrase: Option<&str>,
+        options: InstallOptions,
+    ) -> Result<InstalledIdentity, BundleError> {
+        let root_ca = self
+            .chain
+            .last()
+            .filter(|_| name == DEFAULT_IDENTITY || options.root_ca);
+        if let (Some(ca), Some((_, cert_dir))) = (root_ca, panduza_dirs()) {
+            check_root_ca(&cert_dir.join(ROOT_CA_CERTIFICATE), ca, options.force)?;
+        }
+
+        let key = match passphrase {
+            Some(passphrase) => encrypt_key(&self.key_pair()?, passphrase)?,
+            None => self.private_key.clone(),
+        };
+        let write = |file_type, filename: String, content: &str| {
+            write_panduza_file(file_type, &filename, content).map_err(|e| BundleError::Io {
+                path: filename,
+                cause: e.to_string(),
+            })
+        };
+
+        let ca_certificate = match root_ca {
+            Some(ca) => Some(write(
+                PanduzaFileType::Certificate,
+                ROOT_CA_CERTIFICATE.to_string(),
+                ca,
+            )?),
+            None => None,
+        };
+        let certificate = write(
+            PanduzaFileType::Certificate,
+            format!("{}_certificate.pem", name),
+            &self.certificate,
+        )?;
+        let private_key = write(
+            PanduzaFileType::Key,
+            format!("{}_private_key.pem", name),
+            &key,
+        )?;
+
+        Ok(InstalledIdentity {
+            ca_certificate,
+            certificate,
+            private_key,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Split certificates between the one matching the key and the chain
+    ///
+    fn from_parts(certificates: Vec<String>, key: KeyPair) -> Result<Self, BundleError> {
+        let mut certificate = None;
+        let mut chain = Vec::new();
+        for pem in certificates {
+            if certificate.is_none() && matches_key(&pem, &key)? {
+                certificate = Some(pem);
+            } else {
+                chain.push(pem);
+            }
+        }
+        Ok(Self {
+            certificate: certificate.ok_or(BundleError::MissingCertificate)?,
+            private_key: key.serialize_pem(),
+            chain,
+        })
+    }
+
+    /// Key pair of the identity
+    ///
+    fn key_pair(&self) -> Result<KeyPair, BundleError> {
+        KeyPair::from_pem(&self.private_key)
+            .map_err(|e| KeyProtectionError::Key(e.to_string()).into())
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity")
+            .field("certificate", &self.certificate)
+            .field("private_key", &"<redacted>")
+            .field("chain", &self.chain)
+            .finish()
+    }
+}
+
+/// Refuse to replace an installed root CA by a different one, unless forced
+///
+fn check_root_ca(path: &Path, ca: &str, force: bool) -> Result<(), BundleError> {
+    if force || !path.exists() {
+        return Ok(());
+    }
+    if decode_pem(&read_pem_file(path)?)?.contents != decode_pem(ca)?.contents {
+        return Err(BundleError::RootCaMismatch {
+            path: path.display().to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Split a PEM file holding several certificates
+///
+fn split_certificates(content: &str) -> Result<Vec<String>, BundleError> {
+    let mut certificates = Vec::new();
+    for block in Pem::iter_from_buffer(content.as_bytes()) {
+        let block = block.map_err(|e| BundleError::Pem(e.to_string()))?;
+        if block.label == CERTIFICATE_LABEL {
+            certificates.push(encode_pem(CERTIFICATE_LABEL, &block.contents)?);
+        }
+    }
+    Ok(certificates)
+}
+
+/// First PEM block of a content
+///
+fn decode_pem(content: &str) -> Result<Pem, BundleError> {
+    Pem::iter_from_buffer(content.as_bytes())
+        .next()
+        .ok_or_else(|| BundleError::Pem("no PEM block".to_string()))?
+        .map_err(|e| BundleError::Pem(e.to_string()))
+}
+
+/// PEM text of a DER content
+///
+fn encode_pem(label: &str, der: &[u8]) -> Result<String, BundleError> {
+    pkcs8::der::pem::encode_string(label, LineEnding::LF, der)
+        .map_err(|e| BundleError::Pem(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::certificate::generate_cert_client_with_san;
+    use crate::security::certificate::generate_root_ca;
+    use crate::security::certificate::CertParams;
+    use crate::security::inspect::verify_chain;
+
+    fn identity() -> (Identity, String) {
+        let (ca, ca_key) = generate_root_ca();
+        let (cert, key) = generate_cert_client_with_san(
+            &ca,
+            &ca_key,
+            CertParams {
+                san: vec!["localhost".into()],
+                validity_days: 30,
+                common_name: "writer.local".into(),
+            },
+        );
+        let identity = Identity::new(cert.pem(), &key.serialize_pem(), vec![ca.pem()], None);
+        (identity.unwrap(), ca.pem())
+    }
+
+    #[test]
+    fn pem_bundle_round_trip() {
+        let (identity, ca_pem) = identity();
+        assert!(!format!("{:?}", identity).contains(KEY_LABEL));
+
+        let bundle = identity.to_pem_bundle(None).unwrap();
+        let imported = Identity::from_pem_bundle(&bundle, None).unwrap();
+        assert_eq!(imported.certificate(), identity.certificate());
+        assert_eq!(imported.private_key(), identity.private_key());
+        assert_eq!(imported.chain(), std::slice::from_ref(&ca_pem));
+        assert!(verify_chain(imported.certificate(), &imported.chain()[0]).is_ok());
+
+        // Order of the blocks does not matter, the key selects the certificate
+        let reordered = format!(
+            "{}{}{}",
+            ca_pem,
+            identity.private_key(),
+            identity.certificate()
+        );
+        let imported = Identity::from_pem_bundle(&reordered, None).unwrap();
+        assert_eq!(imported.certificate(), identity.certificate());
+
+        assert!(matches!(
+            Identity::from_pem_bundle(&ca_pem, None),
+            Err(BundleError::MissingKey)
+        ));
+        let foreign = format!("{}{}", ca_pem, identity.private_key());
+        assert!(matches!(
+            Identity::from_pem_bundle(&foreign, None),
+            Err(BundleError::MissingCertificate)
+        ));
+    }
+
+    #[test]
+    fn pkcs12_round_trip() {
+        let (identity, ca_pem) = identity();
+
+        let p12 = identity.to_pkcs12("changeit").unwrap();
+        assert!(Identity::from_pkcs12(&p12, "wrong").is_err());
+
+        let imported = Identity::from_pkcs12(&p12, "changeit").unwrap();
+        assert_eq!(imported.certificate(), identity.certificate());
+        assert_eq!(imported.private_key(), identity.private_key());
+        assert_eq!(imported.chain(), [ca_pem]);
+    }
+
+    #[test]
+    fn installed_root_ca_is_kept() {
+        let (_, ca_pem) = identity();
+        let (_, other_pem) = identity();
+        let dir = std::env::temp_dir().join(format!("pza-bundle-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(ROOT_CA_CERTIFICATE);
+
+        // Nothing installed yet, then the same CA
+        assert!(check_root_ca(&path, &ca_pem, false).is_ok());
+        std::fs::write(&path, &ca_pem).unwrap();
+        assert!(check_root_ca(&path, &ca_pem, false).is_ok());
+
+        assert!(matches!(
+            check_root_ca(&path, &other_pem, false),
+            Err(BundleError::RootCaMismatch { .. })
+        ));
+        assert!(check_root_ca(&path, &other_pem, true).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
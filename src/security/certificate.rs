@@ -2,7 +2,7 @@ use super::utils::{load_key_from_pem, validity_period};
 use rcgen::{
     BasicConstraints, Certificate, CertificateParams, CertificateSigningRequest,
     CertificateSigningRequestParams, DnType, DnValue::PrintableString, ExtendedKeyUsagePurpose,
-    IsCa, KeyPair, KeyUsagePurpose,
+    IsCa, KeyPair, KeyUsagePurpose, SerialNumber, SubjectPublicKeyInfo,
 };
 
 const CA_VALIDITY_DAYS: i32 = 365000;
@@ -204,3 +204,84 @@ pub fn sign_csr_with_ca(
 
     Ok(cert)
 }
+
+// ------ RENEWAL ------
+
+/// Re-issue a certificate with a new validity period, keeping its subject, SANs,
+/// key usages and public key (the private key of the holder stays valid)
+pub fn renew_certificate(
+    cert_pem: &str,
+    ca_cert: &Certificate,
+    ca_key: &KeyPair,
+    validity_days: i32,
+) -> Result<Certificate, rcgen::Error> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(cert_pem.as_bytes())
+        .map_err(|_| rcgen::Error::CouldNotParseCertificate)?;
+    let x509 = pem
+        .parse_x509()
+        .map_err(|_| rcgen::Error::CouldNotParseCertificate)?;
+    let public_key = SubjectPublicKeyInfo::from_der(x509.public_key().raw)?;
+
+    let mut params = CertificateParams::from_ca_cert_pem(cert_pem)?;
+    let (yesterday, tomorrow) = validity_period(validity_days);
+    params.not_before = yesterday;
+    params.not_after = tomorrow;
+    // A fresh serial: rcgen would otherwise derive the same one from the same key
+    let mut serial: [u8; 16] = rand::random();
+    serial[0] &= 0x7f;
+    params.serial_number = Some(SerialNumber::from_slice(&serial));
+    params.use_authority_key_identifier_extension = true;
+
+    params.signed_by(&public_key, ca_cert, ca_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::inspect::inspect_pem;
+    use crate::security::inspect::verify_chain;
+
+    #[test]
+    fn renewal_keeps_identity_and_key() {
+        let (ca, ca_key) = generate_root_ca();
+        let (cert, _) = generate_cert_client_with_san(
+            &ca,
+            &ca_key,
+            CertParams {
+                san: vec!["localhost".into()],
+                validity_days: 10,
+                common_name: "logger.local".into(),
+            },
+        );
+
+        let renewed = renew_certificate(&cert.pem(), &ca, &ca_key, 400).unwrap();
+        let before = inspect_pem(&cert.pem()).unwrap();
+        let after = verify_chain(&renewed.pem(), &ca.pem()).unwrap();
+
+        assert_eq!(after.common_name, before.common_name);
+        assert_eq!(after.san, before.san);
+        assert_eq!(after.extended_key_usages, before.extended_key_usages);
+        assert_ne!(after.serial, before.serial);
+        assert!(after.not_after > before.not_after);
+        assert_eq!(
+            renewed.params().distinguished_name,
+            cert.params().distinguished_name
+        );
+        assert_eq!(
+            x509_parser::pem::parse_x509_pem(renewed.pem().as_bytes())
+                .unwrap()
+                .1
+                .parse_x509()
+                .unwrap()
+                .public_key()
+                .raw,
+            x509_parser::pem::parse_x509_pem(cert.pem().as_bytes())
+                .unwrap()
+                .1
+                .parse_x509()
+                .unwrap()
+                .public_key()
+                .raw
+        );
+    }
+}
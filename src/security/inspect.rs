@@ -0,0 +1,405 @@
+use super::revocation::parse_crl;
+use rcgen::KeyPair;
+use std::net::IpAddr;
+use std::path::Path;
+use thiserror::Error;
+use time::Duration;
+use time::OffsetDateTime;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::pem::Pem;
+
+/// Error while reading a certificate
+///
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InspectError {
+    #[error("Cannot read certificate file {path:?} because {cause}")]
+    Io { path: String, cause: String },
+
+    #[error("Invalid PEM content: {0}")]
+    Pem(String),
+
+    #[error("Invalid X.509 certificate: {0}")]
+    X509(String),
+}
+
+/// Reason why a certificate cannot be used with a given root CA
+///
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    #[error(transparent)]
+    Inspect(#[from] InspectError),
+
+    #[error("Certificate {subject:?} expired on {not_after}")]
+    Expired {
+        subject: String,
+        not_after: OffsetDateTime,
+    },
+
+    #[error("Certificate {subject:?} is not valid before {not_before}")]
+    NotYetValid {
+        subject: String,
+        not_before: OffsetDateTime,
+    },
+
+    #[error("Certificate {ca_subject:?} is not a certificate authority")]
+    NotCa { ca_subject: String },
+
+    #[error("Certificate {subject:?} is issued by {issuer:?}, not by the CA {ca_subject:?}")]
+    IssuerMismatch {
+        subject: String,
+        issuer: String,
+        ca_subject: String,
+    },
+
+    #[error("Certificate {subject:?} is not signed by the CA {ca_subject:?}: {cause}")]
+    BadSignature {
+        subject: String,
+        ca_subject: String,
+        cause: String,
+    },
+
+    #[error("Certificate {subject:?} (serial {serial}) was revoked on {revoked_at}")]
+    Revoked {
+        subject: String,
+        serial: String,
+        revoked_at: OffsetDateTime,
+    },
+
+    #[error("Cannot check revocation: {0}")]
+    Crl(String),
+}
+
+/// Human readable content of a certificate
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateSummary {
+    /// Common name of the subject (e.g. `writer.local`)
+    pub common_name: Option<String>,
+    /// Full subject distinguished name
+    pub subject: String,
+    /// Full issuer distinguished name
+    pub issuer: String,
+    /// Serial number (hexadecimal, `:` separated)
+    pub serial: String,
+    /// Subject alternative names (DNS names and IP addresses)
+    pub san: Vec<String>,
+    /// Start of the validity window
+    pub not_before: OffsetDateTime,
+    /// End of the validity window
+    pub not_after: OffsetDateTime,
+    /// True for a certificate authority
+    pub is_ca: bool,
+    /// Key usages (e.g. `Digital Signature`)
+    pub key_usages: Vec<String>,
+    /// Extended key usages (e.g. `clientAuth`)
+    pub extended_key_usages: Vec<String>,
+}
+
+impl CertificateSummary {
+    /// Role carried by the common name: `writer.local` gives `writer`
+    ///
+    pub fn role(&self) -> Option<&str> {
+        self.common_name
+            .as_deref()
+            .and_then(|cn| cn.split('.').next())
+            .filter(|role| !role.is_empty())
+    }
+
+    /// True if the certificate is valid right now
+    ///
+    pub fn is_valid_now(&self) -> bool {
+        let now = OffsetDateTime::now_utc();
+        self.not_before <= now && now <= self.not_after
+    }
+
+    /// True if the validity window is over
+    ///
+    pub fn is_expired(&self) -> bool {
+        OffsetDateTime::now_utc() > self.not_after
+    }
+
+    /// Remaining validity (negative once expired)
+    ///
+    pub fn expires_in(&self) -> Duration {
+        self.not_after - OffsetDateTime::now_utc()
+    }
+
+    /// True if the certificate expires within the given delay
+    ///
+    pub fn expires_within(&self, delay: Duration) -> bool {
+        self.expires_in() <= delay
+    }
+}
+
+/// Decode the first certificate of a PEM string
+///
+fn decode_pem(pem: &str) -> Result<Pem, InspectError> {
+    let (_, pem) = parse_x509_pem(pem.as_bytes()).map_err(|e| InspectError::Pem(e.to_string()))?;
+    Ok(pem)
+}
+
+/// Parse a decoded PEM into a certificate
+///
+fn parse_certificate(pem: &Pem) -> Result<X509Certificate<'_>, InspectError> {
+    pem.parse_x509()
+        .map_err(|e| InspectError::X509(e.to_string()))
+}
+
+/// Build the summary of a parsed certificate
+///
+fn summarize(cert: &X509Certificate<'_>) -> CertificateSummary {
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string());
+
+    let san = match cert.subject_alternative_name() {
+        Ok(Some(ext)) => ext
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => Some(IpAddr::from(<[u8; 4]>::try_from(*bytes).ok()?).to_string()),
+                    16 => Some(IpAddr::from(<[u8; 16]>::try_from(*bytes).ok()?).to_string()),
+                    _ => None,
+                },
+                GeneralName::URI(uri) => Some(uri.to_string()),
+                GeneralName::RFC822Name(mail) => Some(mail.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let key_usages = match cert.key_usage() {
+        Ok(Some(ext)) => ext
+            .value
+            .to_string()
+            .split(", ")
+            .filter(|usage| !usage.is_empty())
+            .map(|usage| usage.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let extended_key_usages = match cert.extended_key_usage() {
+        Ok(Some(ext)) => {
+            let eku = ext.value;
+            [
+                (eku.any, "any"),
+                (eku.server_auth, "serverAuth"),
+                (eku.client_auth, "clientAuth"),
+                (eku.code_signing, "codeSigning"),
+                (eku.email_protection, "emailProtection"),
+                (eku.time_stamping, "timeStamping"),
+                (eku.ocsp_signing, "ocspSigning"),
+            ]
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| name.to_string())
+            .chain(eku.other.iter().map(|oid| oid.to_id_string()))
+            .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    CertificateSummary {
+        common_name,
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: cert.raw_serial_as_string(),
+        san,
+        not_before: cert.validity().not_before.to_datetime(),
+        not_after: cert.validity().not_after.to_datetime(),
+        is_ca: cert.is_ca(),
+        key_usages,
+        extended_key_usages,
+    }
+}
+
+/// Read a PEM file
+///
+pub fn read_pem_file<P: AsRef<Path>>(path: P) -> Result<String, InspectError> {
+    let path = path.as_ref();
+    std::fs::read_to_string(path).map_err(|e| InspectError::Io {
+        path: path.display().to_string(),
+        cause: e.to_string(),
+    })
+}
+
+/// Summarize a PEM encoded certificate
+///
+pub fn inspect_pem(pem: &str) -> Result<CertificateSummary, InspectError> {
+    let pem = decode_pem(pem)?;
+    let cert = parse_certificate(&pem)?;
+    Ok(summarize(&cert))
+}
+
+/// Summarize a PEM certificate file
+///
+pub fn inspect_file<P: AsRef<Path>>(path: P) -> Result<CertificateSummary, InspectError> {
+    inspect_pem(&read_pem_file(path)?)
+}
+
+/// True if the certificate carries the public key of the given key pair
+///
+pub fn matches_key(cert_pem: &str, key: &KeyPair) -> Result<bool, InspectError> {
+    let pem = decode_pem(cert_pem)?;
+    let cert = parse_certificate(&pem)?;
+    Ok(cert.public_key().raw == key.public_key_der().as_slice())
+}
+
+/// Check that a certificate is currently valid and signed by the given root CA
+///
+pub fn verify_chain(cert_pem: &str, ca_pem: &str) -> Result<CertificateSummary, ChainError> {
+    let cert_pem = decode_pem(cert_pem)?;
+    let cert = parse_certificate(&cert_pem)?;
+    let ca_pem = decode_pem(ca_pem)?;
+    let ca = parse_certificate(&ca_pem)?;
+
+    let summary = summarize(&cert);
+    let ca_subject = ca.subject().to_string();
+
+    let now = OffsetDateTime::now_utc();
+    if now > summary.not_after {
+        return Err(ChainError::Expired {
+            subject: summary.subject,
+            not_after: summary.not_after,
+        });
+    }
+    if now < summary.not_before {
+        return Err(ChainError::NotYetValid {
+            subject: summary.subject,
+            not_before: summary.not_before,
+        });
+    }
+    if !ca.is_ca() {
+        return Err(ChainError::NotCa { ca_subject });
+    }
+    if cert.issuer() != ca.subject() {
+        return Err(ChainError::IssuerMismatch {
+            subject: summary.subject,
+            issuer: summary.issuer,
+            ca_subject,
+        });
+    }
+    cert.verify_signature(Some(ca.public_key()))
+        .map_err(|e| ChainError::BadSignature {
+            subject: summary.subject.clone(),
+            ca_subject,
+            cause: e.to_string(),
+        })?;
+
+    Ok(summary)
+}
+
+/// Same as [verify_chain], also checking that the certificate is not in the
+/// CRL signed by the root CA
+///
+pub fn verify_chain_with_crl(
+    cert_pem: &str,
+    ca_pem: &str,
+    crl_pem: &str,
+) -> Result<CertificateSummary, ChainError> {
+    let summary = verify_chain(cert_pem, ca_pem)?;
+    let crl = parse_crl(crl_pem, ca_pem).map_err(|e| ChainError::Crl(e.to_string()))?;
+    if let Some(entry) = crl.find_certificate(&summary) {
+        return Err(ChainError::Revoked {
+            subject: summary.subject,
+            serial: summary.serial,
+            revoked_at: entry.revoked_at,
+        });
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::certificate::generate_cert_client_with_san;
+    use crate::security::certificate::generate_root_ca;
+    use crate::security::certificate::CertParams;
+    use crate::security::revocation::revoke_serial;
+
+    fn client_params(validity_days: i32) -> CertParams {
+        CertParams {
+            san: vec!["localhost".into(), "127.0.0.1".into()],
+            validity_days,
+            common_name: "writer.local".into(),
+        }
+    }
+
+    #[test]
+    fn summary_of_client_certificate() {
+        let (ca, ca_key) = generate_root_ca();
+        let (cert, _) = generate_cert_client_with_san(&ca, &ca_key, client_params(30));
+
+        let summary = inspect_pem(&cert.pem()).unwrap();
+        assert_eq!(summary.common_name.as_deref(), Some("writer.local"));
+        assert_eq!(summary.role(), Some("writer"));
+        assert_eq!(summary.san, vec!["localhost", "127.0.0.1"]);
+        assert!(summary.issuer.contains("Panduza Root CA"));
+        assert!(!summary.is_ca);
+        assert!(summary
+            .key_usages
+            .contains(&"Digital Signature".to_string()));
+        assert_eq!(summary.extended_key_usages, vec!["clientAuth"]);
+        assert!(summary.is_valid_now());
+        assert!(summary.expires_within(Duration::days(31)));
+        assert!(!summary.expires_within(Duration::days(29)));
+
+        let ca_summary = inspect_pem(&ca.pem()).unwrap();
+        assert!(ca_summary.is_ca);
+
+        assert!(matches!(inspect_pem("garbage"), Err(InspectError::Pem(_))));
+    }
+
+    #[test]
+    fn chain_validation() {
+        let (ca, ca_key) = generate_root_ca();
+        let (cert, _) = generate_cert_client_with_san(&ca, &ca_key, client_params(30));
+        assert!(verify_chain(&cert.pem(), &ca.pem()).is_ok());
+
+        // Same subject, different key
+        let (other_ca, _) = generate_root_ca();
+        assert!(matches!(
+            verify_chain(&cert.pem(), &other_ca.pem()),
+            Err(ChainError::BadSignature { .. })
+        ));
+
+        // Not a CA
+        assert!(matches!(
+            verify_chain(&cert.pem(), &cert.pem()),
+            Err(ChainError::NotCa { .. })
+        ));
+
+        // Revoked by the CA
+        let crl = revoke_serial(
+            None,
+            &inspect_pem(&cert.pem()).unwrap().serial,
+            None,
+            &ca,
+            &ca_key,
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_chain_with_crl(&cert.pem(), &ca.pem(), &crl),
+            Err(ChainError::Revoked { .. })
+        ));
+        let (other, _) = generate_cert_client_with_san(&ca, &ca_key, client_params(30));
+        assert!(verify_chain_with_crl(&other.pem(), &ca.pem(), &crl).is_ok());
+
+        // Validity window ended yesterday
+        let (expired, _) = generate_cert_client_with_san(&ca, &ca_key, client_params(-2));
+        assert!(matches!(
+            verify_chain(&expired.pem(), &ca.pem()),
+            Err(ChainError::Expired { .. })
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use super::inspect::read_pem_file;
+use super::inspect::InspectError;
+use pkcs8::der::pem::LineEnding;
+use pkcs8::EncryptedPrivateKeyInfo;
+use pkcs8::PrivateKeyInfo;
+use pkcs8::SecretDocument;
+use rand::rngs::OsRng;
+use rcgen::KeyPair;
+use std::path::Path;
+use thiserror::Error;
+
+/// PEM label of a passphrase-encrypted PKCS#8 key
+///
+pub const ENCRYPTED_KEY_LABEL: &str = "ENCRYPTED PRIVATE KEY";
+
+/// PEM label of a plain PKCS#8 key
+///
+const KEY_LABEL: &str = "PRIVATE KEY";
+
+/// Error while protecting or opening a private key
+///
+#[derive(Error, Debug)]
+pub enum KeyProtectionError {
+    #[error(transparent)]
+    Read(#[from] InspectError),
+
+    #[error("Invalid private key: {0}")]
+    Key(String),
+
+    #[error("Private key is encrypted, a passphrase is required")]
+    PassphraseRequired,
+
+    #[error("Cannot decrypt private key (wrong passphrase?)")]
+    Decrypt,
+
+    #[error("Failed to encrypt private key: {0}")]
+    Encrypt(String),
+}
+
+/// True if the PEM content is a passphrase-encrypted key
+///
+pub fn is_encrypted_pem(pem: &str) -> bool {
+    pem.contains(&format!("-----BEGIN {}-----", ENCRYPTED_KEY_LABEL))
+}
+
+/// Encrypt a key with a passphrase (PKCS#8, scrypt and AES-256-CBC)
+///
+/// # Returns
+/// The `ENCRYPTED PRIVATE KEY` PEM content
+pub fn encrypt_key(key: &KeyPair, passphrase: &str) -> Result<String, KeyProtectionError> {
+    let der = key.serialize_der();
+    let info = PrivateKeyInfo::try_from(der.as_slice())
+        .map_err(|e| KeyProtectionError::Key(e.to_string()))?;
+    let document = info
+        .encrypt(OsRng, passphrase.as_bytes())
+        .map_err(|e| KeyProtectionError::Encrypt(e.to_string()))?;
+    let pem = document
+        .to_pem(ENCRYPTED_KEY_LABEL, LineEnding::LF)
+        .map_err(|e| KeyProtectionError::Encrypt(e.to_string()))?;
+    Ok(pem.to_string())
+}
+
+/// Decrypt an encrypted key
+///
+/// # Returns
+/// The plain `PRIVATE KEY` PEM content
+pub fn decrypt_key_pem(pem: &str, passphrase: &str) -> Result<String, KeyProtectionError> {
+    let (label, document) =
+        SecretDocument::from_pem(pem).map_err(|e| KeyProtectionError::Key(e.to_string()))?;
+    if label != ENCRYPTED_KEY_LABEL {
+        return Err(KeyProtectionError::Key(format!(
+            "expected {}, found {}",
+            ENCRYPTED_KEY_LABEL, label
+        )));
+    }
+    let info = EncryptedPrivateKeyInfo::try_from(document.as_bytes())
+        .map_err(|e| KeyProtectionError::Key(e.to_string()))?;
+    let plain = info
+        .decrypt(passphrase.as_bytes())
+        .map_err(|_| KeyProtectionError::Decrypt)?;
+    let pem = plain
+        .to_pem(KEY_LABEL, LineEnding::LF)
+        .map_err(|e| KeyProtectionError::Key(e.to_string()))?;
+    Ok(pem.to_string())
+}
+
+/// Plain PEM content of a key that may be encrypted
+///
+pub fn open_key_pem(pem: &str, passphrase: Option<&str>) -> Result<String, KeyProtectionError> {
+    match (is_encrypted_pem(pem), passphrase) {
+        (false, _) => Ok(pem.to_string()),
+        (true, Some(passphrase)) => decrypt_key_pem(pem, passphrase),
+        (true, None) => Err(KeyProtectionError::PassphraseRequired),
+    }
+}
+
+/// Load a key that may be encrypted
+///
+pub fn decode_key(pem: &str, passphrase: Option<&str>) -> Result<KeyPair, KeyProtectionError> {
+    KeyPair::from_pem(&open_key_pem(pem, passphrase)?)
+        .map_err(|e| KeyProtectionError::Key(e.to_string()))
+}
+
+/// Load a key file that may be encrypted
+///
+pub fn load_key_file<P: AsRef<Path>>(
+    path: P,
+    passphrase: Option<&str>,
+) -> Result<KeyPair, KeyProtectionError> {
+    decode_key(&read_pem_file(path)?, passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::utils::generate_key;
+
+    #[test]
+    fn encrypted_key_round_trip() {
+        let key = generate_key().unwrap();
+        let plain = key.serialize_pem();
+        assert!(!is_encrypted_pem(&plain));
+        assert_eq!(open_key_pem(&plain, None).unwrap(), plain);
+
+        let encrypted = encrypt_key(&key, "correct horse").unwrap();
+        assert!(is_encrypted_pem(&encrypted));
+        assert!(!encrypted.contains(&plain));
+
+        let opened = decode_key(&encrypted, Some("correct horse")).unwrap();
+        assert_eq!(opened.public_key_der(), key.public_key_der());
+
+        assert!(matches!(
+            decode_key(&encrypted, None),
+            Err(KeyProtectionError::PassphraseRequired)
+        ));
+        assert!(matches!(
+            decode_key(&encrypted, Some("battery staple")),
+            Err(KeyProtectionError::Decrypt)
+        ));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod bundle;
 pub mod certificate;
+pub mod inspect;
+pub mod key_protection;
+pub mod revocation;
+pub mod rotation;
 pub mod utils;
@@ -0,0 +1,386 @@
+use super::inspect::CertificateSummary;
+use super::utils::ensure_panduza_programdata_dirs;
+use super::utils::write_atomic;
+use rcgen::Certificate;
+use rcgen::CertificateRevocationListParams;
+use rcgen::KeyIdMethod;
+use rcgen::KeyPair;
+use rcgen::RevocationReason;
+use rcgen::RevokedCertParams;
+use rcgen::SerialNumber;
+use std::path::Path;
+use std::path::PathBuf;
+use thiserror::Error;
+use time::Duration;
+use time::OffsetDateTime;
+use x509_parser::parse_x509_crl;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::x509::X509Version;
+
+/// File name of the CRL, next to the root CA certificate
+///
+pub const ROOT_CA_CRL: &str = "root_ca_crl.pem";
+
+/// Validity of a freshly signed CRL, it must be re-signed before this delay
+///
+pub const CRL_VALIDITY_DAYS: i64 = 30;
+
+/// Error while producing or reading a certificate revocation list
+///
+#[derive(Error, Debug)]
+pub enum RevocationError {
+    #[error("Cannot access CRL file {path:?} because {cause}")]
+    Io { path: String, cause: String },
+
+    #[error("Invalid CRL: {0}")]
+    Invalid(String),
+
+    #[error("CRL is not signed by the CA {ca_subject:?}: {cause}")]
+    BadSignature { ca_subject: String, cause: String },
+
+    #[error("Invalid serial number {0:?} (hexadecimal, optionally ':' separated)")]
+    InvalidSerial(String),
+
+    #[error("Failed to sign the CRL: {0}")]
+    Sign(#[from] rcgen::Error),
+}
+
+/// A revoked certificate
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevokedEntry {
+    /// Serial number (hexadecimal, `:` separated, as in [CertificateSummary::serial])
+    pub serial: String,
+    /// Revocation date
+    pub revoked_at: OffsetDateTime,
+    /// Optional reason
+    pub reason: Option<RevocationReason>,
+}
+
+/// Content of a CRL whose signature has been checked
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevocationList {
+    /// Sequence number, incremented on each update
+    pub crl_number: u64,
+    /// Issuer distinguished name
+    pub issuer: String,
+    /// Signature date
+    pub this_update: OffsetDateTime,
+    /// Date before which a new CRL must be signed
+    pub next_update: Option<OffsetDateTime>,
+    /// Revoked certificates
+    pub revoked: Vec<RevokedEntry>,
+}
+
+impl RevocationList {
+    /// Entry of a serial number, if revoked
+    ///
+    pub fn find(&self, serial: &str) -> Option<&RevokedEntry> {
+        let serial = parse_serial(serial).ok()?;
+        self.revoked
+            .iter()
+            .find(|entry| parse_serial(&entry.serial).ok().as_ref() == Some(&serial))
+    }
+
+    /// Entry of a certificate, if revoked
+    ///
+    pub fn find_certificate(&self, summary: &CertificateSummary) -> Option<&RevokedEntry> {
+        self.find(&summary.serial)
+    }
+
+    /// True if the next update date is over
+    ///
+    pub fn is_outdated(&self) -> bool {
+        self.next_update
+            .map(|next| OffsetDateTime::now_utc() > next)
+            .unwrap_or(false)
+    }
+}
+
+/// Parse a serial number written in hexadecimal, leading zeros are ignored
+///
+pub fn parse_serial(serial: &str) -> Result<Vec<u8>, RevocationError> {
+    let invalid = || RevocationError::InvalidSerial(serial.to_string());
+    let digits: String = serial.chars().filter(|c| *c != ':').collect();
+    if digits.is_empty() || !digits.is_ascii() {
+        return Err(invalid());
+    }
+    let bytes = digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => u8::from_str_radix(&format!("{}{}", *high as char, *low as char), 16)
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    Ok(bytes[first..].to_vec())
+}
+
+/// Write a serial number as in [CertificateSummary::serial]
+///
+fn format_serial(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Reason code of a CRL entry, as known by rcgen
+///
+fn reason_from_code(code: u8) -> Option<RevocationReason> {
+    match code {
+        0 => Some(RevocationReason::Unspecified),
+        1 => Some(RevocationReason::KeyCompromise),
+        2 => Some(RevocationReason::CaCompromise),
+        3 => Some(RevocationReason::AffiliationChanged),
+        4 => Some(RevocationReason::Superseded),
+        5 => Some(RevocationReason::CessationOfOperation),
+        6 => Some(RevocationReason::CertificateHold),
+        8 => Some(RevocationReason::RemoveFromCrl),
+        9 => Some(RevocationReason::PrivilegeWithdrawn),
+        10 => Some(RevocationReason::AaCompromise),
+        _ => None,
+    }
+}
+
+/// Sign a CRL with the root CA
+///
+/// # Returns
+/// The PEM encoded CRL
+pub fn generate_crl(
+    ca_cert: &Certificate,
+    ca_key: &KeyPair,
+    revoked: &[RevokedEntry],
+    crl_number: u64,
+) -> Result<String, RevocationError> {
+    let revoked_certs = revoked
+        .iter()
+        .map(|entry| {
+            Ok(RevokedCertParams {
+                serial_number: SerialNumber::from_slice(&parse_serial(&entry.serial)?),
+                revocation_time: entry.revoked_at,
+                reason_code: entry.reason,
+                invalidity_date: None,
+            })
+        })
+        .collect::<Result<Vec<_>, RevocationError>>()?;
+
+    let now = OffsetDateTime::now_utc();
+    let params = CertificateRevocationListParams {
+        this_update: now,
+        next_update: now + Duration::days(CRL_VALIDITY_DAYS),
+        crl_number: SerialNumber::from(crl_number),
+        issuing_distribution_point: None,
+        revoked_certs,
+        key_identifier_method: KeyIdMethod::Sha256,
+    };
+    Ok(params.signed_by(ca_cert, ca_key)?.pem()?)
+}
+
+/// Read a PEM CRL and check that it is signed by the given root CA
+///
+pub fn parse_crl(crl_pem: &str, ca_pem: &str) -> Result<RevocationList, RevocationError> {
+    let (_, crl_pem) =
+        parse_x509_pem(crl_pem.as_bytes()).map_err(|e| RevocationError::Invalid(e.to_string()))?;
+    let (_, crl) =
+        parse_x509_crl(&crl_pem.contents).map_err(|e| RevocationError::Invalid(e.to_string()))?;
+    let (_, ca_pem) =
+        parse_x509_pem(ca_pem.as_bytes()).map_err(|e| RevocationError::Invalid(e.to_string()))?;
+    let ca = ca_pem
+        .parse_x509()
+        .map_err(|e| RevocationError::Invalid(e.to_string()))?;
+
+    let ca_subject = ca.subject().to_string();
+    if crl.issuer() != ca.subject() {
+        return Err(RevocationError::BadSignature {
+            ca_subject,
+            cause: format!("issued by {}", crl.issuer()),
+        });
+    }
+    crl.verify_signature(ca.public_key())
+        .map_err(|e| RevocationError::BadSignature {
+            ca_subject,
+            cause: e.to_string(),
+        })?;
+    if crl.version() != Some(X509Version::V2) {
+        return Err(RevocationError::Invalid("not a version 2 CRL".to_string()));
+    }
+
+    let crl_number = match crl.crl_number() {
+        Some(number) => u64::try_from(number)
+            .map_err(|_| RevocationError::Invalid("CRL number too large".to_string()))?,
+        None => 0,
+    };
+    let revoked = crl
+        .iter_revoked_certificates()
+        .map(|entry| RevokedEntry {
+            serial: format_serial(&parse_serial(&entry.raw_serial_as_string()).unwrap_or_default()),
+            revoked_at: entry.revocation_date.to_datetime(),
+            reason: entry
+                .reason_code()
+                .and_then(|(_, reason)| reason_from_code(reason.0)),
+        })
+        .collect();
+
+    Ok(RevocationList {
+        crl_number,
+        issuer: crl.issuer().to_string(),
+        this_update: crl.last_update().to_datetime(),
+        next_update: crl.next_update().map(|next| next.to_datetime()),
+        revoked,
+    })
+}
+
+/// Add a serial number to a CRL and sign it again with an incremented number
+///
+/// Starts a new CRL if `crl_pem` is `None`. Revoking an already revoked
+/// serial keeps its original date and reason.
+///
+/// # Returns
+/// The updated PEM encoded CRL
+pub fn revoke_serial(
+    crl_pem: Option<&str>,
+    serial: &str,
+    reason: Option<RevocationReason>,
+    ca_cert: &Certificate,
+    ca_key: &KeyPair,
+) -> Result<String, RevocationError> {
+    let mut list = match crl_pem {
+        Some(crl_pem) => parse_crl(crl_pem, &ca_cert.pem())?,
+        None => RevocationList {
+            crl_number: 0,
+            issuer: String::new(),
+            this_update: OffsetDateTime::now_utc(),
+            next_update: None,
+            revoked: Vec::new(),
+        },
+    };
+
+    if list.find(serial).is_none() {
+        list.revoked.push(RevokedEntry {
+            serial: format_serial(&parse_serial(serial)?),
+            revoked_at: OffsetDateTime::now_utc(),
+            reason,
+        });
+    }
+    generate_crl(ca_cert, ca_key, &list.revoked, list.crl_number + 1)
+}
+
+/// Sign the same revoked entries again with a fresh validity window
+///
+pub fn refresh_crl(
+    crl_pem: &str,
+    ca_cert: &Certificate,
+    ca_key: &KeyPair,
+) -> Result<String, RevocationError> {
+    let list = parse_crl(crl_pem, &ca_cert.pem())?;
+    generate_crl(ca_cert, ca_key, &list.revoked, list.crl_number + 1)
+}
+
+/// Path of the CRL in the machine-wide panduza directory, next to the root CA
+///
+pub fn crl_path() -> Result<PathBuf, RevocationError> {
+    let (_, cert_dir) = ensure_panduza_programdata_dirs().map_err(|e| RevocationError::Io {
+        path: "panduza data directory".to_string(),
+        cause: e.to_string(),
+    })?;
+    Ok(cert_dir.join(ROOT_CA_CRL))
+}
+
+/// Read a CRL file, `None` if it does not exist
+///
+pub fn load_crl<P: AsRef<Path>>(path: P) -> Result<Option<String>, RevocationError> {
+    let path = path.as_ref();
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(RevocationError::Io {
+            path: path.display().to_string(),
+            cause: e.to_string(),
+        }),
+    }
+}
+
+/// Write a CRL file, replaced atomically
+///
+pub fn save_crl<P: AsRef<Path>>(path: P, crl_pem: &str) -> Result<(), RevocationError> {
+    let path = path.as_ref();
+    write_atomic(path, crl_pem).map_err(|e| RevocationError::Io {
+        path: path.display().to_string(),
+        cause: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::certificate::generate_cert_client_with_san;
+    use crate::security::certificate::generate_root_ca;
+    use crate::security::certificate::CertParams;
+    use crate::security::inspect::inspect_pem;
+
+    fn client(ca: &Certificate, ca_key: &KeyPair, role: &str) -> CertificateSummary {
+        let (cert, _) = generate_cert_client_with_san(
+            ca,
+            ca_key,
+            CertParams {
+                san: vec!["localhost".into()],
+                validity_days: 30,
+                common_name: format!("{}.local", role),
+            },
+        );
+        inspect_pem(&cert.pem()).unwrap()
+    }
+
+    #[test]
+    fn serial_formats() {
+        assert_eq!(parse_serial("00:0a:FF").unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(parse_serial("0aff").unwrap(), vec![0x0a, 0xff]);
+        assert!(parse_serial("abc").is_err());
+        assert!(parse_serial("zz").is_err());
+    }
+
+    #[test]
+    fn revoke_and_update() {
+        let (ca, ca_key) = generate_root_ca();
+        let writer = client(&ca, &ca_key, "writer");
+        let logger = client(&ca, &ca_key, "logger");
+
+        let crl = generate_crl(&ca, &ca_key, &[], 1).unwrap();
+        let list = parse_crl(&crl, &ca.pem()).unwrap();
+        assert_eq!(list.crl_number, 1);
+        assert!(list.revoked.is_empty());
+        assert!(!list.is_outdated());
+
+        let crl = revoke_serial(
+            Some(&crl),
+            &writer.serial,
+            Some(RevocationReason::KeyCompromise),
+            &ca,
+            &ca_key,
+        )
+        .unwrap();
+        // Revoking twice does not duplicate the entry
+        let crl = revoke_serial(Some(&crl), &writer.serial, None, &ca, &ca_key).unwrap();
+        let list = parse_crl(&crl, &ca.pem()).unwrap();
+        assert_eq!(list.crl_number, 3);
+        assert_eq!(list.revoked.len(), 1);
+        let entry = list.find_certificate(&writer).unwrap();
+        assert_eq!(entry.reason, Some(RevocationReason::KeyCompromise));
+        assert!(list.find_certificate(&logger).is_none());
+
+        let list = parse_crl(&refresh_crl(&crl, &ca, &ca_key).unwrap(), &ca.pem()).unwrap();
+        assert_eq!(list.crl_number, 4);
+        assert_eq!(list.revoked.len(), 1);
+
+        let (other_ca, _) = generate_root_ca();
+        assert!(matches!(
+            parse_crl(&crl, &other_ca.pem()),
+            Err(RevocationError::BadSignature { .. })
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use super::certificate::generate_csr_client;
+use super::certificate::generate_root_ca_with_key;
+use super::certificate::sign_csr_with_ca;
+use super::inspect::inspect_file;
+use super::inspect::inspect_pem;
+use super::inspect::matches_key;
+use super::inspect::read_pem_file;
+use super::inspect::CertificateSummary;
+use super::inspect::InspectError;
+use super::key_protection::load_key_file;
+use super::key_protection::KeyProtectionError;
+use super::utils::write_atomic;
+use rcgen::Certificate;
+use rcgen::KeyPair;
+use std::path::Path;
+use thiserror::Error;
+use time::Duration;
+
+/// Error while renewing client credentials
+///
+#[derive(Error, Debug)]
+pub enum RotationError {
+    #[error(transparent)]
+    Inspect(#[from] InspectError),
+
+    #[error("Invalid private key: {0}")]
+    Key(String),
+
+    #[error(transparent)]
+    KeyProtection(#[from] KeyProtectionError),
+
+    #[error("Certificate {0:?} has no common name, its role is unknown")]
+    NoCommonName(String),
+
+    #[error("Failed to create the signing request: {0}")]
+    Csr(String),
+
+    #[error("Signer refused the request: {0}")]
+    Signer(String),
+
+    #[error("Signed certificate does not match the stored private key")]
+    KeyMismatch,
+
+    #[error("Cannot write certificate file {path:?} because {cause}")]
+    Io { path: String, cause: String },
+}
+
+/// Authority able to sign a certificate signing request
+///
+/// [LocalSigner] signs with the root CA available on this machine, other
+/// implementations can forward the request to a remote service.
+///
+pub trait CertificateSigner: Send + Sync {
+    /// Sign a PEM signing request for the given common name (`{role}.local`)
+    ///
+    /// # Returns
+    /// The PEM encoded certificate
+    fn sign(
+        &self,
+        csr_pem: &str,
+        common_name: &str,
+        validity_days: i32,
+    ) -> Result<String, RotationError>;
+}
+
+/// Signer using a root CA certificate and key in memory
+///
+pub struct LocalSigner {
+    ca_cert: Certificate,
+    ca_key: KeyPair,
+}
+
+impl LocalSigner {
+    /// Signer from a root CA certificate and its key
+    ///
+    pub fn new(ca_cert: Certificate, ca_key: KeyPair) -> Self {
+        Self { ca_cert, ca_key }
+    }
+
+    /// Signer from a root CA private key file, the CA certificate is rebuilt from it
+    ///
+    pub fn from_key_file<P: AsRef<Path>>(key_path: P) -> Result<Self, RotationError> {
+        let key_pem = read_pem_file(key_path)?;
+        let ca_key = KeyPair::from_pem(&key_pem).map_err(|e| RotationError::Key(e.to_string()))?;
+        let ca_cert = generate_root_ca_with_key(&ca_key);
+        Ok(Self::new(ca_cert, ca_key))
+    }
+}
+
+impl CertificateSigner for LocalSigner {
+    fn sign(
+        &self,
+        csr_pem: &str,
+        common_name: &str,
+        validity_days: i32,
+    ) -> Result<String, RotationError> {
+        sign_csr_with_ca(
+            csr_pem,
+            &self.ca_cert,
+            &self.ca_key,
+            common_name,
+            validity_days,
+        )
+        .map(|cert| cert.pem())
+        .map_err(|e| RotationError::Signer(e.to_string()))
+    }
+}
+
+/// True if the certificate file expires within the given delay
+///
+pub fn needs_renewal<P: AsRef<Path>>(
+    cert_path: P,
+    within: Duration,
+) -> Result<bool, RotationError> {
+    Ok(inspect_file(cert_path)?.expires_within(within))
+}
+
+/// Renew a client certificate while keeping its private key
+///
+/// A signing request is built from the stored key with the common name and
+/// SANs of the current certificate, signed by `signer`, checked against the
+/// key, then atomically written over `cert_path`. The key file is not modified,
+/// `passphrase` is required when it is encrypted.
+///
+/// # Returns
+/// The summary of the new certificate
+pub fn renew_client_certificate<C: AsRef<Path>, K: AsRef<Path>>(
+    cert_path: C,
+    key_path: K,
+    passphrase: Option<&str>,
+    signer: &dyn CertificateSigner,
+    validity_days: i32,
+) -> Result<CertificateSummary, RotationError> {
+    let cert_path = cert_path.as_ref();
+    let current = inspect_file(cert_path)?;
+    let common_name = current
+        .common_name
+        .clone()
+        .ok_or_else(|| RotationError::NoCommonName(current.subject.clone()))?;
+
+    let key = load_key_file(key_path, passphrase)?;
+    let csr_pem = generate_csr_client(&key, current.san.clone(), validity_days)
+        .pem()
+        .map_err(|e| RotationError::Csr(e.to_string()))?;
+
+    let cert_pem = signer.sign(&csr_pem, &common_name, validity_days)?;
+    let renewed = inspect_pem(&cert_pem)?;
+    if !matches_key(&cert_pem, &key)? {
+        return Err(RotationError::KeyMismatch);
+    }
+
+    write_atomic(cert_path, &cert_pem).map_err(|e| RotationError::Io {
+        path: cert_path.display().to_string(),
+        cause: e.to_string(),
+    })?;
+    Ok(renewed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::certificate::generate_cert_client_with_san;
+    use crate::security::certificate::generate_root_ca;
+    use crate::security::certificate::CertParams;
+    use crate::security::inspect::verify_chain;
+
+    struct RefusingSigner;
+
+    impl CertificateSigner for RefusingSigner {
+        fn sign(&self, _: &str, _: &str, _: i32) -> Result<String, RotationError> {
+            Err(RotationError::Signer("offline".to_string()))
+        }
+    }
+
+    #[test]
+    fn renewal_swaps_certificate_and_keeps_key() {
+        let (ca, ca_key) = generate_root_ca();
+        let ca_pem = ca.pem();
+        let (cert, key) = generate_cert_client_with_san(
+            &ca,
+            &ca_key,
+            CertParams {
+                san: vec!["localhost".into(), "127.0.0.1".into()],
+                validity_days: 5,
+                common_name: "writer.local".into(),
+            },
+        );
+
+        let dir = std::env::temp_dir().join(format!("pza-rotation-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("writer_certificate.pem");
+        let key_path = dir.join("writer_private_key.pem");
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+        std::fs::write(&key_path, key.serialize_pem()).unwrap();
+
+        assert!(needs_renewal(&cert_path, Duration::days(30)).unwrap());
+
+        // A failing signer leaves the current certificate in place
+        assert!(
+            renew_client_certificate(&cert_path, &key_path, None, &RefusingSigner, 365).is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&cert_path).unwrap(), cert.pem());
+
+        let signer = LocalSigner::new(ca, ca_key);
+        let renewed = renew_client_certificate(&cert_path, &key_path, None, &signer, 365).unwrap();
+        assert_eq!(renewed.common_name.as_deref(), Some("writer.local"));
+        assert_eq!(renewed.san, vec!["localhost", "127.0.0.1"]);
+        assert!(!needs_renewal(&cert_path, Duration::days(30)).unwrap());
+
+        let stored = std::fs::read_to_string(&cert_path).unwrap();
+        assert!(verify_chain(&stored, &ca_pem).is_ok());
+        assert_eq!(
+            std::fs::read_to_string(&key_path).unwrap(),
+            key.serialize_pem()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
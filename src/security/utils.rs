@@ -1,8 +1,20 @@
 use rcgen::KeyPair;
 use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use time::{Duration, OffsetDateTime};
-use super::certificate::{generate_cert_client_from_pem_with_san, CertParams};
+use super::certificate::{generate_cert_client_with_san, generate_root_ca_with_key, CertParams};
+
+/// Permissions of the panduza directories on Unix
+const DIR_MODE: u32 = 0o700;
+
+/// Permissions of the private key files on Unix
+const KEY_MODE: u32 = 0o600;
+
+/// Permissions of the certificate files on Unix
+const PUBLIC_MODE: u32 = 0o644;
 
 pub enum PanduzaFileType {
     Key,
@@ -10,39 +22,84 @@ pub enum PanduzaFileType {
     Csr,
 }
 
-/// Ensure the .panduza directory exist or create it with .panduza/keys and .panduza/certificate directories
-pub fn ensure_panduza_dirs() -> (PathBuf, PathBuf) {
-    let home_dir = dirs::home_dir().expect("Unable to find user folder");
-    let panduza_dir = home_dir.join(".panduza");
+/// Create a directory (and its parents) readable only by the owner on Unix
+///
+/// The permissions of an existing directory are restricted as well.
+pub fn create_private_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        use std::os::unix::fs::PermissionsExt;
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(DIR_MODE)
+            .create(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(DIR_MODE))?;
+    }
+    #[cfg(not(unix))]
+    fs::create_dir_all(path)?;
+    Ok(())
+}
+
+/// Create the panduza directory with its keys and certificate sub-directories
+fn create_panduza_dirs(panduza_dir: PathBuf) -> io::Result<(PathBuf, PathBuf)> {
     let keys_dir = panduza_dir.join("keys");
     let cert_dir = panduza_dir.join("certificate");
 
-    fs::create_dir_all(&keys_dir).expect("Fail to create key folder");
-    fs::create_dir_all(&cert_dir).expect("Fail to create certificate folder");
+    create_private_dir(&panduza_dir)?;
+    create_private_dir(&keys_dir)?;
+    create_private_dir(&cert_dir)?;
+
+    Ok((keys_dir, cert_dir))
+}
 
-    (keys_dir, cert_dir)
+/// Ensure the .panduza directory exist or create it with .panduza/keys and .panduza/certificate directories
+pub fn ensure_panduza_dirs() -> io::Result<(PathBuf, PathBuf)> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unable to find user folder"))?;
+    create_panduza_dirs(home_dir.join(".panduza"))
 }
 
 /// Ensure a machine-wide directory under %PROGRAMDATA% (e.g. C:\\ProgramData\\panduza\\{keys,certificate})
 /// Falls back to user data dir if PROGRAMDATA is unavailable
-pub fn ensure_panduza_programdata_dirs() -> (PathBuf, PathBuf) {
+pub fn ensure_panduza_programdata_dirs() -> io::Result<(PathBuf, PathBuf)> {
     #[cfg(target_os = "windows")]
-    let base_dir: PathBuf = std::env::var_os("PROGRAMDATA")
+    let base_dir: Option<PathBuf> = std::env::var_os("PROGRAMDATA")
         .map(PathBuf::from)
-        .or_else(|| dirs::data_dir())
-        .expect("Unable to find a suitable data folder");
+        .or_else(|| dirs::data_dir());
 
     #[cfg(not(target_os = "windows"))]
-    let base_dir: PathBuf = dirs::data_dir().expect("Unable to find a suitable data folder");
+    let base_dir: Option<PathBuf> = dirs::data_dir();
 
-    let panduza_dir = base_dir.join("panduza");
-    let keys_dir = panduza_dir.join("keys");
-    let cert_dir = panduza_dir.join("certificate");
+    let base_dir = base_dir.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to find a suitable data folder",
+        )
+    })?;
+    create_panduza_dirs(base_dir.join("panduza"))
+}
 
-    fs::create_dir_all(&keys_dir).expect("Fail to create key folder in program data");
-    fs::create_dir_all(&cert_dir).expect("Fail to create certificate folder in program data");
+/// Path of a panduza file depending on its type
+fn panduza_file_path(
+    (keys_dir, cert_dir): (PathBuf, PathBuf),
+    file_type: &PanduzaFileType,
+    filename: &str,
+) -> PathBuf {
+    match file_type {
+        PanduzaFileType::Key => keys_dir.join(filename),
+        PanduzaFileType::Certificate => cert_dir.join(filename),
+        PanduzaFileType::Csr => cert_dir.join(filename),
+    }
+}
 
-    (keys_dir, cert_dir)
+/// Write a panduza file atomically, keys are readable only by the owner
+fn write_typed_file(path: &Path, file_type: &PanduzaFileType, content: &str) -> io::Result<()> {
+    match file_type {
+        PanduzaFileType::Key => write_private_atomic(path, content),
+        PanduzaFileType::Certificate | PanduzaFileType::Csr => write_atomic(path, content),
+    }
 }
 
 /// Write a file in the panduza directories depending on the file type
@@ -50,14 +107,9 @@ pub fn write_panduza_file(
     file_type: PanduzaFileType,
     filename: &str,
     content: &str,
-) -> std::io::Result<PathBuf> {
-    let (keys_dir, cert_dir) = ensure_panduza_dirs();
-    let path = match file_type {
-        PanduzaFileType::Key => keys_dir.join(filename),
-        PanduzaFileType::Certificate => cert_dir.join(filename),
-        PanduzaFileType::Csr => cert_dir.join(filename),
-    };
-    std::fs::write(path.clone(), content)?;
+) -> io::Result<PathBuf> {
+    let path = panduza_file_path(ensure_panduza_dirs()?, &file_type, filename);
+    write_typed_file(&path, &file_type, content)?;
     Ok(path)
 }
 
@@ -66,58 +118,106 @@ pub fn write_panduza_file_programdata(
     file_type: PanduzaFileType,
     filename: &str,
     content: &str,
-) -> std::io::Result<PathBuf> {
-    let (keys_dir, cert_dir) = ensure_panduza_programdata_dirs();
-    let path = match file_type {
-        PanduzaFileType::Key => keys_dir.join(filename),
-        PanduzaFileType::Certificate => cert_dir.join(filename),
-        PanduzaFileType::Csr => cert_dir.join(filename),
-    };
-    std::fs::write(path.clone(), content)?;
+) -> io::Result<PathBuf> {
+    let path = panduza_file_path(ensure_panduza_programdata_dirs()?, &file_type, filename);
+    write_typed_file(&path, &file_type, content)?;
     Ok(path)
 }
 
+/// Replace a file atomically: the content is written in a temporary file of the
+/// same directory, flushed, then renamed over the destination
+fn write_atomic_with_mode(path: &Path, content: &str, mode: u32) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No file name"))?;
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    // A leftover temporary file would keep its previous permissions
+    let _ = fs::remove_file(&tmp_path);
+    let result = (|| {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Replace a file atomically (see [write_private_atomic] for secrets)
+pub fn write_atomic<P: AsRef<Path>>(path: P, content: &str) -> io::Result<()> {
+    write_atomic_with_mode(path.as_ref(), content, PUBLIC_MODE)
+}
+
+/// Replace a file atomically, the new file is readable only by the owner on Unix
+pub fn write_private_atomic<P: AsRef<Path>>(path: P, content: &str) -> io::Result<()> {
+    write_atomic_with_mode(path.as_ref(), content, KEY_MODE)
+}
+
 /// Get the path to the panduza directories depending on the file type
-pub fn get_panduza_dir(file_type: PanduzaFileType) -> PathBuf {
-    let (keys_dir, cert_dir) = ensure_panduza_dirs();
-    match file_type {
+pub fn get_panduza_dir(file_type: PanduzaFileType) -> io::Result<PathBuf> {
+    let (keys_dir, cert_dir) = ensure_panduza_dirs()?;
+    Ok(match file_type {
         PanduzaFileType::Key => keys_dir,
         PanduzaFileType::Certificate => cert_dir,
         PanduzaFileType::Csr => cert_dir,
-    }
+    })
 }
 
 /// Get the path to the panduza directories depending on the file type
-pub fn get_panduza_dir_programdata(file_type: PanduzaFileType) -> PathBuf {
-    let (keys_dir, cert_dir) = ensure_panduza_programdata_dirs();
-    match file_type {
+pub fn get_panduza_dir_programdata(file_type: PanduzaFileType) -> io::Result<PathBuf> {
+    let (keys_dir, cert_dir) = ensure_panduza_programdata_dirs()?;
+    Ok(match file_type {
         PanduzaFileType::Key => keys_dir,
         PanduzaFileType::Certificate => cert_dir,
         PanduzaFileType::Csr => cert_dir,
-    }
+    })
 }
 
 /// Check if a key exists in the panduza directories
 pub fn key_exists(filename: &str) -> bool {
-    let (keys_dir, _) = ensure_panduza_dirs();
-    let key_path = keys_dir.join(filename);
-    key_path.exists()
+    panduza_dirs()
+        .map(|(keys_dir, _)| keys_dir.join(filename).exists())
+        .unwrap_or(false)
 }
 
 /// Check if a certificate exists in the panduza directories
 pub fn certificate_exists(filename: &str) -> bool {
-    let (_, cert_dir) = ensure_panduza_dirs();
-    let cert_path = cert_dir.join(filename);
-    cert_path.exists()
+    panduza_dirs()
+        .map(|(_, cert_dir)| cert_dir.join(filename).exists())
+        .unwrap_or(false)
 }
 
-/// Get the default credentials paths of a user to create a Reactor
-pub fn get_default_certificate_paths() -> (PathBuf, PathBuf, PathBuf) {
-    let (keys_dir, cert_dir) = ensure_panduza_dirs();
+/// Get the .panduza/keys and .panduza/certificate paths without creating them
+pub fn panduza_dirs() -> Option<(PathBuf, PathBuf)> {
+    let panduza_dir = dirs::home_dir()?.join(".panduza");
+    Some((panduza_dir.join("keys"), panduza_dir.join("certificate")))
+}
+
+/// Get the default credentials paths of a user, without creating any directory
+pub fn default_certificate_paths() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let (keys_dir, cert_dir) = panduza_dirs()?;
     let root_ca_path = cert_dir.join("root_ca_certificate.pem");
     let client_cert_path = cert_dir.join("client_certificate.pem");
     let client_key_path = keys_dir.join("client_private_key.pem");
-    (root_ca_path, client_cert_path, client_key_path)
+    Some((root_ca_path, client_cert_path, client_key_path))
+}
+
+/// Get the default credentials paths of a user to create a Reactor
+pub fn get_default_certificate_paths() -> io::Result<(PathBuf, PathBuf, PathBuf)> {
+    default_certificate_paths()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unable to find user folder"))
 }
 
 /// Generate a validity period
@@ -137,8 +237,8 @@ pub fn load_key_from_pem(key_path: &str) -> Result<KeyPair, Box<dyn std::error::
 }
 
 /// Generate a key rcgen struct KeyPair
-pub fn generate_key() -> KeyPair {
-    KeyPair::generate().expect("Fail to generate key")
+pub fn generate_key() -> Result<KeyPair, rcgen::Error> {
+    KeyPair::generate()
 }
 
 
@@ -152,7 +252,7 @@ pub fn generate_and_store_client_credentials(
     san: Vec<String>,
     validity_days: i32,
 ) -> Result<(String, String, String), Box<dyn std::error::Error>> {
-    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs();
+    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs()?;
     let root_key_dir_display = root_key_dir.display().to_string().replace("\\", "/");
     let root_cert_dir_display = root_cert_dir.display().to_string().replace("\\", "/");
 
@@ -165,7 +265,9 @@ pub fn generate_and_store_client_credentials(
         common_name: format!("{}.local", role),
     };
 
-    let (cert, key) = generate_cert_client_from_pem_with_san(&root_ca_private_key, cert_params);
+    let root_ca_key = load_key_from_pem(&root_ca_private_key)?;
+    let root_ca = generate_root_ca_with_key(&root_ca_key);
+    let (cert, key) = generate_cert_client_with_san(&root_ca, &root_ca_key, cert_params);
 
     let cert_filename = format!("{}_certificate.pem", role);
     let key_filename = format!("{}_private_key.pem", role);
@@ -173,7 +275,7 @@ pub fn generate_and_store_client_credentials(
     write_panduza_file(PanduzaFileType::Certificate, &cert_filename, &cert.pem())?;
     write_panduza_file(PanduzaFileType::Key, &key_filename, &key.serialize_pem())?;
 
-    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs();
+    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs()?;
     let client_certificate = user_cert_dir
         .join(&cert_filename)
         .display()
@@ -187,3 +289,29 @@ pub fn generate_and_store_client_credentials(
 
     Ok((root_ca_certificate, client_certificate, client_key))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn private_files_and_dirs_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("pza-utils-{}", rand::random::<u64>()));
+        let keys_dir = dir.join("keys");
+        create_private_dir(&keys_dir).unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&keys_dir), DIR_MODE);
+
+        let key_path = keys_dir.join("client_private_key.pem");
+        write_private_atomic(&key_path, "old").unwrap();
+        write_private_atomic(&key_path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&key_path).unwrap(), "new");
+        assert_eq!(mode(&key_path), KEY_MODE);
+        assert!(!keys_dir.join(".client_private_key.pem.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,47 @@
+/// Restart policies of supervised tasks
+pub mod restart;
+pub use restart::Backoff;
+pub use restart::RestartMode;
+pub use restart::RestartPolicy;
+
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
+use futures::StreamExt;
+use restart::RestartHistory;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::SystemTime;
 use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tokio::task::JoinError;
 use tokio::task::JoinHandle;
-
-/// Delay between 2 monitoring action (in milliseconds)
-///
-const DELAY_MS_BETWEEN_MONITORING: u64 = 200;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 
 /// Default capacity for event and handle channels
 const DEFAULT_CHANNEL_CAPACITY: usize = 512;
 
+/// Time given to the tasks to finish when the monitor is stopped
+///
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of finished (not supervised) tasks kept for [TaskMonitor::tasks]
+///
+const FINISHED_TASK_HISTORY: usize = 64;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Payload of event enums
 ///
@@ -28,6 +57,10 @@ pub struct EventBody {
     /// Error message if any
     ///
     pub error_message: Option<String>,
+
+    /// Number of restarts of a supervised task (0 for the first run)
+    ///
+    pub restart_count: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -54,11 +87,256 @@ pub enum Event {
     ///
     TaskPanicOMG(EventBody),
 
+    /// Supervised task about to be restarted, the body holds the new restart
+    /// count and the error of the previous run
+    ///
+    TaskRestarting(EventBody),
+
+    /// Supervised task not restarted because its restart limit is reached
+    ///
+    TaskGaveUp(EventBody),
+
     /// No more task to monitor
     ///
     NoMoreTask,
 }
 
+impl Event {
+    /// Payload of the event, if any
+    ///
+    pub fn body(&self) -> Option<&EventBody> {
+        match self {
+            Event::TaskCreated(body)
+            | Event::TaskStopProperly(body)
+            | Event::TaskStopWithPain(body)
+            | Event::TaskPanicOMG(body)
+            | Event::TaskRestarting(body)
+            | Event::TaskGaveUp(body) => Some(body),
+            Event::TaskMonitorError(_) | Event::NoMoreTask => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// State of a task known by the monitor
+///
+pub struct TaskInfo {
+    /// Name of the task
+    ///
+    pub name: String,
+
+    /// Id of the current (or last) run of the task
+    ///
+    pub task_id: Option<String>,
+
+    /// Number of restarts, always 0 for a task that is not supervised
+    ///
+    pub restart_count: u32,
+
+    /// True while the task runs
+    ///
+    pub running: bool,
+
+    /// True if the task is restarted by the monitor
+    ///
+    pub supervised: bool,
+
+    /// Start of the current (or last) run
+    ///
+    pub started_at: SystemTime,
+
+    /// Duration of the current run, or of the last one once finished
+    ///
+    pub duration: Duration,
+
+    /// Error of the last failed run, if any
+    ///
+    pub last_error: Option<String>,
+}
+
+/// Task known by the monitor, with its metrics
+///
+struct Entry {
+    name: String,
+    task_id: Option<String>,
+    restart_count: u32,
+    supervised: bool,
+
+    /// True while the current run is alive
+    ///
+    running: bool,
+
+    /// True while the task (or its supervisor) is alive
+    ///
+    alive: bool,
+
+    started_at: SystemTime,
+    started: Instant,
+    ended: Option<Instant>,
+    last_error: Option<String>,
+
+    /// Aborts the task, or the supervisor and its current run
+    ///
+    abort: Option<AbortHandle>,
+}
+
+impl Entry {
+    /// New alive entry, not running yet
+    ///
+    fn new(name: String, supervised: bool, abort: Option<AbortHandle>) -> Self {
+        Self {
+            name,
+            task_id: None,
+            restart_count: 0,
+            supervised,
+            running: false,
+            alive: true,
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+            ended: None,
+            last_error: None,
+            abort,
+        }
+    }
+
+    /// Record the start of a run
+    ///
+    fn start_run(&mut self, task_id: String) {
+        self.task_id = Some(task_id);
+        self.running = true;
+        self.started_at = SystemTime::now();
+        self.started = Instant::now();
+        self.ended = None;
+    }
+
+    /// Record the end of a run
+    ///
+    fn end_run(&mut self, error: Option<String>) {
+        self.running = false;
+        self.ended.get_or_insert_with(Instant::now);
+        if error.is_some() {
+            self.last_error = error;
+        }
+    }
+
+    /// Public view of the entry
+    ///
+    fn info(&self) -> TaskInfo {
+        TaskInfo {
+            name: self.name.clone(),
+            task_id: self.task_id.clone(),
+            restart_count: self.restart_count,
+            running: self.running,
+            supervised: self.supervised,
+            started_at: self.started_at,
+            duration: self
+                .ended
+                .unwrap_or_else(Instant::now)
+                .duration_since(self.started),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Tasks known by the monitor, in registration order
+///
+#[derive(Default)]
+struct Registry {
+    next_key: u64,
+    entries: BTreeMap<u64, Entry>,
+}
+
+impl Registry {
+    /// Register an entry
+    ///
+    fn insert(&mut self, entry: Entry) -> u64 {
+        let key = self.next_key;
+        self.next_key += 1;
+        self.entries.insert(key, entry);
+        key
+    }
+
+    /// Entry of a registered task
+    ///
+    fn entry(&mut self, key: u64) -> Option<&mut Entry> {
+        self.entries.get_mut(&key)
+    }
+
+    /// Number of alive tasks
+    ///
+    fn active_count(&self) -> usize {
+        self.entries.values().filter(|entry| entry.alive).count()
+    }
+
+    /// Abort handles of the alive tasks
+    ///
+    fn alive_aborts(&self) -> Vec<AbortHandle> {
+        self.entries
+            .values()
+            .filter(|entry| entry.alive)
+            .filter_map(|entry| entry.abort.clone())
+            .collect()
+    }
+
+    /// Forget the oldest finished tasks beyond [FINISHED_TASK_HISTORY]
+    ///
+    fn prune(&mut self) {
+        let finished: Vec<u64> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.alive && !entry.supervised)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in finished
+            .iter()
+            .take(finished.len().saturating_sub(FINISHED_TASK_HISTORY))
+        {
+            self.entries.remove(key);
+        }
+    }
+}
+
+/// State shared by the monitor handles, the monitoring task and the supervisors
+///
+struct Shared {
+    /// Known tasks
+    ///
+    registry: std::sync::Mutex<Registry>,
+
+    /// Number of alive tasks, updated on each change
+    ///
+    active: watch::Sender<usize>,
+
+    /// Events dropped because the event channel was full
+    ///
+    dropped_events: AtomicU64,
+}
+
+impl Shared {
+    /// Modify the registry and publish the new number of alive tasks
+    ///
+    fn update<R>(&self, f: impl FnOnce(&mut Registry) -> R) -> R {
+        let mut registry = self.registry.lock().unwrap();
+        let result = f(&mut registry);
+        let count = registry.active_count();
+        self.active.send_if_modified(|active| {
+            let changed = *active != count;
+            *active = count;
+            changed
+        });
+        result
+    }
+}
+
+/// End of a work followed by the monitoring task
+///
+enum Completion {
+    /// A monitored task finished
+    Task(u64, EventBody, Result<Result<(), String>, JoinError>),
+    /// A supervisor finished
+    Supervisor(u64),
+}
+
 /// Type of handle managed by this monitor
 ///
 pub type TaskHandle = JoinHandle<Result<(), String>>;
@@ -77,22 +355,49 @@ pub type TaskMonitorLink = Sender<NamedTaskHandle>;
 #[derive(Clone)]
 /// This object is able to monitor a group of tokio task and report status
 ///
+/// The end of each task is reported as soon as it happens.
+///
 pub struct TaskMonitor {
-    /// List of the monitored task handles
+    /// Name of the monitor, used in warnings
     ///
-    handles: Arc<Mutex<Vec<NamedTaskHandle>>>,
+    name: String,
+
+    /// Known tasks and their metrics
+    ///
+    shared: Arc<Shared>,
+
+    /// Sender of the monitoring events
+    ///
+    event_sender: Sender<Event>,
 
     /// Sender that allow other task to send their handle for monitoring
     ///
     handle_sender: Sender<NamedTaskHandle>,
 
-    /// Internal handle for the task that feed this monitor
+    /// Sender of the supervisors to the monitoring task
     ///
-    task_feeding: Arc<JoinHandle<()>>,
+    supervisor_sender: UnboundedSender<(u64, JoinHandle<()>)>,
+
+    /// Cancelled to ask the tasks to stop
+    ///
+    token: CancellationToken,
+
+    /// Cancelled to stop the monitoring task
+    ///
+    halt: CancellationToken,
 
     /// Internal handle for the task that monitor other tasks
     ///
-    task_monitoring: Arc<JoinHandle<()>>,
+    task_monitoring: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl std::fmt::Debug for TaskMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskMonitor")
+            .field("name", &self.name)
+            .field("task_count", &*self.shared.active.borrow())
+            .finish()
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -105,8 +410,13 @@ impl TaskMonitor {
         let name = name.into();
 
         //
-        // Initialize handles
-        let handles = Arc::new(Mutex::new(Vec::new()));
+        // Initialize the registry of the tasks
+        let (active, _) = watch::channel(0);
+        let shared = Arc::new(Shared {
+            registry: std::sync::Mutex::new(Registry::default()),
+            active,
+            dropped_events: AtomicU64::new(0),
+        });
 
         //
         // Initialize events channel to alert the parent listener
@@ -114,108 +424,31 @@ impl TaskMonitor {
 
         //
         // Initialize handles channel to allow other object to send task handles to monitor
-        let (handle_sender, mut handle_receiver) =
-            channel::<NamedTaskHandle>(DEFAULT_CHANNEL_CAPACITY);
+        let (handle_sender, handle_receiver) = channel::<NamedTaskHandle>(DEFAULT_CHANNEL_CAPACITY);
+        let (supervisor_sender, supervisor_receiver) = unbounded_channel();
 
         //
-        // TASK to register new handles
-        let feed_t = tokio::spawn({
-            // Local clone variables
-            let handles = handles.clone();
-            let event_sender = event_sender.clone();
-            let name = name.clone();
-
-            // Task body
-            async move {
-                loop {
-                    match handle_receiver.recv().await {
-                        Some(new_handle) => {
-                            // Send an event
-                            if let Err(e) = event_sender
-                                .send(Event::TaskCreated(EventBody {
-                                    task_name: new_handle.0.clone(),
-                                    task_id: new_handle.1.id().to_string(),
-                                    error_message: None,
-                                }))
-                                .await
-                            {
-                                print_warning(&name, line!(), e);
-                            }
-
-                            // Save the handle
-                            handles.lock().await.push(new_handle);
-                        }
-                        None => {
-                            // Properly handle the closure of the channel
-                            if let Err(e) = event_sender
-                                .send(Event::TaskMonitorError(format!("Channel has been closed")))
-                                .await
-                            {
-                                print_warning(&name, line!(), e);
-                            }
-                            break; // Exit the loop when the channel is closed
-                        }
-                    }
-                }
-            }
-        });
-
-        //
-        // TASK to monitor other tasks
-        let monitor_t = tokio::spawn({
-            // Local clone variables for the task
-            let handles = handles.clone();
-            let event_sender = event_sender.clone();
-            let name = name.clone();
-
-            async move {
-                // Track if we have already sent the NoMoreTask event
-                let mut no_more_task_sent = false;
-
-                loop {
-                    // Wait before next monitoring
-                    tokio::time::sleep(Duration::from_millis(DELAY_MS_BETWEEN_MONITORING)).await;
-
-                    // Lock handles
-                    let mut hlock = handles.lock().await;
-
-                    // No more task to check
-                    if hlock.is_empty() {
-                        if !no_more_task_sent {
-                            if let Err(e) = event_sender.send(Event::NoMoreTask).await {
-                                print_warning(&name, line!(), e);
-                            }
-                            no_more_task_sent = true;
-                        }
-                    } else {
-                        no_more_task_sent = false;
-                    }
-
-                    // Monitor tasks, checking for each task if it is finished
-                    let mut i = 0;
-                    while i < hlock.len() {
-                        let h = &mut hlock[i];
-                        if h.1.is_finished() {
-                            let element = hlock.remove(i);
-                            process_finished_task(&event_sender, &name, element).await;
-                        } else {
-                            // Increment the index only if the element has not been removed
-                            i += 1;
-                        }
-                    }
-
-                    // Release handles
-                    drop(hlock);
-                }
-            }
-        });
+        // TASK to register new handles and monitor them
+        let halt = CancellationToken::new();
+        let monitor_t = tokio::spawn(monitor_tasks(
+            name.clone(),
+            shared.clone(),
+            event_sender.clone(),
+            handle_receiver,
+            supervisor_receiver,
+            halt.clone(),
+        ));
 
         (
             Self {
-                handles: handles,
-                handle_sender: handle_sender,
-                task_feeding: Arc::new(feed_t),
-                task_monitoring: Arc::new(monitor_t),
+                name,
+                shared,
+                event_sender,
+                handle_sender,
+                supervisor_sender,
+                token: CancellationToken::new(),
+                halt,
+                task_monitoring: Arc::new(Mutex::new(Some(monitor_t))),
             },
             event_receiver,
         )
@@ -223,25 +456,16 @@ impl TaskMonitor {
 
     /// Cancel all tasks
     ///
+    /// Tasks are aborted, supervised tasks are not restarted. Returns once the
+    /// end of each task has been reported.
     pub async fn cancel_all_monitored_tasks(&mut self) {
-        //
-        // println!("Cancelling all monitored tasks");
-
-        // lock elements
-        let mut hlock = self.handles.lock().await;
-
-        // abort all tasks first
-        for h in hlock.iter_mut() {
-            // println!("Aborting task: {:?}", h.0);
-            h.1.abort();
+        // abort all tasks first, supervisors abort their current run
+        for abort in self.shared.update(|registry| registry.alive_aborts()) {
+            abort.abort();
         }
 
         // Then wait for them to complete
-        for element in hlock.drain(..) {
-            let _ = element.1.await;
-        }
-
-        // hlock est maintenant vide grâce à drain
+        self.wait_idle().await;
     }
 
     /// Provides access to the handler sender to send new handle to monitor
@@ -250,25 +474,249 @@ impl TaskMonitor {
         self.handle_sender.clone()
     }
 
-    /// Provides access to the handler sender to send new handle to monitor
+    /// Token cancelled when the monitor shuts down
+    ///
+    /// Tasks that select on it can finish properly during the drain delay of
+    /// [TaskMonitor::shutdown].
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.child_token()
+    }
+
+    /// Run a task under supervision
+    ///
+    /// The task is created by `factory` and restarted according to `policy`.
+    /// Each run is reported with the usual events, with its restart count, and
+    /// the restarts with [Event::TaskRestarting] and [Event::TaskGaveUp].
+    ///
+    /// # Arguments
+    /// * `name` - Name of the task, used by [TaskMonitor::find]
+    /// * `policy` - When and how often the task is restarted
+    /// * `factory` - Creates the future of a new run
+    pub async fn supervise<N, F, Fut>(&self, name: N, policy: RestartPolicy, factory: F)
+    where
+        N: Into<String>,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let name = name.into();
+        let key = self
+            .shared
+            .update(|registry| registry.insert(Entry::new(name, true, None)));
+        let supervisor = tokio::spawn(run_supervised(
+            self.shared.clone(),
+            key,
+            policy,
+            factory,
+            self.event_sender.clone(),
+            self.name.clone(),
+            self.token.clone(),
+        ));
+        let abort = supervisor.abort_handle();
+        self.shared.update(|registry| {
+            if let Some(entry) = registry.entry(key) {
+                entry.abort = Some(abort.clone());
+            }
+        });
+        if self.supervisor_sender.send((key, supervisor)).is_err() {
+            // The monitor is stopped, nobody would report the task
+            abort.abort();
+        }
+    }
+
+    /// State of the last registered task with the given name
+    ///
+    pub async fn find(&self, name: &str) -> Option<TaskInfo> {
+        self.tasks()
+            .await
+            .into_iter()
+            .rev()
+            .find(|task| task.name == name)
+    }
+
+    /// State and metrics of the known tasks, in registration order
+    ///
+    /// Finished tasks are kept, up to the last 64 ones that are not supervised.
+    pub async fn tasks(&self) -> Vec<TaskInfo> {
+        self.shared
+            .registry
+            .lock()
+            .unwrap()
+            .entries
+            .values()
+            .map(Entry::info)
+            .collect()
+    }
+
+    /// Stop the tasks gracefully, then the monitor
+    ///
+    /// The cancellation token is cancelled and supervised tasks are not
+    /// restarted anymore. Tasks still running after `drain_timeout` are
+    /// aborted. Returns once the end of each task has been reported.
+    ///
+    /// # Returns
+    /// The number of tasks aborted after the drain timeout
+    pub async fn shutdown(&self, drain_timeout: Duration) -> usize {
+        self.token.cancel();
+
+        let mut aborted = 0;
+        if tokio::time::timeout(drain_timeout, self.wait_idle())
+            .await
+            .is_err()
+        {
+            let aborts = self.shared.update(|registry| registry.alive_aborts());
+            aborted = aborts.len();
+            for abort in aborts {
+                abort.abort();
+            }
+            self.wait_idle().await;
+        }
+
+        self.halt.cancel();
+        if let Some(monitoring) = self.task_monitoring.lock().await.take() {
+            let _ = monitoring.await;
+        }
+        aborted
+    }
+
+    /// Stop the monitor, see [TaskMonitor::shutdown] (2 s of drain delay)
     ///
     pub async fn stop(self) {
-        self.task_feeding.abort();
-        self.task_monitoring.abort();
-        // self.task_feeding.await;
-        // self.task_monitoring.await;
+        self.shutdown(DEFAULT_DRAIN_TIMEOUT).await;
     }
 
     /// Returns the number of tasks currently being monitored
     ///
+    /// Supervised tasks count until they are not restarted anymore.
     pub async fn task_count(&self) -> usize {
-        self.handles.lock().await.len()
+        *self.shared.active.borrow()
+    }
+
+    /// Number of events dropped because the receiver was not drained
+    ///
+    pub fn dropped_events(&self) -> u64 {
+        self.shared.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Wait until no task is alive
+    ///
+    async fn wait_idle(&self) {
+        if self.halt.is_cancelled() {
+            return;
+        }
+        let mut active = self.shared.active.subscribe();
+        let _ = active.wait_for(|count| *count == 0).await;
+    }
+}
+
+/// Body of the monitoring task: register the new tasks and report the end of
+/// each one as soon as it happens
+///
+async fn monitor_tasks(
+    name: String,
+    shared: Arc<Shared>,
+    event_sender: Sender<Event>,
+    mut handle_receiver: Receiver<NamedTaskHandle>,
+    mut supervisor_receiver: UnboundedReceiver<(u64, JoinHandle<()>)>,
+    halt: CancellationToken,
+) {
+    let mut running: FuturesUnordered<BoxFuture<'static, Completion>> = FuturesUnordered::new();
+    let mut feeding = true;
+
+    loop {
+        tokio::select! {
+            _ = halt.cancelled() => break,
+
+            new_handle = handle_receiver.recv(), if feeding => match new_handle {
+                Some((task_name, handle)) => {
+                    let task_id = handle.id().to_string();
+                    let abort = handle.abort_handle();
+                    let key = shared.update(|registry| {
+                        let mut entry = Entry::new(task_name.clone(), false, Some(abort));
+                        entry.start_run(task_id.clone());
+                        registry.insert(entry)
+                    });
+
+                    // Send an event
+                    let body = EventBody {
+                        task_name,
+                        task_id,
+                        error_message: None,
+                        restart_count: 0,
+                    };
+                    send_event(&shared, &event_sender, &name, Event::TaskCreated(body.clone()));
+
+                    running.push(async move { Completion::Task(key, body, handle.await) }.boxed());
+                }
+                None => {
+                    // Properly handle the closure of the channel
+                    send_event(
+                        &shared,
+                        &event_sender,
+                        &name,
+                        Event::TaskMonitorError("Channel has been closed".to_string()),
+                    );
+                    feeding = false;
+                }
+            },
+
+            Some((key, supervisor)) = supervisor_receiver.recv() => {
+                running.push(
+                    async move {
+                        let _ = supervisor.await;
+                        Completion::Supervisor(key)
+                    }
+                    .boxed(),
+                );
+            }
+
+            Some(completion) = running.next() => {
+                let (event, remaining) = match completion {
+                    Completion::Task(key, body, result) => {
+                        let event = stop_event(result, body);
+                        let error = event.body().and_then(|body| body.error_message.clone());
+                        let remaining = shared.update(|registry| {
+                            if let Some(entry) = registry.entry(key) {
+                                entry.end_run(error);
+                                entry.alive = false;
+                                entry.abort = None;
+                            }
+                            registry.prune();
+                            registry.active_count()
+                        });
+                        (Some(event), remaining)
+                    }
+                    Completion::Supervisor(key) => shared.update(|registry| {
+                        // A supervisor aborted during a run takes the run with it
+                        let event = registry.entry(key).and_then(|entry| {
+                            let interrupted = entry.running.then(|| EventBody {
+                                task_name: entry.name.clone(),
+                                task_id: entry.task_id.clone().unwrap_or_default(),
+                                error_message: Some("task was cancelled".to_string()),
+                                restart_count: entry.restart_count,
+                            });
+                            entry.end_run(None);
+                            entry.alive = false;
+                            entry.abort = None;
+                            interrupted.map(Event::TaskPanicOMG)
+                        });
+                        (event, registry.active_count())
+                    }),
+                };
+
+                if let Some(event) = event {
+                    send_event(&shared, &event_sender, &name, event);
+                }
+                if remaining == 0 {
+                    send_event(&shared, &event_sender, &name, Event::NoMoreTask);
+                }
+            }
+        }
     }
 }
 
 /// Print a warning message for this task monitor
 ///
-fn print_warning(name: &String, line: u32, e: tokio::sync::mpsc::error::SendError<Event>) {
+fn print_warning(name: &String, line: u32, e: TrySendError<Event>) {
     println!(
         "{:?} - {:?} - TaskMonitor warning ! {:?}",
         &name,
@@ -277,51 +725,317 @@ fn print_warning(name: &String, line: u32, e: tokio::sync::mpsc::error::SendErro
     );
 }
 
-/// Process a finished task
+/// Send an event without waiting for the receiver
 ///
-async fn process_finished_task(event_sender: &Sender<Event>, name: &str, element: NamedTaskHandle) {
-    let task_id = element.1.id().to_string();
-    match element.1.await {
-        Ok(result) => match result {
-            // Task end properly
-            Ok(_) => {
-                if let Err(e) = event_sender
-                    .send(Event::TaskStopProperly(EventBody {
-                        task_name: element.0,
-                        task_id,
-                        error_message: None,
-                    }))
-                    .await
-                {
-                    print_warning(&name.to_string(), line!(), e);
-                }
-            }
-            // Task end with an error
-            Err(e) => {
-                if let Err(e) = event_sender
-                    .send(Event::TaskStopWithPain(EventBody {
-                        task_name: element.0,
-                        task_id,
-                        error_message: Some(e),
-                    }))
-                    .await
-                {
-                    print_warning(&name.to_string(), line!(), e);
-                }
+/// The monitoring must go on when the receiver is not drained: events that do
+/// not fit in the channel are dropped and counted. A warning is printed if the
+/// receiver is gone.
+fn send_event(shared: &Shared, event_sender: &Sender<Event>, name: &str, event: Event) {
+    match event_sender.try_send(event) {
+        Ok(()) => {}
+        Err(TrySendError::Full(event)) => {
+            let count = shared.dropped_events.fetch_add(1, Ordering::Relaxed) + 1;
+            // Log the first drop then every 100 to avoid flooding
+            if count % 100 == 1 {
+                tracing::warn!(
+                    "TaskMonitor {}: event channel full, {} event(s) dropped, last: {:?}",
+                    name,
+                    count,
+                    event
+                );
             }
-        },
+        }
+        Err(e) => print_warning(&name.to_string(), line!(), e),
+    }
+}
+
+/// Event reporting the end of a task
+///
+fn stop_event(result: Result<Result<(), String>, JoinError>, mut body: EventBody) -> Event {
+    match result {
+        // Task end properly
+        Ok(Ok(_)) => Event::TaskStopProperly(body),
+        // Task end with an error
+        Ok(Err(e)) => {
+            body.error_message = Some(e);
+            Event::TaskStopWithPain(body)
+        }
         // Task PANIC
         Err(e) => {
-            if let Err(e) = event_sender
-                .send(Event::TaskPanicOMG(EventBody {
-                    task_name: element.0,
-                    task_id,
-                    error_message: Some(e.to_string()),
-                }))
-                .await
-            {
-                print_warning(&name.to_string(), line!(), e);
+            body.error_message = Some(e.to_string());
+            Event::TaskPanicOMG(body)
+        }
+    }
+}
+
+/// Abort the wrapped task when dropped
+///
+/// The run of a supervised task stops with its supervisor.
+///
+struct AbortOnDrop(JoinHandle<Result<(), String>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Body of a supervisor: run the task, report, restart it as the policy says
+///
+async fn run_supervised<F, Fut>(
+    shared: Arc<Shared>,
+    key: u64,
+    policy: RestartPolicy,
+    factory: F,
+    event_sender: Sender<Event>,
+    name: String,
+    token: CancellationToken,
+) where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let mut history = RestartHistory::default();
+    let mut failures = 0;
+
+    loop {
+        //
+        // Start a new run
+        let mut run = AbortOnDrop(tokio::spawn(factory()));
+        let task_id = run.0.id().to_string();
+        let Some(body) = shared.update(|registry| {
+            let entry = registry.entry(key)?;
+            entry.start_run(task_id.clone());
+            Some(EventBody {
+                task_name: entry.name.clone(),
+                task_id,
+                error_message: None,
+                restart_count: entry.restart_count,
+            })
+        }) else {
+            return;
+        };
+        send_event(
+            &shared,
+            &event_sender,
+            &name,
+            Event::TaskCreated(body.clone()),
+        );
+
+        //
+        // Wait for its end
+        let result = (&mut run.0).await;
+        let event = stop_event(result, body);
+        let failed = !matches!(event, Event::TaskStopProperly(_));
+        let mut body = event.body().cloned().expect("stop events have a body");
+        shared.update(|registry| {
+            if let Some(entry) = registry.entry(key) {
+                entry.end_run(body.error_message.clone());
+            }
+        });
+        send_event(&shared, &event_sender, &name, event);
+
+        //
+        // Restart if allowed
+        if token.is_cancelled() || !policy.should_restart(failed) {
+            break;
+        }
+        if !history.try_record(policy.max_restarts, Instant::now()) {
+            send_event(&shared, &event_sender, &name, Event::TaskGaveUp(body));
+            break;
+        }
+        failures = if failed { failures + 1 } else { 0 };
+        body.restart_count = shared.update(|registry| {
+            registry
+                .entry(key)
+                .map(|entry| {
+                    entry.restart_count += 1;
+                    entry.restart_count
+                })
+                .unwrap_or_default()
+        });
+        send_event(&shared, &event_sender, &name, Event::TaskRestarting(body));
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = tokio::time::sleep(policy.backoff.delay(failures.max(1))) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn supervised_task_restarts_until_limit() {
+        let (monitor, mut events) = TaskMonitor::new("test");
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let policy = RestartPolicy::on_failure()
+            .with_backoff(Backoff::fixed(Duration::from_millis(10)))
+            .with_max_restarts(2, Duration::from_secs(60));
+        monitor
+            .supervise("flaky", policy, {
+                let runs = runs.clone();
+                move || {
+                    let run = runs.fetch_add(1, Ordering::SeqCst);
+                    async move { Err(format!("run {} failed", run)) }
+                }
+            })
+            .await;
+
+        let mut restarts = Vec::new();
+        loop {
+            match events.recv().await.unwrap() {
+                Event::TaskRestarting(body) => restarts.push(body.restart_count),
+                Event::TaskGaveUp(body) => {
+                    assert_eq!(body.restart_count, 2);
+                    assert_eq!(body.error_message.as_deref(), Some("run 2 failed"));
+                    break;
+                }
+                _ => {}
             }
         }
+        assert_eq!(restarts, vec![1, 2]);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        let info = monitor.find("flaky").await.unwrap();
+        assert_eq!(info.restart_count, 2);
+        assert!(info.supervised);
+        assert!(monitor.find("unknown").await.is_none());
+        monitor.stop().await;
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_supervised_runs() {
+        let (mut monitor, _events) = TaskMonitor::new("test");
+        let running = Arc::new(AtomicU32::new(0));
+
+        monitor
+            .supervise("forever", RestartPolicy::always(), {
+                let running = running.clone();
+                move || {
+                    let running = running.clone();
+                    async move {
+                        running.fetch_add(1, Ordering::SeqCst);
+                        std::future::pending::<()>().await;
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+        while running.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(monitor.task_count().await, 1);
+        assert!(monitor.find("forever").await.unwrap().running);
+
+        monitor.cancel_all_monitored_tasks().await;
+        assert_eq!(monitor.task_count().await, 0);
+    }
+
+    /// Next event, well before the former 200 ms polling period
+    async fn next(events: &mut Receiver<Event>) -> Option<Event> {
+        tokio::time::timeout(Duration::from_millis(150), events.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn end_of_task_is_reported_immediately() {
+        let (monitor, mut events) = TaskMonitor::new("test");
+        let handle = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err("broken".to_string())
+        });
+        monitor
+            .handle_sender()
+            .send(("worker".to_string(), handle))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            next(&mut events).await,
+            Some(Event::TaskCreated(_))
+        ));
+        match next(&mut events).await {
+            Some(Event::TaskStopWithPain(body)) => assert_eq!(body.task_name, "worker"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(next(&mut events).await, Some(Event::NoMoreTask));
+
+        let info = monitor.find("worker").await.unwrap();
+        assert!(!info.running);
+        assert!(info.duration >= Duration::from_millis(20));
+        assert_eq!(info.last_error.as_deref(), Some("broken"));
+        assert_eq!(monitor.task_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_drains_then_aborts() {
+        let (monitor, mut events) = TaskMonitor::new("test");
+
+        let token = monitor.cancellation_token();
+        let polite = tokio::spawn(async move {
+            token.cancelled().await;
+            Ok(())
+        });
+        let stubborn = tokio::spawn(std::future::pending::<Result<(), String>>());
+        let sender = monitor.handle_sender();
+        sender.send(("polite".to_string(), polite)).await.unwrap();
+        sender
+            .send(("stubborn".to_string(), stubborn))
+            .await
+            .unwrap();
+        while monitor.task_count().await < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(monitor.shutdown(Duration::from_millis(50)).await, 1);
+        assert_eq!(monitor.task_count().await, 0);
+
+        let mut stops = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                Event::TaskStopProperly(body) => stops.push((body.task_name, true)),
+                Event::TaskPanicOMG(body) => stops.push((body.task_name, false)),
+                _ => {}
+            }
+        }
+        assert_eq!(
+            stops,
+            vec![
+                ("polite".to_string(), true),
+                ("stubborn".to_string(), false)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn undrained_receiver_does_not_block_the_monitor() {
+        let (monitor, _events) = TaskMonitor::new("test");
+
+        let sender = monitor.handle_sender();
+        let stubborn = tokio::spawn(std::future::pending::<Result<(), String>>());
+        sender
+            .send(("stubborn".to_string(), stubborn))
+            .await
+            .unwrap();
+        for i in 0..400 {
+            let handle = tokio::spawn(async { Ok(()) });
+            sender.send((format!("task-{}", i), handle)).await.unwrap();
+        }
+        while monitor.dropped_events() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let shutdown = monitor.shutdown(Duration::from_millis(50));
+        let aborted = tokio::time::timeout(Duration::from_secs(5), shutdown)
+            .await
+            .expect("shutdown must not wait for the event receiver");
+        assert_eq!(aborted, 1);
+        assert_eq!(monitor.task_count().await, 0);
     }
 }
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// When a supervised task is started again
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartMode {
+    /// The task runs once
+    Never,
+    /// Restarted when it returns an error or panics
+    OnFailure,
+    /// Restarted whatever the outcome
+    Always,
+}
+
+/// Delay before a restart, growing with the consecutive failures
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// Delay before the first restart
+    pub initial: Duration,
+    /// Upper bound of the delay
+    pub max: Duration,
+    /// Multiplier applied after each consecutive failure
+    pub factor: u32,
+}
+
+impl Backoff {
+    /// Same delay before each restart
+    ///
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            initial: delay,
+            max: delay,
+            factor: 1,
+        }
+    }
+
+    /// Delay doubled after each consecutive failure, up to `max`
+    ///
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            factor: 2,
+        }
+    }
+
+    /// Delay before the restart following `failures` consecutive failures
+    ///
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = self
+            .factor
+            .checked_pow(failures.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    /// 100 ms doubled up to 30 s
+    ///
+    fn default() -> Self {
+        Self::exponential(Duration::from_millis(100), Duration::from_secs(30))
+    }
+}
+
+/// Restart policy of a supervised task
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// When the task is started again
+    pub mode: RestartMode,
+    /// Delay before each restart
+    pub backoff: Backoff,
+    /// Maximum number of restarts within a time window, unlimited if None
+    pub max_restarts: Option<(u32, Duration)>,
+}
+
+impl RestartPolicy {
+    /// The task runs once
+    ///
+    pub fn never() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            backoff: Backoff::default(),
+            max_restarts: None,
+        }
+    }
+
+    /// Restart the task when it fails
+    ///
+    pub fn on_failure() -> Self {
+        Self {
+            mode: RestartMode::OnFailure,
+            ..Self::never()
+        }
+    }
+
+    /// Restart the task each time it stops
+    ///
+    pub fn always() -> Self {
+        Self {
+            mode: RestartMode::Always,
+            ..Self::never()
+        }
+    }
+
+    /// Sets the delay before each restart
+    ///
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Give up after `count` restarts within `window`
+    ///
+    pub fn with_max_restarts(mut self, count: u32, window: Duration) -> Self {
+        self.max_restarts = Some((count, window));
+        self
+    }
+
+    /// True if a task that stopped (with `failed` outcome) must be started again
+    ///
+    pub fn should_restart(&self, failed: bool) -> bool {
+        match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => failed,
+            RestartMode::Always => true,
+        }
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+/// Dates of the recent restarts, to enforce [RestartPolicy::max_restarts]
+///
+#[derive(Debug, Default)]
+pub(crate) struct RestartHistory {
+    restarts: VecDeque<Instant>,
+}
+
+impl RestartHistory {
+    /// Record a restart at `now` if the limit allows it
+    ///
+    /// # Returns
+    /// False when the limit is reached, the restart must not happen
+    pub(crate) fn try_record(&mut self, limit: Option<(u32, Duration)>, now: Instant) -> bool {
+        // Without limit there is nothing to keep
+        let Some((count, window)) = limit else {
+            return true;
+        };
+        while self
+            .restarts
+            .front()
+            .is_some_and(|date| now.duration_since(*date) >= window)
+        {
+            self.restarts.pop_front();
+        }
+        if self.restarts.len() >= count as usize {
+            return false;
+        }
+        self.restarts.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_and_limit() {
+        let backoff = Backoff::exponential(Duration::from_millis(100), Duration::from_secs(1));
+        assert_eq!(backoff.delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.delay(3), Duration::from_millis(400));
+        assert_eq!(backoff.delay(40), Duration::from_secs(1));
+        assert_eq!(
+            Backoff::fixed(Duration::from_secs(2)).delay(5),
+            Duration::from_secs(2)
+        );
+
+        assert!(RestartPolicy::on_failure().should_restart(true));
+        assert!(!RestartPolicy::on_failure().should_restart(false));
+        assert!(RestartPolicy::always().should_restart(false));
+        assert!(!RestartPolicy::never().should_restart(true));
+
+        let limit = Some((2, Duration::from_secs(10)));
+        let start = Instant::now();
+        let mut history = RestartHistory::default();
+        assert!(history.try_record(limit, start));
+        assert!(history.try_record(limit, start + Duration::from_secs(1)));
+        assert!(!history.try_record(limit, start + Duration::from_secs(2)));
+        // The first restart leaves the window
+        assert!(history.try_record(limit, start + Duration::from_secs(10)));
+
+        let mut unlimited = RestartHistory::default();
+        for _ in 0..100 {
+            assert!(unlimited.try_record(None, start));
+        }
+        assert!(unlimited.restarts.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use std::net::TcpListener;
+use std::time::Duration;
+use zenoh::Session;
+
+/// How long the helpers wait for a declaration to reach the other session
+///
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Local TCP endpoint on a port picked by the system
+///
+pub(crate) fn local_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("no free local port");
+    let port = listener.local_addr().unwrap().port();
+    format!("tcp/127.0.0.1:{}", port)
+}
+
+// ----------------------------------------------------------------------------
+
+/// Open a peer session without scouting, listening or connecting on the given
+/// endpoints
+///
+pub(crate) async fn open_session(listen: Option<&str>, connect: Option<&str>) -> Session {
+    let mut config = serde_json::json!({
+        "mode": "peer",
+        "scouting": {"multicast": {"enabled": false}},
+    });
+    if let Some(listen) = listen {
+        config["listen"] = serde_json::json!({ "endpoints": [listen] });
+    }
+    if let Some(connect) = connect {
+        config["connect"] = serde_json::json!({ "endpoints": [connect] });
+    }
+    let config = zenoh::Config::from_json5(&config.to_string()).unwrap();
+    zenoh::open(config).await.unwrap()
+}
+
+// ----------------------------------------------------------------------------
+
+/// Open a platform session on a free local port and a client connected to it
+///
+pub(crate) async fn platform_and_client() -> (Session, Session, String) {
+    let endpoint = local_endpoint();
+    let platform = open_session(Some(&endpoint), None).await;
+    let client = open_session(None, Some(&endpoint)).await;
+    (platform, client, endpoint)
+}
+
+// ----------------------------------------------------------------------------
+
+/// Wait until a subscriber matches what `session` publishes on `key`
+///
+pub(crate) async fn wait_for_subscriber(session: &Session, key: &str) {
+    let publisher = session.declare_publisher(key.to_string()).await.unwrap();
+    tokio::time::timeout(READY_TIMEOUT, async {
+        while !publisher.matching_status().await.unwrap().matching() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no subscriber on {}", key));
+}
+
+// ----------------------------------------------------------------------------
+
+/// Wait until a queryable answers what `session` queries on `key`
+///
+pub(crate) async fn wait_for_queryable(session: &Session, key: &str) {
+    let querier = session.declare_querier(key.to_string()).await.unwrap();
+    tokio::time::timeout(READY_TIMEOUT, async {
+        while !querier.matching_status().await.unwrap().matching() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no queryable on {}", key));
+}
@@ -1,4 +1,70 @@
-#[derive(Debug)]
+use std::fmt::Display;
+use thiserror::Error as ThisError;
+use zenoh::key_expr::KeyExpr;
+
+/// Fixed identifier of Panduza topics
+///
+const PZA_IDENTIFIER: &str = "pza";
+
+/// Error raised when a topic cannot be parsed or built
+///
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum TopicError {
+    #[error("Identifier 'pza' not found in topic {0:?}")]
+    MissingPza(String),
+
+    #[error("No instance found in topic {0:?}")]
+    MissingInstance(String),
+
+    #[error("Empty layer found in topic {0:?}")]
+    EmptyLayer(String),
+
+    #[error("Topic {0:?} cannot be parsed back to the same components")]
+    Ambiguous(String),
+
+    #[error("Topic {topic:?} is not a valid key expression because {cause:?}")]
+    InvalidKeyExpr { topic: String, cause: String },
+}
+
+/// Trailing segment of a topic
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicSuffix {
+    /// Command topic, used to send requests to the driver
+    ///
+    Cmd,
+
+    /// Attribute topic, used by the driver to publish values
+    ///
+    Att,
+}
+
+impl TopicSuffix {
+    /// String used in the topic for this suffix
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TopicSuffix::Cmd => "cmd",
+            TopicSuffix::Att => "att",
+        }
+    }
+
+    /// Parse a topic layer into a suffix
+    ///
+    pub fn from_layer(layer: &str) -> Option<Self> {
+        match layer {
+            "cmd" => Some(TopicSuffix::Cmd),
+            "att" => Some(TopicSuffix::Att),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TopicSuffix {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 
 /// Helper to decompose Panduza topics into a structured object
 ///
@@ -48,8 +114,11 @@
 /// - `color` is the attribute.
 /// - `cmd` is the command that completes the path.
 ///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Topic {
-    pub _namespace: String,
+    /// Namespace, empty if the topic has no namespace
+    ///
+    pub namespace: String,
 
     /// Name of the instance
     ///
@@ -62,49 +131,56 @@ pub struct Topic {
     /// True if it is an attribute path, false for container
     ///
     pub is_attribute: bool,
+
+    /// Trailing [cmd|att] segment, if present
+    ///
+    pub suffix: Option<TopicSuffix>,
 }
 
 impl Topic {
+    /// Returns a builder to create a topic from its components
+    ///
+    pub fn builder() -> TopicBuilder {
+        TopicBuilder::default()
+    }
+
+    // ------------------------------------------------------------------------
+
     /// Instance name getter
     ///
     pub fn instance_name(&self) -> &String {
         &self.instance
     }
 
+    // ------------------------------------------------------------------------
+
+    /// Namespace getter, None if the topic has no namespace
     ///
+    pub fn namespace(&self) -> Option<&str> {
+        if self.namespace.is_empty() {
+            None
+        } else {
+            Some(self.namespace.as_str())
+        }
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Class layers joined with '/'
+    ///
+    /// If the topic is an attribute path, the last layer (the attribute name) is excluded.
     ///
     pub fn class_stack_name(&self) -> String {
-        let mut r = String::new();
-        if self.is_attribute {
-            //
-            // Copy layers and remove the last one (which is the name the attribute)
-            let mut n = self.layers.clone();
-            n.remove(n.len() - 1);
-            // CODE DUPLICATION
-            let mut first = true;
-            for l in &n {
-                if first {
-                    r = format!("{}", l);
-                    first = false;
-                } else {
-                    r = format!("{}/{}", r, l);
-                }
-            }
+        let classes = if self.is_attribute && !self.layers.is_empty() {
+            &self.layers[..self.layers.len() - 1]
         } else {
-            // CODE DUPLICATION
-            let mut first = true;
-            for l in &self.layers {
-                if first {
-                    r = format!("{}", l);
-                    first = false;
-                } else {
-                    r = format!("{}/{}", r, l);
-                }
-            }
-        }
-        r
+            &self.layers[..]
+        };
+        classes.join("/")
     }
 
+    // ------------------------------------------------------------------------
+
     /// Attribute of Class name getter
     ///
     /// We cannot know if it is a attribute or class just with the topic
@@ -113,51 +189,84 @@ impl Topic {
         self.layers.last()
     }
 
-    pub fn from_string<A: Into<String>>(topic: A, is_attribute: bool) -> Self {
-        // Split the topic
-        let topic_string = topic.into();
-        let mut layers: Vec<&str> = topic_string.split('/').collect();
-
-        //
-        //
-        let mut namespace_parts: Vec<String> = Vec::new();
-        while !layers.is_empty() {
-            {
-                let layer = layers.get(0).unwrap();
-                if *layer == "pza" {
-                    break;
-                }
-                namespace_parts.push(layer.to_string());
-            }
-            layers.remove(0);
+    // ------------------------------------------------------------------------
+
+    /// Parse a topic string
+    ///
+    /// The trailing `cmd` or `att` segment is recognized and stored in `suffix`,
+    /// in this case the topic is considered as an attribute path.
+    ///
+    pub fn parse<A: AsRef<str>>(topic: A) -> Result<Self, TopicError> {
+        let topic_str = topic.as_ref();
+        let mut layers: Vec<&str> = topic_str.split('/').collect();
+
+        // Empty layers are not allowed (double or trailing '/')
+        if layers.iter().any(|l| l.is_empty()) {
+            return Err(TopicError::EmptyLayer(topic_str.to_string()));
         }
 
-        // Remove pza
-        layers.remove(0);
+        // Everything before 'pza' is the namespace
+        let pza_index = layers
+            .iter()
+            .position(|l| *l == PZA_IDENTIFIER)
+            .ok_or_else(|| TopicError::MissingPza(topic_str.to_string()))?;
+        let namespace = layers[..pza_index].join("/");
+        let mut layers = layers.split_off(pza_index + 1);
+
+        // Trailing suffix
+        let suffix = match layers.last() {
+            Some(last) if layers.len() > 1 => TopicSuffix::from_layer(last),
+            _ => None,
+        };
+        if suffix.is_some() {
+            layers.pop();
+        }
 
-        //
-        //
-        let namespace = namespace_parts.join("/");
-        let device = layers.remove(0).to_string();
+        // Instance
+        if layers.is_empty() {
+            return Err(TopicError::MissingInstance(topic_str.to_string()));
+        }
+        let instance = layers.remove(0).to_string();
 
-        Self {
-            _namespace: namespace,
-            instance: device,
+        Ok(Self {
+            namespace,
+            instance,
             layers: layers.into_iter().map(|l| l.to_string()).collect(),
-            is_attribute: is_attribute,
-        }
+            is_attribute: suffix.is_some(),
+            suffix,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Decompose a topic string, panics if the topic is not valid
+    ///
+    /// Prefer [Topic::parse] which returns an error instead.
+    ///
+    pub fn from_string<A: Into<String>>(topic: A, is_attribute: bool) -> Self {
+        let topic_string = topic.into();
+        let mut topic = Self::parse(&topic_string)
+            .unwrap_or_else(|e| panic!("Invalid topic {:?}: {}", topic_string, e));
+        topic.is_attribute = is_attribute || topic.suffix.is_some();
+        topic
     }
 
+    // ------------------------------------------------------------------------
+
     pub fn layers_len(&self) -> usize {
         self.layers.len()
     }
 
-    pub fn first_layer(&self) -> String {
-        self.layers.first().unwrap().clone()
+    // ------------------------------------------------------------------------
+
+    pub fn first_layer(&self) -> Option<String> {
+        self.layers.first().cloned()
     }
 
-    pub fn last_layer(&self) -> String {
-        self.layers.last().unwrap().clone()
+    // ------------------------------------------------------------------------
+
+    pub fn last_layer(&self) -> Option<String> {
+        self.layers.last().cloned()
     }
 
     // ------------------------------------------------------------------------
@@ -171,11 +280,208 @@ impl Topic {
     }
 
     // ------------------------------------------------------------------------
+
+    /// Returns a copy of this topic with the given suffix
+    ///
+    pub fn with_suffix(&self, suffix: TopicSuffix) -> Self {
+        let mut topic = self.clone();
+        topic.suffix = Some(suffix);
+        topic.is_attribute = true;
+        topic
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Returns a copy of this topic without suffix
+    ///
+    pub fn without_suffix(&self) -> Self {
+        let mut topic = self.clone();
+        topic.suffix = None;
+        topic
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Topic string ending with '/att'
+    ///
+    pub fn att_topic(&self) -> String {
+        self.with_suffix(TopicSuffix::Att).to_string()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Topic string ending with '/cmd'
+    ///
+    pub fn cmd_topic(&self) -> String {
+        self.with_suffix(TopicSuffix::Cmd).to_string()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Convert the topic into a Zenoh key expression
+    ///
+    pub fn to_key_expr(&self) -> Result<KeyExpr<'static>, TopicError> {
+        let topic = self.to_string();
+        KeyExpr::try_from(topic.clone()).map_err(|e| TopicError::InvalidKeyExpr {
+            topic,
+            cause: e.to_string(),
+        })
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+impl Display for Topic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if !self.namespace.is_empty() {
+            write!(f, "{}/", self.namespace)?;
+        }
+        write!(f, "{}/{}", PZA_IDENTIFIER, self.instance)?;
+        for layer in &self.layers {
+            write!(f, "/{}", layer)?;
+        }
+        if let Some(suffix) = &self.suffix {
+            write!(f, "/{}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Topic {
+    type Err = TopicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ----------------------------------------------------------------------------
+// ----------------------------------------------------------------------------
+
+/// Builder to create a topic from its components
+///
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TopicBuilder {
+    /// Optional namespace
+    ///
+    namespace: Option<String>,
+
+    /// Name of the instance
+    ///
+    instance: Option<String>,
+
+    /// Class layers
+    ///
+    classes: Vec<String>,
+
+    /// Name of the attribute
+    ///
+    attribute: Option<String>,
+
+    /// Trailing [cmd|att] segment
+    ///
+    suffix: Option<TopicSuffix>,
+}
+
+impl TopicBuilder {
+    // ------------------------------------------------------------------------
+
+    /// Set the namespace, an empty namespace means no namespace
+    ///
+    pub fn with_namespace<S: Into<String>>(mut self, namespace: S) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the instance name
+    ///
+    pub fn with_instance<S: Into<String>>(mut self, instance: S) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Append a class layer
+    ///
+    pub fn with_class<S: Into<String>>(mut self, class: S) -> Self {
+        self.classes.push(class.into());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Replace all class layers
+    ///
+    pub fn with_classes(mut self, classes: Vec<String>) -> Self {
+        self.classes = classes;
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the attribute name
+    ///
+    pub fn with_attribute<S: Into<String>>(mut self, attribute: S) -> Self {
+        self.attribute = Some(attribute.into());
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Set the trailing [cmd|att] segment
+    ///
+    pub fn with_suffix(mut self, suffix: TopicSuffix) -> Self {
+        self.suffix = Some(suffix);
+        self
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Build the topic
+    ///
+    pub fn build(self) -> Result<Topic, TopicError> {
+        let instance = self
+            .instance
+            .ok_or_else(|| TopicError::MissingInstance(String::new()))?;
+
+        let is_attribute = self.attribute.is_some() || self.suffix.is_some();
+        let mut layers = self.classes;
+        layers.extend(self.attribute);
+
+        let topic = Topic {
+            namespace: self.namespace.unwrap_or_default(),
+            instance,
+            layers,
+            is_attribute,
+            suffix: self.suffix,
+        };
+
+        // Check that the topic can be parsed back to the same components
+        let topic_str = topic.to_string();
+        let parsed = Topic::parse(&topic_str)?;
+        if parsed.namespace != topic.namespace
+            || parsed.instance != topic.instance
+            || parsed.layers != topic.layers
+            || parsed.suffix != topic.suffix
+        {
+            return Err(TopicError::Ambiguous(topic_str));
+        }
+
+        Ok(topic)
+    }
+
+    // ------------------------------------------------------------------------
 }
 
 #[cfg(test)]
 mod tests {
     use super::Topic;
+    use super::TopicError;
+    use super::TopicSuffix;
 
     #[test]
     fn test_stack_name() {
@@ -183,4 +489,71 @@ mod tests {
 
         assert_eq!(topic.class_stack_name(), "".to_string());
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Topic::parse("company/truc/machin"),
+            Err(TopicError::MissingPza("company/truc/machin".to_string()))
+        );
+        assert_eq!(
+            Topic::parse("company/pza"),
+            Err(TopicError::MissingInstance("company/pza".to_string()))
+        );
+        assert_eq!(
+            Topic::parse("pza/truc//machin"),
+            Err(TopicError::EmptyLayer("pza/truc//machin".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_suffix() {
+        let topic = Topic::parse("company/pza/service_001/level1/level2/color/cmd").unwrap();
+
+        assert_eq!(topic.namespace(), Some("company"));
+        assert_eq!(topic.instance_name(), "service_001");
+        assert_eq!(topic.layers, vec!["level1", "level2", "color"]);
+        assert_eq!(topic.suffix, Some(TopicSuffix::Cmd));
+        assert_eq!(topic.class_stack_name(), "level1/level2");
+        assert_eq!(topic.leaf_name(), Some(&"color".to_string()));
+
+        let topic = Topic::parse("pza/service_001/color").unwrap();
+        assert_eq!(topic.namespace(), None);
+        assert_eq!(topic.suffix, None);
+        assert!(!topic.is_attribute);
+    }
+
+    #[test]
+    fn test_builder_display_round_trip() {
+        let topic = Topic::builder()
+            .with_namespace("company")
+            .with_instance("service_001")
+            .with_class("level1")
+            .with_class("level2")
+            .with_attribute("color")
+            .with_suffix(TopicSuffix::Att)
+            .build()
+            .unwrap();
+
+        let topic_str = topic.to_string();
+        assert_eq!(topic_str, "company/pza/service_001/level1/level2/color/att");
+        assert_eq!(Topic::parse(&topic_str).unwrap(), topic);
+        assert_eq!(
+            topic.cmd_topic(),
+            "company/pza/service_001/level1/level2/color/cmd"
+        );
+        assert_eq!(
+            topic.to_key_expr().unwrap().as_str(),
+            "company/pza/service_001/level1/level2/color/att"
+        );
+
+        assert!(Topic::builder().with_attribute("color").build().is_err());
+        assert_eq!(
+            Topic::builder()
+                .with_instance("service_001")
+                .with_class("a/b")
+                .build(),
+            Err(TopicError::Ambiguous("pza/service_001/a/b".to_string()))
+        );
+    }
 }
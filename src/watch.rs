@@ -0,0 +1,715 @@
+/// Conditions watched on attributes and their alarm state
+pub mod condition;
+pub use condition::AlarmReason;
+pub use condition::WatchCondition;
+
+use crate::attribute::tasks::AttributeTasks;
+use crate::attribute::AttributeError;
+use crate::attribute::CallbackGuard;
+use crate::fbs::BooleanBuffer;
+use crate::fbs::NotificationBuffer;
+use crate::fbs::NotificationType;
+use crate::fbs::NumberBuffer;
+use crate::fbs::PzaBuffer;
+use crate::reactor::Access;
+use crate::reactor::Connection;
+use crate::BooleanAttribute;
+use crate::NumberAttribute;
+use crate::Reactor;
+use crate::Topic;
+use condition::Evaluator;
+use condition::Reading;
+use condition::Transition;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+use thiserror::Error;
+use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Capacity of the alarm event channel
+///
+const EVENT_CHANNEL_CAPACITY: usize = 512;
+
+/// Topic of the platform notifications the alerts are published on
+///
+const NOTIFICATION_TOPIC: &str = "pza/_/notifications";
+
+/// Identifier of a condition attached by a [Watch]
+///
+pub type WatchId = u64;
+
+/// Error while attaching a condition
+///
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("Condition {condition:?} cannot watch the {kind} attribute {topic}")]
+    Unsupported {
+        condition: WatchCondition,
+        kind: &'static str,
+        topic: String,
+    },
+
+    #[error(transparent)]
+    Attribute(#[from] AttributeError),
+}
+
+/// State of an alarm
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmState {
+    /// The condition is violated
+    Raised,
+    /// The condition is satisfied again
+    Cleared,
+}
+
+/// Change of the alarm of a watched condition
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlarmEvent {
+    /// Identifier of the condition
+    pub id: WatchId,
+    /// Name given to the condition
+    pub name: String,
+    /// Topic of the watched attribute
+    pub topic: String,
+    /// Raised or cleared
+    pub state: AlarmState,
+    /// Why the alarm was raised
+    pub reason: AlarmReason,
+    /// Date of the change
+    pub at: SystemTime,
+}
+
+impl AlarmEvent {
+    /// Human readable description, used as notification message
+    ///
+    pub fn message(&self) -> String {
+        match self.state {
+            AlarmState::Raised => format!("{} ({}): {}", self.name, self.topic, self.reason),
+            AlarmState::Cleared => {
+                format!("{} ({}): back to normal", self.name, self.topic)
+            }
+        }
+    }
+}
+
+/// Condition followed by the evaluation task
+///
+struct Rule {
+    name: String,
+    topic: String,
+    evaluator: Evaluator,
+    last_sample: Instant,
+}
+
+/// Request to the evaluation task
+///
+enum Command {
+    Add(WatchId, Rule),
+    Remove(WatchId),
+    Reading(WatchId, Reading),
+}
+
+/// Where the alerts are published
+///
+type AlertTarget = Arc<Mutex<Option<(String, watch::Receiver<Connection>)>>>;
+
+/// Alarms and watchdogs on attributes
+///
+/// Conditions are attached to number and boolean attributes: bounds with
+/// hysteresis, must-stay-true, and staleness when no sample is received for a
+/// while. Each change of an alarm is sent on the event channel and, once
+/// [Watch::publish_alerts_on] is called, published as a notification
+/// ([NotificationType::Alert] when raised, [NotificationType::Info] when cleared).
+/// The evaluation never waits for the event receiver: events that do not fit in
+/// the channel are dropped and counted by [Watch::dropped_events].
+///
+/// The conditions are followed until the watch is dropped.
+///
+pub struct Watch {
+    /// Name of the watch, source of the notifications
+    name: String,
+
+    /// Requests to the evaluation task
+    commands: UnboundedSender<Command>,
+
+    /// Next condition identifier
+    next_id: AtomicU64,
+
+    /// Callbacks on the watched attributes
+    guards: Mutex<HashMap<WatchId, CallbackGuard>>,
+
+    /// Last raised event of each active alarm
+    active: Arc<Mutex<BTreeMap<WatchId, AlarmEvent>>>,
+
+    /// Publication of the alerts, if any
+    alerts: AlertTarget,
+
+    /// Events dropped because the channel was full
+    dropped: Arc<AtomicU64>,
+
+    /// Evaluation task, aborted with the watch
+    tasks: Arc<AttributeTasks>,
+}
+
+impl std::fmt::Debug for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watch")
+            .field("name", &self.name)
+            .field("conditions", &self.guards.lock().unwrap().len())
+            .field("active", &self.active.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl Watch {
+    /// Create a watch without condition
+    ///
+    /// # Returns
+    /// The watch and the receiver of its alarm events
+    pub fn new<N: Into<String>>(name: N) -> (Self, Receiver<AlarmEvent>) {
+        let name = name.into();
+        let (commands, command_receiver) = unbounded_channel();
+        let (event_sender, event_receiver) = channel(EVENT_CHANNEL_CAPACITY);
+        let active = Arc::new(Mutex::new(BTreeMap::new()));
+        let alerts: AlertTarget = Arc::new(Mutex::new(None));
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let tasks = AttributeTasks::new(format!("watch/{}", name));
+        tasks.spawn(
+            "evaluate",
+            evaluate(
+                name.clone(),
+                command_receiver,
+                event_sender,
+                active.clone(),
+                alerts.clone(),
+                dropped.clone(),
+            ),
+        );
+
+        (
+            Self {
+                name,
+                commands,
+                next_id: AtomicU64::new(0),
+                guards: Mutex::new(HashMap::new()),
+                active,
+                alerts,
+                dropped,
+                tasks,
+            },
+            event_receiver,
+        )
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Watch a number attribute with bounds or staleness
+    ///
+    /// The current value, if any, is evaluated immediately.
+    pub async fn watch_number<N: Into<String>>(
+        &self,
+        name: N,
+        attribute: &NumberAttribute,
+        condition: WatchCondition,
+    ) -> Result<WatchId, WatchError> {
+        let topic = attribute.metadata().topic.clone();
+        if condition == WatchCondition::MustStayTrue {
+            return Err(WatchError::Unsupported {
+                condition,
+                kind: "number",
+                topic,
+            });
+        }
+        let id = self.add_rule(name.into(), topic, condition);
+
+        let commands = self.commands.clone();
+        let guard = attribute
+            .add_callback(
+                move |buffer: NumberBuffer| {
+                    if let Some(value) = buffer.value() {
+                        let _ = commands.send(Command::Reading(id, Reading::Number(value)));
+                    }
+                    Box::pin(async {})
+                },
+                None::<fn(&NumberBuffer) -> bool>,
+            )
+            .await;
+        self.guards.lock().unwrap().insert(id, guard);
+
+        if let Some(value) = attribute.get().await.and_then(|buffer| buffer.value()) {
+            let _ = self
+                .commands
+                .send(Command::Reading(id, Reading::Number(value)));
+        }
+        Ok(id)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Watch a boolean attribute that must stay true, or its staleness
+    ///
+    /// The current value, if any, is evaluated immediately.
+    pub async fn watch_boolean<N: Into<String>>(
+        &self,
+        name: N,
+        attribute: &BooleanAttribute,
+        condition: WatchCondition,
+    ) -> Result<WatchId, WatchError> {
+        let topic = attribute.metadata().topic.clone();
+        if matches!(condition, WatchCondition::Bounds { .. }) {
+            return Err(WatchError::Unsupported {
+                condition,
+                kind: "boolean",
+                topic,
+            });
+        }
+        let id = self.add_rule(name.into(), topic, condition);
+
+        let commands = self.commands.clone();
+        let guard = attribute
+            .add_callback(
+                move |buffer: BooleanBuffer| {
+                    if let Some(value) = buffer.value() {
+                        let _ = commands.send(Command::Reading(id, Reading::Boolean(value)));
+                    }
+                    Box::pin(async {})
+                },
+                None::<fn(&BooleanBuffer) -> bool>,
+            )
+            .await;
+        self.guards.lock().unwrap().insert(id, guard);
+
+        if let Some(value) = attribute.get().await.and_then(|buffer| buffer.value()) {
+            let _ = self
+                .commands
+                .send(Command::Reading(id, Reading::Boolean(value)));
+        }
+        Ok(id)
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Register a condition in the evaluation task
+    ///
+    fn add_rule(&self, name: String, topic: String, condition: WatchCondition) -> WatchId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let rule = Rule {
+            name,
+            topic,
+            evaluator: Evaluator::new(condition),
+            last_sample: Instant::now(),
+        };
+        let _ = self.commands.send(Command::Add(id, rule));
+        id
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Stop watching a condition, its alarm is forgotten without event
+    ///
+    pub fn unwatch(&self, id: WatchId) -> bool {
+        let removed = self.guards.lock().unwrap().remove(&id).is_some();
+        let _ = self.commands.send(Command::Remove(id));
+        removed
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Publish the alarm changes on the platform notifications of the reactor
+    ///
+    /// The evaluation task is then stopped by [Reactor::shutdown].
+    ///
+    /// # Returns
+    /// An error if the role of the reactor cannot publish notifications
+    pub fn publish_alerts_on(&self, reactor: &Reactor) -> Result<(), WatchError> {
+        let att_topic = Topic::parse(NOTIFICATION_TOPIC)
+            .map_err(AttributeError::from)?
+            .att_topic();
+        if let Some(role) = reactor.role() {
+            if !role.can_publish(&att_topic) {
+                return Err(AttributeError::PermissionDenied {
+                    role,
+                    access: Access::Publish,
+                    topic: att_topic,
+                }
+                .into());
+            }
+        }
+        *self.alerts.lock().unwrap() = Some((att_topic, reactor.connection_updates()));
+        reactor.task_scope().track(&self.tasks);
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Publish the alarm changes on the notifications of the connections
+    /// published on the channel
+    ///
+    pub fn publish_alerts(&self, connections: watch::Receiver<Connection>) {
+        let att_topic = Topic::parse(NOTIFICATION_TOPIC)
+            .expect("Invalid notification topic")
+            .att_topic();
+        *self.alerts.lock().unwrap() = Some((att_topic, connections));
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Raised events of the active alarms, by condition identifier
+    ///
+    pub fn active_alarms(&self) -> Vec<AlarmEvent> {
+        self.active.lock().unwrap().values().cloned().collect()
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Name of the watch
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // ------------------------------------------------------------------------
+
+    /// Number of alarm events dropped because the receiver was full
+    ///
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    // ------------------------------------------------------------------------
+}
+
+/// Body of the evaluation task: evaluate the samples and the staleness
+/// deadlines, then report the alarm changes
+///
+async fn evaluate(
+    name: String,
+    mut commands: UnboundedReceiver<Command>,
+    events: Sender<AlarmEvent>,
+    active: Arc<Mutex<BTreeMap<WatchId, AlarmEvent>>>,
+    alerts: AlertTarget,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut rules: BTreeMap<WatchId, Rule> = BTreeMap::new();
+    let mut sequence: u16 = 0;
+
+    loop {
+        // Next staleness deadline among the conditions not raised yet
+        let deadline = rules
+            .values()
+            .filter(|rule| rule.evaluator.active().is_none())
+            .filter_map(|rule| {
+                rule.evaluator
+                    .stale_timeout()
+                    .map(|timeout| rule.last_sample + timeout)
+            })
+            .min();
+
+        let command = tokio::select! {
+            command = commands.recv() => match command {
+                Some(command) => Some(command),
+                None => return,
+            },
+            _ = tokio::time::sleep_until(
+                deadline.unwrap_or_else(|| Instant::now() + Duration::from_secs(3600))
+            ), if deadline.is_some() => None,
+        };
+
+        let now = Instant::now();
+        let mut transitions = Vec::new();
+        match command {
+            Some(Command::Add(id, rule)) => {
+                rules.insert(id, rule);
+            }
+            Some(Command::Remove(id)) => {
+                rules.remove(&id);
+                active.lock().unwrap().remove(&id);
+            }
+            Some(Command::Reading(id, reading)) => {
+                if let Some(rule) = rules.get_mut(&id) {
+                    rule.last_sample = now;
+                    transitions.extend(
+                        rule.evaluator
+                            .on_reading(reading)
+                            .into_iter()
+                            .map(|transition| (id, transition)),
+                    );
+                }
+            }
+            None => {}
+        }
+        for (id, rule) in rules.iter_mut() {
+            if let Some(transition) = rule
+                .evaluator
+                .on_silence(now.duration_since(rule.last_sample))
+            {
+                transitions.push((*id, transition));
+            }
+        }
+
+        for (id, transition) in transitions {
+            let Some(rule) = rules.get(&id) else {
+                continue;
+            };
+            let (state, reason) = match transition {
+                Transition::Raised(reason) => (AlarmState::Raised, reason),
+                Transition::Cleared(reason) => (AlarmState::Cleared, reason),
+            };
+            let event = AlarmEvent {
+                id,
+                name: rule.name.clone(),
+                topic: rule.topic.clone(),
+                state,
+                reason,
+                at: SystemTime::now(),
+            };
+
+            match state {
+                AlarmState::Raised => {
+                    tracing::warn!("Watch {}: {}", name, event.message());
+                    active.lock().unwrap().insert(id, event.clone());
+                }
+                AlarmState::Cleared => {
+                    tracing::info!("Watch {}: {}", name, event.message());
+                    active.lock().unwrap().remove(&id);
+                }
+            }
+
+            let target = alerts.lock().unwrap().clone();
+            if let Some((att_topic, connections)) = target {
+                sequence = sequence.wrapping_add(1);
+                publish_alert(&name, &event, sequence, &att_topic, &connections).await;
+            }
+
+            // The receiver may be slow or gone, the alarms are still evaluated
+            // and published
+            if let Err(TrySendError::Full(event)) = events.try_send(event) {
+                let count = dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // Log the first drop then every 100 to avoid flooding
+                if count % 100 == 1 {
+                    tracing::warn!(
+                        "Watch {}: event channel full, {} event(s) dropped, last: {}",
+                        name,
+                        count,
+                        event.message()
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Publish an alarm change as a platform notification
+///
+async fn publish_alert(
+    name: &str,
+    event: &AlarmEvent,
+    sequence: u16,
+    att_topic: &str,
+    connections: &watch::Receiver<Connection>,
+) {
+    let notification_type = match event.state {
+        AlarmState::Raised => NotificationType::Alert,
+        AlarmState::Cleared => NotificationType::Info,
+    };
+    let buffer = NotificationBuffer::builder()
+        .with_notification_type(notification_type)
+        .with_notification_source(name)
+        .with_notification_message(event.message())
+        .with_sequence(sequence)
+        .with_source(0)
+        .build()
+        .expect("Failed to build NotificationBuffer");
+    let Connection { session, role } = connections.borrow().clone();
+    if let Some(role) = role.filter(|role| !role.can_publish(att_topic)) {
+        // The role may have changed since publish_alerts_on checked it
+        tracing::warn!("Watch {}: role {} cannot publish alerts", name, role);
+        return;
+    }
+    if let Err(e) = session.put(att_topic, buffer.to_zbytes()).await {
+        tracing::warn!("Watch {}: failed to publish alert: {}", name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::platform_and_client;
+    use crate::test_support::wait_for_subscriber;
+    use crate::AttributeMetadata;
+    use crate::AttributeMode;
+
+    fn metadata(topic: &str, r#type: &str) -> AttributeMetadata {
+        AttributeMetadata::from_topic(
+            topic.to_string(),
+            Some(r#type.to_string()),
+            AttributeMode::WriteOnly,
+        )
+    }
+
+    fn number(value: f64) -> zenoh::bytes::ZBytes {
+        NumberBuffer::builder()
+            .with_value(value)
+            .with_source(0)
+            .with_random_sequence()
+            .build()
+            .unwrap()
+            .to_zbytes()
+    }
+
+    async fn next(events: &mut Receiver<AlarmEvent>) -> AlarmEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("no alarm event")
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn alarms_are_raised_cleared_and_published() {
+        let (platform, client, _) = platform_and_client().await;
+        let notifications = platform
+            .declare_subscriber("pza/_/notifications/att")
+            .await
+            .unwrap();
+
+        let voltage =
+            NumberAttribute::new(client.clone(), metadata("pza/test/watch/voltage", "number"))
+                .await
+                .unwrap();
+        let ready =
+            BooleanAttribute::new(client.clone(), metadata("pza/test/watch/ready", "boolean"))
+                .await
+                .unwrap();
+
+        let (watch, mut events) = Watch::new("bench");
+        let (connections, _) = watch::channel(Connection::new(client.clone()));
+        watch.publish_alerts(connections.subscribe());
+        assert!(matches!(
+            watch
+                .watch_boolean("ready", &ready, WatchCondition::max(1.0))
+                .await,
+            Err(WatchError::Unsupported { .. })
+        ));
+        let overvoltage = watch
+            .watch_number(
+                "overvoltage",
+                &voltage,
+                WatchCondition::range(0.0, 5.0).with_hysteresis(0.5),
+            )
+            .await
+            .unwrap();
+        wait_for_subscriber(&platform, "pza/test/watch/voltage/att").await;
+        wait_for_subscriber(&platform, "pza/test/watch/ready/att").await;
+        wait_for_subscriber(&client, "pza/_/notifications/att").await;
+
+        // Bounds with hysteresis
+        platform
+            .put("pza/test/watch/voltage/att", number(6.0))
+            .await
+            .unwrap();
+        let event = next(&mut events).await;
+        assert_eq!(event.id, overvoltage);
+        assert_eq!(event.state, AlarmState::Raised);
+        assert!(matches!(event.reason, AlarmReason::High { .. }));
+        assert_eq!(watch.active_alarms(), vec![event.clone()]);
+
+        let sample = tokio::time::timeout(Duration::from_secs(5), notifications.recv_async())
+            .await
+            .unwrap()
+            .unwrap();
+        let alert = NotificationBuffer::from_zbytes(sample.payload().clone());
+        assert_eq!(alert.notification_type(), Some(NotificationType::Alert));
+        assert_eq!(alert.source_str(), Some("bench"));
+        assert_eq!(alert.message_str(), Some(event.message().as_str()));
+
+        platform
+            .put("pza/test/watch/voltage/att", number(4.8))
+            .await
+            .unwrap();
+        platform
+            .put("pza/test/watch/voltage/att", number(4.0))
+            .await
+            .unwrap();
+        let event = next(&mut events).await;
+        assert_eq!(event.state, AlarmState::Cleared);
+        assert!(watch.active_alarms().is_empty());
+
+        // Staleness
+        let stale = watch
+            .watch_boolean(
+                "ready-stale",
+                &ready,
+                WatchCondition::stale_after(Duration::from_millis(300)),
+            )
+            .await
+            .unwrap();
+        let event = next(&mut events).await;
+        assert_eq!((event.id, event.state), (stale, AlarmState::Raised));
+        assert_eq!(event.reason, AlarmReason::Stale(Duration::from_millis(300)));
+        platform
+            .put(
+                "pza/test/watch/ready/att",
+                BooleanBuffer::builder()
+                    .with_value(true)
+                    .with_source(0)
+                    .with_random_sequence()
+                    .build()
+                    .unwrap()
+                    .to_zbytes(),
+            )
+            .await
+            .unwrap();
+        let event = next(&mut events).await;
+        assert_eq!((event.id, event.state), (stale, AlarmState::Cleared));
+
+        assert!(watch.unwatch(stale));
+        assert!(!watch.unwatch(stale));
+    }
+
+    #[tokio::test]
+    async fn full_event_channel_does_not_stop_evaluation() {
+        let (watch, mut events) = Watch::new("bench");
+        let id = watch.add_rule(
+            "overvoltage".to_string(),
+            "pza/test/watch/voltage".to_string(),
+            WatchCondition::range(0.0, 1.0),
+        );
+
+        // Raise and clear the alarm more often than the channel can hold
+        let changes = EVENT_CHANNEL_CAPACITY + 10;
+        for change in 0..changes {
+            let value = if change % 2 == 0 { 2.0 } else { 0.5 };
+            let _ = watch
+                .commands
+                .send(Command::Reading(id, Reading::Number(value)));
+        }
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while watch.dropped_events() < 10 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("events not dropped");
+        assert_eq!(watch.dropped_events(), 10);
+        assert!(watch.active_alarms().is_empty());
+
+        // The oldest events are kept
+        assert_eq!(next(&mut events).await.state, AlarmState::Raised);
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+/// Condition attached to an attribute by a [Watch](super::Watch)
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchCondition {
+    /// Number that must stay within optional bounds
+    ///
+    /// The alarm is raised when the value crosses a bound and cleared once it
+    /// is back inside by at least `hysteresis`.
+    Bounds {
+        low: Option<f64>,
+        high: Option<f64>,
+        hysteresis: f64,
+    },
+
+    /// Boolean that must stay true, the alarm is raised while it is false
+    MustStayTrue,
+
+    /// Raised when no sample is received for the given duration
+    Stale(Duration),
+}
+
+impl WatchCondition {
+    /// Number that must not exceed `high`
+    ///
+    pub fn max(high: f64) -> Self {
+        Self::Bounds {
+            low: None,
+            high: Some(high),
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Number that must not go under `low`
+    ///
+    pub fn min(low: f64) -> Self {
+        Self::Bounds {
+            low: Some(low),
+            high: None,
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Number that must stay between `low` and `high`
+    ///
+    pub fn range(low: f64, high: f64) -> Self {
+        Self::Bounds {
+            low: Some(low),
+            high: Some(high),
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Boolean that must stay true
+    ///
+    pub fn must_stay_true() -> Self {
+        Self::MustStayTrue
+    }
+
+    /// No sample received for `timeout`
+    ///
+    pub fn stale_after(timeout: Duration) -> Self {
+        Self::Stale(timeout)
+    }
+
+    /// Sets the margin a value must be back inside its bounds by to clear the
+    /// alarm, ignored by the other conditions
+    ///
+    pub fn with_hysteresis(mut self, margin: f64) -> Self {
+        if let Self::Bounds { hysteresis, .. } = &mut self {
+            *hysteresis = margin.abs();
+        }
+        self
+    }
+}
+
+/// Why an alarm is raised
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlarmReason {
+    /// Value above the high bound
+    High { value: f64, limit: f64 },
+    /// Value under the low bound
+    Low { value: f64, limit: f64 },
+    /// Boolean value false
+    NotTrue,
+    /// No sample for the given duration
+    Stale(Duration),
+}
+
+impl std::fmt::Display for AlarmReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlarmReason::High { value, limit } => write!(f, "value {} above {}", value, limit),
+            AlarmReason::Low { value, limit } => write!(f, "value {} under {}", value, limit),
+            AlarmReason::NotTrue => write!(f, "value is not true"),
+            AlarmReason::Stale(timeout) => write!(f, "no update for {:?}", timeout),
+        }
+    }
+}
+
+/// Value of a sample received by a watch
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Reading {
+    Number(f64),
+    Boolean(bool),
+}
+
+/// Change of the alarm state of a condition
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Transition {
+    Raised(AlarmReason),
+    Cleared(AlarmReason),
+}
+
+/// Alarm state of a condition
+///
+#[derive(Debug)]
+pub(crate) struct Evaluator {
+    condition: WatchCondition,
+    active: Option<AlarmReason>,
+}
+
+impl Evaluator {
+    pub(crate) fn new(condition: WatchCondition) -> Self {
+        Self {
+            condition,
+            active: None,
+        }
+    }
+
+    /// Reason of the active alarm, if any
+    ///
+    pub(crate) fn active(&self) -> Option<AlarmReason> {
+        self.active
+    }
+
+    /// Timeout of a staleness condition
+    ///
+    pub(crate) fn stale_timeout(&self) -> Option<Duration> {
+        match self.condition {
+            WatchCondition::Stale(timeout) => Some(timeout),
+            _ => None,
+        }
+    }
+
+    /// Evaluate a new sample
+    ///
+    /// # Returns
+    /// The state changes, a value jumping from one bound to the other clears
+    /// the first alarm and raises the second one
+    pub(crate) fn on_reading(&mut self, reading: Reading) -> Vec<Transition> {
+        match (self.condition, reading) {
+            (
+                WatchCondition::Bounds {
+                    low,
+                    high,
+                    hysteresis,
+                },
+                Reading::Number(value),
+            ) => {
+                let violation = match (low, high) {
+                    (_, Some(limit)) if value > limit => Some(AlarmReason::High { value, limit }),
+                    (Some(limit), _) if value < limit => Some(AlarmReason::Low { value, limit }),
+                    _ => None,
+                };
+                let back_inside = |reason: &AlarmReason| match reason {
+                    AlarmReason::High { limit, .. } => value <= limit - hysteresis,
+                    AlarmReason::Low { limit, .. } => value >= limit + hysteresis,
+                    _ => true,
+                };
+                let mut transitions = Vec::new();
+                match (self.active, violation) {
+                    (None, Some(reason)) => transitions.push(self.raise(reason)),
+                    (Some(active), Some(reason))
+                        if std::mem::discriminant(&active) != std::mem::discriminant(&reason) =>
+                    {
+                        transitions.extend(self.clear());
+                        transitions.push(self.raise(reason));
+                    }
+                    (Some(active), None) if back_inside(&active) => {
+                        transitions.extend(self.clear())
+                    }
+                    _ => {}
+                }
+                transitions
+            }
+            (WatchCondition::MustStayTrue, Reading::Boolean(false)) if self.active.is_none() => {
+                vec![self.raise(AlarmReason::NotTrue)]
+            }
+            (WatchCondition::MustStayTrue, Reading::Boolean(true)) => {
+                self.clear().into_iter().collect()
+            }
+            // Any sample refreshes a staleness condition
+            (WatchCondition::Stale(_), _) => self.clear().into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Evaluate the time elapsed since the last sample
+    ///
+    pub(crate) fn on_silence(&mut self, elapsed: Duration) -> Option<Transition> {
+        match self.condition {
+            WatchCondition::Stale(timeout) if elapsed >= timeout && self.active.is_none() => {
+                Some(self.raise(AlarmReason::Stale(timeout)))
+            }
+            _ => None,
+        }
+    }
+
+    fn raise(&mut self, reason: AlarmReason) -> Transition {
+        self.active = Some(reason);
+        Transition::Raised(reason)
+    }
+
+    fn clear(&mut self) -> Option<Transition> {
+        self.active.take().map(Transition::Cleared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_with_hysteresis() {
+        let mut evaluator = Evaluator::new(WatchCondition::range(0.0, 5.0).with_hysteresis(0.5));
+        assert!(evaluator.on_reading(Reading::Number(4.9)).is_empty());
+        assert_eq!(
+            evaluator.on_reading(Reading::Number(5.2)),
+            vec![Transition::Raised(AlarmReason::High {
+                value: 5.2,
+                limit: 5.0
+            })]
+        );
+        // Still raised until back under 4.5
+        assert!(evaluator.on_reading(Reading::Number(5.4)).is_empty());
+        assert!(evaluator.on_reading(Reading::Number(4.7)).is_empty());
+        assert!(matches!(
+            evaluator.on_reading(Reading::Number(4.4))[..],
+            [Transition::Cleared(AlarmReason::High { .. })]
+        ));
+
+        // From one bound to the other
+        evaluator.on_reading(Reading::Number(6.0));
+        assert!(matches!(
+            evaluator.on_reading(Reading::Number(-1.0))[..],
+            [
+                Transition::Cleared(AlarmReason::High { .. }),
+                Transition::Raised(AlarmReason::Low { .. })
+            ]
+        ));
+        assert!(evaluator.active().is_some());
+    }
+
+    #[test]
+    fn must_stay_true_and_staleness() {
+        let mut evaluator = Evaluator::new(WatchCondition::must_stay_true());
+        assert!(evaluator.on_reading(Reading::Boolean(true)).is_empty());
+        assert_eq!(
+            evaluator.on_reading(Reading::Boolean(false)),
+            vec![Transition::Raised(AlarmReason::NotTrue)]
+        );
+        assert!(evaluator.on_reading(Reading::Boolean(false)).is_empty());
+        assert_eq!(
+            evaluator.on_reading(Reading::Boolean(true)),
+            vec![Transition::Cleared(AlarmReason::NotTrue)]
+        );
+
+        let timeout = Duration::from_secs(2);
+        let mut evaluator = Evaluator::new(WatchCondition::stale_after(timeout));
+        assert_eq!(evaluator.stale_timeout(), Some(timeout));
+        assert_eq!(evaluator.on_silence(Duration::from_secs(1)), None);
+        assert_eq!(
+            evaluator.on_silence(timeout),
+            Some(Transition::Raised(AlarmReason::Stale(timeout)))
+        );
+        assert_eq!(evaluator.on_silence(Duration::from_secs(3)), None);
+        assert_eq!(
+            evaluator.on_reading(Reading::Number(1.0)),
+            vec![Transition::Cleared(AlarmReason::Stale(timeout))]
+        );
+    }
+}
@@ -11,7 +11,7 @@ use panduza::security::utils::ensure_panduza_programdata_dirs;
 ///
 #[when(expr = "I try to set rw boolean to {boolean}")]
 async fn i_try_to_set_rw_boolean(world: &mut SecurityWorld, value: Boolean) {
-    let session = world.r.as_mut().unwrap().session.clone();
+    let session = world.r.as_mut().unwrap().current_session();
     let val = value.into_bool();
     let a = session.put("pza/tester/boolean/rw", vec![val as u8]).await.unwrap();
 }
@@ -36,11 +36,11 @@ async fn i_try_to_set_rw_boolean(world: &mut SecurityWorld, value: Boolean) {
 #[when(expr = "I toglle rw boolean")]
 async fn i_toglle_rw_boolean(world: &mut SecurityWorld) {
 
-    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs();
+    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs().expect("panduza data directories");
     let root_cert_dir_display = root_cert_dir.display().to_string().replace("\\", "/");
     let root_ca_certificate = format!("{}/root_ca_certificate.pem", root_cert_dir_display);
 
-    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs();
+    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs().expect("panduza user directories");
     
     let cert_filename = "writer_certificate.pem";
     let key_filename = "writer_private_key.pem";
@@ -95,7 +95,7 @@ async fn i_toglle_rw_boolean(world: &mut SecurityWorld) {
 ///
 #[then(expr = "I receive ten messages")]
 async fn i_receive_messages(world: &mut SecurityWorld) {
-    let subscriber = world.r.as_mut().unwrap().session.declare_subscriber("pza/tester/boolean/rw").await.unwrap();
+    let subscriber = world.r.as_mut().unwrap().current_session().declare_subscriber("pza/tester/boolean/rw").await.unwrap();
     let mut count = 0;
     while let Ok(sample) = subscriber.recv_async().await {
         count += 1;
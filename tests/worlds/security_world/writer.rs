@@ -26,11 +26,11 @@ async fn given_the_json_structure_attribute(world: &mut SecurityWorld) {
 async fn i_modify_structure_attribute(world: &mut SecurityWorld) {
     let value = serde_json::to_string(&"test").unwrap();
 
-    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs();
+    let (root_key_dir, root_cert_dir) = ensure_panduza_programdata_dirs().expect("panduza data directories");
     let root_cert_dir_display = root_cert_dir.display().to_string().replace("\\", "/");
     let root_ca_certificate = format!("{}/root_ca_certificate.pem", root_cert_dir_display);
 
-    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs();
+    let (user_key_dir, user_cert_dir) = ensure_panduza_dirs().expect("panduza user directories");
 
     let cert_filename = "writer_certificate.pem";
     let key_filename = "writer_private_key.pem";
@@ -109,7 +109,7 @@ async fn the_structure_attribute_is_not_modified(world: &mut SecurityWorld) {
         .r
         .as_mut()
         .unwrap()
-        .session
+        .current_session()
         .get("pza/_/structure/att")
         .await
         .unwrap();